@@ -1,36 +1,447 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use probe_rs::MemoryInterface;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
-use tauri::{AppHandle, Emitter, Manager};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
 use tauri::menu::{MenuBuilder, SubmenuBuilder};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
 
+/// Next `LogEntry::id`. Only taken under `EMIT_LOCK` (or by the headless sink)
 static SEQ: AtomicU64 = AtomicU64::new(0);
 
+/// Held while an entry gets its id and is sent as `rtt-log`, so ids reach the
+/// webview in order. Guards the count of filtered entries since the last emit.
+static EMIT_LOCK: Mutex<u32> = Mutex::new(0);
+
+// ── Errors ──
+
+/// Error returned from commands. Serialized as `{ kind, message }` so the
+/// frontend can react to `kind` and still show `message` as-is.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "message")]
+enum AppError {
+    NoProbe(String),
+    ProbeInUse(String),
+    UnknownChip(String),
+    RttNotFound(String),
+    NotConnected(String),
+    InvalidInput(String),
+    /// Read-back protected (nRF APPROTECT); only `recover_target` gets past it
+    Protected(String),
+    Elf(String),
+    Io(String),
+    /// Superseded or cancelled by the user — not worth showing
+    Cancelled(String),
+    Other(String),
+}
+
+impl AppError {
+    fn message(&self) -> &str {
+        match self {
+            Self::NoProbe(m)
+            | Self::ProbeInUse(m)
+            | Self::UnknownChip(m)
+            | Self::RttNotFound(m)
+            | Self::NotConnected(m)
+            | Self::InvalidInput(m)
+            | Self::Protected(m)
+            | Self::Elf(m)
+            | Self::Io(m)
+            | Self::Cancelled(m)
+            | Self::Other(m) => m,
+        }
+    }
+
+    /// Errors that retrying the connection won't fix
+    fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            Self::UnknownChip(_) | Self::InvalidInput(_) | Self::Protected(_)
+        )
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
 struct AppState {
     stop_flag: Arc<AtomicBool>,
+    emit_filter: Mutex<EmitFilter>,
+    /// True while the RTT thread is alive (including its reconnect backoff)
+    rtt_running: AtomicBool,
+    /// Profile of the running RTT session; `restart_rtt` swaps it in place
+    active_profile: Mutex<Option<Profile>>,
+    /// Set by `restart_rtt`: re-read parser settings from `active_profile`
+    hot_reload: AtomicBool,
+    /// Set by `restart_rtt`: drop the session and attach again with `active_profile`
+    reattach: AtomicBool,
+    /// Watches the active profile's ELF while `watch_elf` is set; dropping it stops the watch
+    elf_watcher: Mutex<Option<notify::RecommendedWatcher>>,
+    /// Bumped by every ELF pick and by `cancel_elf_parse`; a parse holding an older value stops
+    elf_parse_gen: AtomicU64,
+    /// `list_probes` capability results keyed by "vid:pid:serial"
+    probe_caps: Mutex<HashMap<String, ProbeCaps>>,
+    /// Key and capabilities of the probe the RTT session holds open, which
+    /// `list_probes` must not open again
+    session_probe: Mutex<Option<(String, ProbeCaps)>>,
+    /// Session requested on the command line, until the frontend takes it
+    startup: Mutex<Option<CliArgs>>,
+    /// Set while `start_server` is running
+    ws_server: Mutex<Option<WsServer>>,
+    /// Set while `log_to_syslog` is forwarding
+    syslog: Mutex<Option<SyslogSink>>,
+    /// Set while `log_to_mqtt` is publishing
+    mqtt: Mutex<Option<MqttSink>>,
+    /// Commands for the running RTT thread; `None` when no session is up
+    rtt_commands: Mutex<Option<std::sync::mpsc::Sender<RttCommand>>>,
+    /// Clock behind `display_time`, deltas and burst markers
+    time_base: Mutex<TimeBase>,
+    /// Everything sent on `rtt-log`, for `search_logs`
+    search_index: Mutex<SearchIndex>,
+    /// Live recording requested by `start_rtt`'s `log_to_sqlite`
+    sqlite_log: Mutex<Option<SqliteLog>>,
+    /// Failed reads in a row before the RTT/SWO loop gives up on the connection
+    error_threshold: AtomicU32,
+    /// Last entry id `append_export` wrote, per file path
+    append_exports: Mutex<HashMap<String, u64>>,
+    /// See `set_read_mode`
+    read_mode: Mutex<ReadMode>,
+    /// See `set_emit_batching`
+    emit_batching: Mutex<EmitBatching>,
+    /// Live filtered streams from `create_view`, matched on every emit
+    views: Mutex<Vec<LogView>>,
+    /// Rolling window of recent entries while `arm_flight_recorder` is on
+    flight_recorder: Mutex<Option<FlightRecorder>>,
+    /// Step durations of the RTT session's latest successful connect
+    connect_timing: Mutex<Option<ConnectTiming>>,
+    /// Latest connection failure with its cause chain, for `last_error_detail`
+    last_error: Mutex<Vec<String>>,
+    /// Where status lines go, see `StatusRouting`
+    status_routing: Mutex<StatusRouting>,
+    /// Control blocks of the running session, for `list_control_blocks`
+    control_blocks: Mutex<Vec<ControlBlockInfo>>,
+    /// Register map from `load_svd`
+    svd: Mutex<Option<Arc<SvdDevice>>>,
+}
+
+/// Emit-time filters, applied in `process_bytes` before entries cross IPC.
+/// `None` lets everything through.
+#[derive(Default)]
+struct EmitFilter {
+    terminals: Option<HashSet<u8>>,
+    channels: Option<HashSet<usize>>,
+    levels: Option<HashSet<String>>,
+    excluded_tags: HashSet<String>,
+}
+
+impl EmitFilter {
+    fn allows(&self, entry: &LogEntry) -> bool {
+        let terminal_ok = self
+            .terminals
+            .as_ref()
+            .is_none_or(|set| entry.terminal.is_none_or(|t| set.contains(&t)));
+        let channel_ok = self
+            .channels
+            .as_ref()
+            .is_none_or(|set| entry.channel.is_none_or(|c| set.contains(&c)));
+        let level_ok = self
+            .levels
+            .as_ref()
+            .is_none_or(|set| set.contains(&entry.level));
+        let tag_ok = entry
+            .tag
+            .as_ref()
+            .is_none_or(|t| !self.excluded_tags.contains(t));
+        terminal_ok && channel_ok && level_ok && tag_ok
+    }
+
+    fn from_preset(filter: &LogFilter) -> Self {
+        fn set<T: Clone + Eq + std::hash::Hash>(items: &[T]) -> Option<HashSet<T>> {
+            (!items.is_empty()).then(|| items.iter().cloned().collect())
+        }
+        Self {
+            terminals: set(&filter.terminals),
+            channels: set(&filter.channels),
+            levels: set(&filter.levels),
+            excluded_tags: filter.excluded_tags.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Which clock `display_time`, `delta_us` and burst detection use. Lines without
+/// a device timestamp always fall back to host time.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum TimeBase {
+    #[default]
+    Device,
+    Host,
+}
+
+/// How the read loops wait when a read comes back empty.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum ReadMode {
+    /// Sleep `POLL_INTERVAL` between empty reads
+    #[default]
+    Poll,
+    /// Wait inside the read for up to `BLOCK_TIMEOUT` where the source can
+    /// (SWO, named pipes). probe-rs has no blocking RTT read, so RTT falls
+    /// back to an `IdleBackoff`: short sleeps right after data, growing to the
+    /// poll interval while the channel stays quiet.
+    Block,
+}
+
+/// Idle sleep in poll mode
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+/// Longest wait inside a blocking read, so stop requests and stats still come through
+const BLOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(100);
+/// First idle sleep after data in block mode
+const BACKOFF_MIN: std::time::Duration = std::time::Duration::from_micros(500);
+
+/// Idle wait for a source that can't block: sleeps `BACKOFF_MIN` after data
+/// and doubles up to `POLL_INTERVAL`, so the next line of a burst is picked
+/// up quickly without spinning on a quiet channel.
+struct IdleBackoff {
+    next: std::time::Duration,
+}
+
+impl Default for IdleBackoff {
+    fn default() -> Self {
+        Self { next: BACKOFF_MIN }
+    }
+}
+
+impl IdleBackoff {
+    /// Call after every read pass.
+    fn pace(&mut self, mode: ReadMode, got_data: bool) {
+        if let Some(idle) = self.idle(mode, got_data) {
+            std::thread::sleep(idle);
+        }
+    }
+
+    /// How long to sleep after a read pass, if at all.
+    fn idle(&mut self, mode: ReadMode, got_data: bool) -> Option<std::time::Duration> {
+        if got_data {
+            self.next = BACKOFF_MIN;
+            return None;
+        }
+        match mode {
+            ReadMode::Poll => Some(POLL_INTERVAL),
+            ReadMode::Block => {
+                let idle = self.next;
+                self.next = (self.next * 2).min(POLL_INTERVAL);
+                Some(idle)
+            }
+        }
+    }
+}
+
+/// Throughput since the session started, emitted as `rtt-stats` once per second.
+#[derive(Debug, Clone, Default, Serialize)]
+struct RttStats {
+    bytes: u64,
+    lines: u64,
+    /// Lines parsed but dropped by the emit-time filter
+    filtered: u64,
+    /// Lines per second over the last interval
+    line_rate: u64,
+    /// Advisory: how often the UI should render new lines, 0 = as they arrive
+    refresh_ms: u32,
+    /// Advisory: render early once this many lines are waiting
+    batch_size: u32,
+    #[serde(skip)]
+    lines_at_tick: u64,
+    /// Up buffer fill as of this emit; empty when the source has no control block
+    channel_fill: Vec<ChannelFill>,
+}
+
+/// (lines per second above which, suggested render interval in ms), highest first
+const REFRESH_STEPS: &[(u64, u32)] = &[(10_000, 250), (1_000, 100), (100, 33)];
+const DEFAULT_EMIT_BATCH_SIZE: u32 = 2000;
+const MAX_EMIT_BATCH_MS: u32 = 1000;
+const MAX_EMIT_BATCH_SIZE: u32 = 100_000;
+
+/// How the UI is told to coalesce rendering, see `set_emit_batching`.
+#[derive(Debug, Clone, Copy, Serialize)]
+struct EmitBatching {
+    /// Fixed render interval; None picks one from the line rate (`REFRESH_STEPS`)
+    #[serde(rename = "batch_ms")]
+    ms: Option<u32>,
+    /// Render as soon as this many lines are waiting, interval or not
+    #[serde(rename = "batch_size")]
+    size: u32,
+}
+
+impl Default for EmitBatching {
+    fn default() -> Self {
+        Self {
+            ms: None,
+            size: DEFAULT_EMIT_BATCH_SIZE,
+        }
+    }
+}
+
+impl RttStats {
+    /// Update the rate and refresh hint; call once per `rtt-stats` emit.
+    fn tick(&mut self, elapsed: std::time::Duration, batching: EmitBatching) {
+        let secs = elapsed.as_secs_f64().max(0.001);
+        self.line_rate = ((self.lines - self.lines_at_tick) as f64 / secs) as u64;
+        self.lines_at_tick = self.lines;
+        self.refresh_ms = batching.ms.unwrap_or_else(|| {
+            REFRESH_STEPS
+                .iter()
+                .find(|(rate, _)| self.line_rate > *rate)
+                .map_or(0, |(_, ms)| *ms)
+        });
+        self.batch_size = batching.size;
+    }
+}
+
+/// Viewer-side instrumentation for "the UI is lagging" reports: tells reads,
+/// parsing and IPC apart. Relaxed atomics only, so it's always on.
+struct Perf {
+    bytes_read: AtomicU64,
+    lines_parsed: AtomicU64,
+    parse_ns: AtomicU64,
+    events_emitted: AtomicU64,
+    emit_failures: AtomicU64,
+    reconnects: AtomicU64,
+}
+
+static PERF: Perf = Perf {
+    bytes_read: AtomicU64::new(0),
+    lines_parsed: AtomicU64::new(0),
+    parse_ns: AtomicU64::new(0),
+    events_emitted: AtomicU64::new(0),
+    emit_failures: AtomicU64::new(0),
+    reconnects: AtomicU64::new(0),
+};
+
+#[derive(Debug, Clone, Serialize)]
+struct PerfCounters {
+    bytes_read: u64,
+    lines_parsed: u64,
+    events_emitted: u64,
+    emit_failures: u64,
+    /// Mean `parse_line` time per line, in nanoseconds
+    avg_parse_ns: u64,
+    reconnects: u64,
+}
+
+impl Perf {
+    fn snapshot(&self) -> PerfCounters {
+        let lines = self.lines_parsed.load(Ordering::Relaxed);
+        PerfCounters {
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            lines_parsed: lines,
+            events_emitted: self.events_emitted.load(Ordering::Relaxed),
+            emit_failures: self.emit_failures.load(Ordering::Relaxed),
+            avg_parse_ns: self.parse_ns.load(Ordering::Relaxed) / lines.max(1),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+        }
+    }
+
+    fn reset(&self) {
+        for counter in [
+            &self.bytes_read,
+            &self.lines_parsed,
+            &self.parse_ns,
+            &self.events_emitted,
+            &self.emit_failures,
+            &self.reconnects,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
 struct ElfInfo {
     rtt_address: String,
+    /// Further `_SEGGER_RTT` symbols at other addresses (e.g. a secure and a
+    /// non-secure image linked together), candidates for `Profile.extra_rtt_blocks`
+    extra_rtt_addresses: Vec<String>,
     chip_hint: Option<String>,
+    /// RAM region the control block lives in, when the source lists one
+    ram_region: Option<RamRegion>,
 }
 
 #[derive(Debug, Clone, Serialize)]
+struct RamRegion {
+    /// Hex start address like "0x20000000"
+    start: String,
+    /// Hex length like "0x00040000"
+    size: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 struct LogEntry {
+    /// Assigned on emit, one after another across every source. Entries the
+    /// emit filter drops use up ids too and are counted in `dropped_before`, so
+    /// `id - prev.id - 1 > dropped_before` means `rtt-log` events were lost.
     id: u64,
+    /// Entries dropped by the emit filter since the previous `rtt-log`
+    dropped_before: u32,
     device_timestamp: Option<String>,
     level: String,
     tag: Option<String>,
+    /// Stable palette index derived from `tag`, so a module keeps its color across sessions
+    tag_color: Option<u8>,
     terminal: Option<u8>,
+    /// `terminal`'s label from `Profile.terminal_names`
+    terminal_name: Option<String>,
+    /// RTT up channel the line was read from
+    channel: Option<usize>,
     message: String,
     raw: String,
+    /// Space-separated hex of the original line bytes (only with `Profile.include_hex`)
+    raw_hex: Option<String>,
+    /// The exact bytes of one read, base64 (only on `Profile.base64_channels`)
+    raw_b64: Option<String>,
+    /// When the host received the line, milliseconds since the Unix epoch
+    host_timestamp: Option<u64>,
+    /// `host_timestamp` as RFC 3339 UTC, for display. Wall-clock time can jump
+    /// (NTP, DST changes elsewhere); use `host_monotonic_ns` to measure.
+    host_wallclock: Option<String>,
+    /// Nanoseconds since the session started, on a clock that never jumps
+    host_monotonic_ns: Option<u64>,
+    /// First line after an idle gap longer than the profile's burst threshold
+    burst_start: bool,
+    /// Microseconds on the session time base: device uptime, or host epoch time
+    /// with the "host" base or when the line has no device timestamp
+    display_time: Option<u64>,
+    /// Microseconds since the previous line, on the same clock
+    delta_us: Option<u64>,
+    /// "app" or "net" when a session reads both nRF5340 cores, or the control
+    /// block's label when it reads `Profile.extra_rtt_blocks`
+    core_name: Option<String>,
+    /// Unparsed lines folded into this error entry (`Profile.merge_continuations`)
+    continuation_lines: u32,
+    /// Logged before we attached, replayed from `Profile.backlog_symbol`
+    backlog: bool,
+    /// `key=value` pairs found in `message` (`Profile.parse_kv`)
+    fields: HashMap<String, String>,
+    /// Probably the tail of a line printed before we attached
+    /// (`Profile.keep_partial_first_line`)
+    partial: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct Profile {
     name: String,
     chip: String,
@@ -40,28 +451,219 @@ struct Profile {
     elf_path: Option<String>,
     /// Core index (0 = app core, 1 = net core on nRF5340)
     core: Option<usize>,
+    /// Attach the hex dump of each raw line to its entry (doubles payload size)
+    #[serde(default)]
+    include_hex: bool,
+    /// DAP access port for the selected core, for multi-AP parts where the default AP is wrong
+    access_port: Option<u8>,
+    /// Re-extract the RTT address whenever the ELF is rebuilt
+    #[serde(default)]
+    watch_elf: bool,
+    /// Control block ID the firmware uses, if not the default "SEGGER RTT"
+    rtt_id: Option<String>,
+    /// probe-rs target description (YAML) for chips missing from the built-in registry
+    target_yaml: Option<String>,
+    /// Idle gap that makes the next line start a new burst (default 1000 ms)
+    burst_gap_ms: Option<u64>,
+    /// nRF5340 only: also read the network core's RTT in the same session
+    #[serde(default)]
+    both_cores: bool,
+    /// Repeat the tag prefix in `message` instead of leaving only the text after it
+    #[serde(default)]
+    keep_tag_in_message: bool,
+    /// Fold unparsed lines that follow an error (backtraces, register dumps) into that entry
+    #[serde(default)]
+    merge_continuations: bool,
+    /// Up channels to show as hex rows instead of text, on top of the ones
+    /// recognised by name (`is_binary_channel`)
+    #[serde(default)]
+    binary_channels: Vec<usize>,
+    /// Up channels passed through untouched: one entry per read carrying the
+    /// exact bytes in `raw_b64`, for frontends that decode the data themselves
+    #[serde(default)]
+    base64_channels: Vec<usize>,
+    /// Text encoding of log lines: "utf8" (default), "latin1", "ascii", or
+    /// another WHATWG label such as "windows-1251"
+    encoding: Option<String>,
+    /// Char array in RAM where the firmware keeps recent log text, replayed on
+    /// connect (needs `elf_path`). See `read_backlog` for the expected layout.
+    backlog_symbol: Option<String>,
+    /// Extract `key=value` / `key="quoted value"` pairs from messages into `fields`
+    #[serde(default)]
+    parse_kv: bool,
+    /// SWJ sequence played before attaching, for targets that don't answer the
+    /// standard JTAG-to-SWD switch. See `ConnectSequence` for the names.
+    connect_sequence: Option<String>,
+    /// Warn when more than this percent of text bytes in a second are
+    /// unprintable or undecodable (default 5, see `CorruptionCheck`)
+    corruption_warn_percent: Option<u8>,
+    /// Turn the corruption warning off, e.g. for firmware that logs raw control bytes
+    #[serde(default)]
+    skip_corruption_check: bool,
+    /// Folder the export dialogs open in
+    export_dir: Option<String>,
+    /// Export file name without extension; `{profile}`, `{chip}`, `{date}` and
+    /// `{time}` are filled in (default `DEFAULT_EXPORT_TEMPLATE`)
+    export_filename_template: Option<String>,
+    /// Keep an unparsed first line after attach (marked `partial`) instead of
+    /// dropping it as the tail of a line printed before we connected
+    #[serde(default)]
+    keep_partial_first_line: bool,
+    /// Emit blank lines as empty "raw" entries instead of dropping them, for
+    /// firmware that spaces out tables and sections
+    #[serde(default)]
+    keep_blank_lines: bool,
+    /// SWD/JTAG clock; the probe's default when unset
+    speed_khz: Option<u32>,
+    /// When reads fail right after attach, reconnect at half the clock until
+    /// they work (down to `AUTO_TUNE_MIN_SPEED_KHZ`) and report the speed
+    #[serde(default)]
+    auto_tune_speed: bool,
+    /// Labels for SEGGER virtual terminals 0-15, e.g. {0: "main", 1: "trace"},
+    /// copied into each entry's `terminal_name`
+    terminal_names: Option<HashMap<u8, String>>,
+    /// Connect through the probe with this serial number instead of the one
+    /// picked in the toolbar
+    probe_serial: Option<String>,
+    /// While that probe is unplugged, reconnect the moment it is listed again
+    /// instead of on the next retry tick (board swaps on a bench)
+    #[serde(default)]
+    watch_probe_serial: bool,
+    /// Decompressor per up channel: "none", "lz4" or "heatshrink[:W,L]".
+    /// See `ChannelCodec` for the framing the firmware must use.
+    #[serde(default)]
+    channel_codec: HashMap<usize, String>,
+    /// Stop the session after this many seconds without RTT data, freeing the
+    /// probe on shared benches. Reconnect attempts don't count as data.
+    idle_timeout_s: Option<u64>,
+    /// Order and byte budget of up-channel reads: "round_robin", "priority" or
+    /// "fair". See `ReadStrategy`.
+    channel_read_strategy: Option<String>,
+    /// More control blocks on the same core, label -> hex address, read
+    /// alongside the one at `rtt_address` (which is labelled "main" then).
+    /// For firmware that logs from a secure and a non-secure image.
+    #[serde(default)]
+    extra_rtt_blocks: BTreeMap<String, String>,
+    /// How long to keep looking for a control block the firmware hasn't set up
+    /// yet, before the slow retry. Default `DEFAULT_RTT_INIT_GRACE_MS`; 0 turns it off.
+    rtt_init_grace_ms: Option<u64>,
+    /// Hide the session's info-level connection progress ("Target attached...",
+    /// "RTT connected!") from the status bar and the log. Warnings and errors
+    /// still show.
+    #[serde(default)]
+    quiet_connect: bool,
+    /// Log the chip's factory unique ID on connect, see `read_device_id`
+    #[serde(default)]
+    show_device_id: bool,
 }
 
 #[tauri::command]
-async fn list_probes() -> Result<Vec<ProbeInfo>, String> {
-    let lister = probe_rs::probe::list::Lister::new();
-    let probes = lister.list_all();
+async fn list_probes(app: AppHandle) -> Result<Vec<ProbeInfo>, AppError> {
+    let probes = get_lister().list_all();
+    let state = app.state::<AppState>();
+    let mut cache = state.probe_caps.lock().unwrap();
+    let session_probe = state.session_probe.lock().unwrap().clone();
     Ok(probes
         .iter()
         .enumerate()
-        .map(|(i, p)| ProbeInfo {
-            index: i,
-            name: p.identifier.clone(),
-            serial: p.serial_number.clone(),
+        .map(|(i, p)| {
+            let key = probe_key(p);
+            let caps = match cache.get(&key) {
+                Some(caps) => Some(caps.clone()),
+                // Opening it again would fail, or worse, disturb the session
+                None if session_probe.as_ref().is_some_and(|(k, _)| *k == key) => {
+                    session_probe.as_ref().map(|(_, caps)| caps.clone())
+                }
+                // Not cached on failure: the probe may just be busy right now
+                None => query_probe_caps(p).inspect(|caps| {
+                    cache.insert(key, caps.clone());
+                }),
+            };
+            ProbeInfo {
+                index: i,
+                name: p.identifier.clone(),
+                serial: p.serial_number.clone(),
+                caps,
+            }
         })
         .collect())
 }
 
+/// Identifies a probe across enumerations: "vid:pid:serial"
+fn probe_key(info: &probe_rs::probe::DebugProbeInfo) -> String {
+    format!(
+        "{:04x}:{:04x}:{}",
+        info.vendor_id,
+        info.product_id,
+        info.serial_number.as_deref().unwrap_or("")
+    )
+}
+
+/// What `list_probes` reports for the probe the session holds: the protocol
+/// and clock in use rather than everything it accepts, as probing those
+/// would reconfigure it under the session.
+fn session_probe_caps(probe: &mut probe_rs::probe::Probe) -> ProbeCaps {
+    ProbeCaps {
+        protocols: probe
+            .protocol()
+            .map(|p| p.to_string())
+            .into_iter()
+            .collect(),
+        max_speed_khz: Some(probe.speed_khz()),
+        swo: probe.get_swo_interface().is_some(),
+        target_power: probe.try_into_jlink().is_ok(),
+    }
+}
+
+/// Auto-tune gives up halving the clock below this
+const AUTO_TUNE_MIN_SPEED_KHZ: u32 = 100;
+
+/// Speeds tried from the top down to find the fastest one the probe accepts
+const PROBE_SPEED_LADDER_KHZ: [u32; 7] = [50_000, 24_000, 12_000, 8_000, 4_000, 1_000, 100];
+
+/// Briefly open the probe to see what it can do. Fails if another program
+/// holds it; `list_probes` never calls it for our own session's probe.
+fn query_probe_caps(info: &probe_rs::probe::DebugProbeInfo) -> Option<ProbeCaps> {
+    use probe_rs::probe::WireProtocol;
+
+    let mut probe = info.open().ok()?;
+    let protocols = [WireProtocol::Swd, WireProtocol::Jtag]
+        .into_iter()
+        .filter(|&proto| probe.select_protocol(proto).is_ok())
+        .map(|proto| proto.to_string())
+        .collect();
+    // CMSIS-DAP takes any speed and clamps in firmware, so this is an upper bound there
+    let max_speed_khz = PROBE_SPEED_LADDER_KHZ
+        .iter()
+        .find_map(|&khz| probe.set_speed(khz).ok());
+    let swo = probe.get_swo_interface().is_some();
+    let target_power = probe.try_into_jlink().is_ok();
+
+    Some(ProbeCaps {
+        protocols,
+        max_speed_khz,
+        swo,
+        target_power,
+    })
+}
+
 #[derive(Debug, Clone, Serialize)]
 struct ProbeInfo {
     index: usize,
     name: String,
     serial: Option<String>,
+    /// `None` if the probe couldn't be opened to query it
+    caps: Option<ProbeCaps>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ProbeCaps {
+    /// Wire protocols the probe accepted, e.g. ["SWD", "JTAG"]
+    protocols: Vec<String>,
+    max_speed_khz: Option<u32>,
+    swo: bool,
+    /// Can supply power to the target (J-Link kickstart)
+    target_power: bool,
 }
 
 // Initialize the probe lister
@@ -80,47 +682,200 @@ fn get_lister() -> &'static probe_rs::probe::list::Lister {
         .0
 }
 
-fn parse_line(raw: &str) -> LogEntry {
-    let clean = raw.trim();
+// Line formats `parse_line` recognises, tried in this order
+
+/// Zephyr: "[00:00:01.234,567] <inf> tag: msg"
+static ZEPHYR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[(\d{2}:\d{2}:\d{2}\.\d{3}(?:,\d{3})?)\]\s*<(\w+)>\s*([\w._-]+):\s*(.*)$")
+        .unwrap()
+});
+/// "[tag] <level> msg"
+static GENERIC_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[([^\]]+)\]\s*<(\w+)>\s*(.*)$").unwrap());
+/// `log` crate, env_logger style: "[0.123 INFO app::net] msg", with an
+/// uptime, a wall-clock time or no time at all
+static LOG_CRATE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[(?:(\S+)\s+)?(TRACE|DEBUG|INFO|WARN|ERROR)\s+([\w:.-]+)\]\s?(.*)$").unwrap()
+});
+/// defmt-print and embassy/RTIC examples: "0.002685 INFO  msg" (the
+/// "└─ module @ file:line" after it stays a line of its own)
+static DEFMT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d+\.\d+)\s+(TRACE|DEBUG|INFO|WARN|ERROR)\s+(.*)$").unwrap());
+/// rtt-logger and esp-println: "INFO - msg"; the empty group stands in for
+/// the time so it reads the same as `DEFMT_RE`
+static DASH_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^()(TRACE|DEBUG|INFO|WARN|ERROR)\s+-\s+(.*)$").unwrap());
 
-    let zephyr_re =
-        Regex::new(r"^\[(\d{2}:\d{2}:\d{2}\.\d{3}(?:,\d{3})?)\]\s*<(\w+)>\s*([\w._-]+):\s*(.*)$")
-            .unwrap();
+/// `raw` is always the line as received; `keep_tag_in_message` only decides
+/// whether `message` repeats the tag prefix ("tag: msg") or holds just the text.
+fn parse_line(raw: &str, keep_tag_in_message: bool) -> LogEntry {
+    let clean = raw.trim();
 
-    if let Some(caps) = zephyr_re.captures(clean) {
+    if let Some(caps) = ZEPHYR_RE.captures(clean) {
+        let message = if keep_tag_in_message {
+            // Straight from the line, so it matches the original text exactly
+            clean[caps.get(3).map_or(0, |m| m.start())..].to_string()
+        } else {
+            caps[4].to_string()
+        };
         return LogEntry {
-            id: SEQ.fetch_add(1, Ordering::Relaxed),
             device_timestamp: Some(caps[1].to_string()),
             level: normalize_level(&caps[2]),
             tag: Some(caps[3].to_string()),
-            terminal: None,
-            message: caps[4].to_string(),
+            tag_color: Some(tag_color_index(&caps[3])),
+            message,
             raw: clean.to_string(),
+            ..Default::default()
         };
     }
 
-    let generic_re = Regex::new(r"^\[([^\]]+)\]\s*<(\w+)>\s*(.*)$").unwrap();
-    if let Some(caps) = generic_re.captures(clean) {
+    if let Some(caps) = GENERIC_RE.captures(clean) {
+        let message = if keep_tag_in_message {
+            format!("[{}] {}", &caps[1], &caps[3])
+        } else {
+            caps[3].to_string()
+        };
         return LogEntry {
-            id: SEQ.fetch_add(1, Ordering::Relaxed),
             device_timestamp: None,
             level: normalize_level(&caps[2]),
             tag: Some(caps[1].to_string()),
-            terminal: None,
+            tag_color: Some(tag_color_index(&caps[1])),
+            message,
+            raw: clean.to_string(),
+            ..Default::default()
+        };
+    }
+
+    if let Some(caps) = LOG_CRATE_RE.captures(clean) {
+        let message = if keep_tag_in_message {
+            format!("[{}] {}", &caps[3], &caps[4])
+        } else {
+            caps[4].to_string()
+        };
+        return LogEntry {
+            device_timestamp: caps.get(1).and_then(|m| uptime_timestamp(m.as_str())),
+            level: normalize_level(&caps[2]),
+            tag: Some(caps[3].to_string()),
+            tag_color: Some(tag_color_index(&caps[3])),
+            message,
+            raw: clean.to_string(),
+            ..Default::default()
+        };
+    }
+
+    if let Some(caps) = DEFMT_RE.captures(clean).or_else(|| DASH_RE.captures(clean)) {
+        return LogEntry {
+            device_timestamp: uptime_timestamp(&caps[1]),
+            level: normalize_level(&caps[2]),
             message: caps[3].to_string(),
             raw: clean.to_string(),
+            ..Default::default()
         };
     }
 
     LogEntry {
-        id: SEQ.fetch_add(1, Ordering::Relaxed),
         device_timestamp: None,
         level: "raw".to_string(),
-        tag: None,
-        terminal: None,
         message: clean.to_string(),
         raw: clean.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Seconds of uptime ("12.345678") as a Zephyr-style "00:00:12.345,678", so
+/// Rust loggers' timestamps work like Zephyr's. None for anything else, e.g.
+/// a wall-clock time.
+fn uptime_timestamp(secs: &str) -> Option<String> {
+    let (whole, frac) = secs.split_once('.')?;
+    let whole: u64 = whole.parse().ok()?;
+    if frac.is_empty() || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let us: u64 = format!("{frac:0<6}")[..6].parse().ok()?;
+    Some(format!(
+        "{:02}:{:02}:{:02}.{:03},{:03}",
+        whole / 3600,
+        whole / 60 % 60,
+        whole % 60,
+        us / 1000,
+        us % 1000
+    ))
+}
+
+/// `key=value` pairs in a message, e.g. `temp=42 state="low power"`. Keys
+/// start a word and are letters, digits, `_`, `.` or `-`. Values run to the
+/// next whitespace, or are quoted with `"` or `'`; a backslash escapes the
+/// next character (`\n`, `\t` and `\"` inside quotes). Later keys win.
+fn parse_kv_fields(message: &str) -> HashMap<String, String> {
+    let chars: Vec<char> = message.chars().collect();
+    let mut fields = HashMap::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let starts_word = i == 0 || chars[i - 1].is_whitespace() || chars[i - 1] == ',';
+        if !starts_word || !(chars[i].is_alphabetic() || chars[i] == '_') {
+            i += 1;
+            continue;
+        }
+        let key_start = i;
+        while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '.' | '-'))
+        {
+            i += 1;
+        }
+        if chars.get(i) != Some(&'=') {
+            continue;
+        }
+        let key: String = chars[key_start..i].iter().collect();
+        i += 1;
+
+        let mut value = String::new();
+        match chars.get(i) {
+            Some(&quote @ ('"' | '\'')) => {
+                i += 1;
+                while i < chars.len() && chars[i] != quote {
+                    if chars[i] == '\\' && i + 1 < chars.len() {
+                        i += 1;
+                        value.push(match chars[i] {
+                            'n' => '\n',
+                            't' => '\t',
+                            c => c,
+                        });
+                    } else {
+                        value.push(chars[i]);
+                    }
+                    i += 1;
+                }
+                // Closing quote
+                i += 1;
+            }
+            _ => {
+                while i < chars.len() && !chars[i].is_whitespace() {
+                    if chars[i] == '\\' && i + 1 < chars.len() {
+                        i += 1;
+                    }
+                    value.push(chars[i]);
+                    i += 1;
+                }
+                // "a=1, b=2"
+                if value.ends_with(',') {
+                    value.pop();
+                }
+            }
+        }
+        fields.insert(key, value);
     }
+    fields
+}
+
+/// Number of entries in the frontend tag palette (`TAG_COLORS` in log-engine.js).
+const TAG_PALETTE_SIZE: u32 = 10;
+
+/// FNV-1a over the tag name. Unlike `DefaultHasher` this is stable across
+/// Rust releases, so a tag maps to the same color in every session.
+fn tag_color_index(tag: &str) -> u8 {
+    let hash = tag.bytes().fold(0x811c_9dc5u32, |h, b| {
+        (h ^ b as u32).wrapping_mul(0x0100_0193)
+    });
+    (hash % TAG_PALETTE_SIZE) as u8
 }
 
 fn normalize_level(s: &str) -> String {
@@ -128,7 +883,8 @@ fn normalize_level(s: &str) -> String {
         "err" | "error" => "error",
         "wrn" | "warn" | "warning" => "warn",
         "inf" | "info" => "info",
-        "dbg" | "debug" => "debug",
+        // No level of its own in the viewer
+        "dbg" | "debug" | "trc" | "trace" => "debug",
         _ => "info",
     }
     .to_string()
@@ -142,634 +898,11749 @@ fn config_dir() -> std::path::PathBuf {
         .join("rtt-viewer")
 }
 
-fn load_profiles() -> Vec<Profile> {
-    let path = config_dir().join("profiles.json");
-    if let Ok(data) = std::fs::read_to_string(&path) {
-        serde_json::from_str(&data).unwrap_or_default()
-    } else {
-        vec![]
-    }
+/// Write `value` as `name` in the config directory. Written to a temporary
+/// file and renamed over the old one, so a crash mid-write never leaves half
+/// a file.
+fn save_config_json<T: Serialize + ?Sized>(name: &str, value: &T) -> Result<(), AppError> {
+    let dir = config_dir();
+    let path = dir.join(name);
+    let tmp = dir.join(format!("{name}.tmp"));
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::Other(format!("Failed to serialize {name}: {e}")))?;
+    std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(&tmp, json))
+        .and_then(|_| std::fs::rename(&tmp, &path))
+        .map_err(|e| AppError::Io(format!("Failed to save {name}: {e}")))
 }
 
-fn save_profiles_to_disk(profiles: &[Profile]) {
-    let dir = config_dir();
-    let _ = std::fs::create_dir_all(&dir);
-    let path = dir.join("profiles.json");
-    if let Ok(json) = serde_json::to_string_pretty(profiles) {
-        let _ = std::fs::write(path, json);
-    }
+/// Named emit-time filters, in `filter_presets.json` next to the profiles.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FilterPresets {
+    /// Applied at startup, until another preset is applied or filters are cleared
+    active: Option<String>,
+    #[serde(default)]
+    presets: BTreeMap<String, LogFilter>,
 }
 
-// ── Tauri commands ──
+/// One preset. Empty lists let everything through.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LogFilter {
+    /// Levels to keep ("error", "warn", "info", "debug", "raw")
+    #[serde(default)]
+    levels: Vec<String>,
+    #[serde(default)]
+    excluded_tags: Vec<String>,
+    #[serde(default)]
+    terminals: Vec<u8>,
+    #[serde(default)]
+    channels: Vec<usize>,
+}
 
-#[tauri::command]
-async fn get_profiles() -> Result<Vec<Profile>, String> {
-    Ok(load_profiles())
+impl FilterPresets {
+    fn active_filter(&self) -> Option<&LogFilter> {
+        self.presets.get(self.active.as_ref()?)
+    }
 }
 
-#[tauri::command]
-async fn save_profile(profile: Profile) -> Result<Vec<Profile>, String> {
-    let mut profiles = load_profiles();
-    if let Some(existing) = profiles.iter_mut().find(|p| p.name == profile.name) {
-        *existing = profile;
+fn load_filter_presets() -> FilterPresets {
+    let path = config_dir().join("filter_presets.json");
+    if let Ok(data) = std::fs::read_to_string(&path) {
+        serde_json::from_str(&data).unwrap_or_default()
     } else {
-        profiles.push(profile);
+        FilterPresets::default()
     }
-    save_profiles_to_disk(&profiles);
-    Ok(profiles)
 }
 
-#[tauri::command]
-async fn delete_profile(name: String) -> Result<Vec<Profile>, String> {
-    let mut profiles = load_profiles();
-    profiles.retain(|p| p.name != name);
-    save_profiles_to_disk(&profiles);
-    Ok(profiles)
+fn save_filter_presets_to_disk(presets: &FilterPresets) -> Result<(), AppError> {
+    save_config_json("filter_presets.json", presets)
 }
 
-#[tauri::command]
-async fn stop_source(app: AppHandle) -> Result<String, String> {
-    let state = app.state::<AppState>();
-    state.stop_flag.store(true, Ordering::Relaxed);
-    let _ = app.emit("rtt-stopped", ());
-    Ok("Stopped".to_string())
+fn load_profiles() -> Vec<Profile> {
+    let path = config_dir().join("profiles.json");
+    if let Ok(data) = std::fs::read_to_string(&path) {
+        serde_json::from_str(&data).unwrap_or_default()
+    } else {
+        vec![]
+    }
 }
 
-fn emit_rtt_status(app: &AppHandle, level: &str, msg: &str) {
-    let _ = app.emit(
-        "rtt-log",
-        &LogEntry {
-            id: SEQ.fetch_add(1, Ordering::Relaxed),
-            device_timestamp: None,
-            level: level.to_string(),
-            tag: Some("rtt".to_string()),
-            terminal: None,
-            message: msg.to_string(),
-            raw: msg.to_string(),
-        },
-    );
+fn save_profiles_to_disk(profiles: &[Profile]) -> Result<(), AppError> {
+    save_config_json("profiles.json", profiles)
 }
 
-// ── Parse RTT address from optional hex string ──
+/// Starter profiles for common boards, compiled into the binary
+const EXAMPLE_PROFILES_JSON: &str = include_str!("example_profiles.json");
 
-fn parse_scan_region(addr: &Option<String>) -> probe_rs::rtt::ScanRegion {
-    match addr {
-        Some(s) => {
-            let addr = u64::from_str_radix(s.trim_start_matches("0x"), 16).unwrap_or(0);
-            probe_rs::rtt::ScanRegion::Exact(addr)
-        }
-        None => probe_rs::rtt::ScanRegion::Ram,
-    }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExampleProfile {
+    board: String,
+    description: String,
+    profile: Profile,
 }
 
-// ── Open probe + attach session + core ──
-
-struct RttSession {
-    rtt: probe_rs::rtt::Rtt,
-    core: probe_rs::Core<'static>, // lifetime managed by caller
+fn example_profiles() -> Vec<ExampleProfile> {
+    serde_json::from_str(EXAMPLE_PROFILES_JSON).expect("example_profiles.json is valid")
 }
 
-enum ConnectError {
-    Retry(String), // transient — retry after delay
-    Fatal(String), // permanent — stop trying
+const MAX_RECENT_ELFS: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecentElf {
+    path: String,
+    /// File modification time, seconds since the Unix epoch
+    mtime: Option<u64>,
 }
 
-fn open_session(
-    lister: &probe_rs::probe::list::Lister,
-    chip: &str,
-    core_idx: usize,
-    app: &AppHandle,
-) -> Result<(probe_rs::Session, usize), ConnectError> {
-    emit_rtt_status(app, "info", "Searching for debug probe...");
+fn file_mtime(path: &std::path::Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    modified
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
 
-    let probes = lister.list_all();
-    if probes.is_empty() {
-        return Err(ConnectError::Retry("No debug probe found".into()));
+fn load_recent_elfs() -> Vec<RecentElf> {
+    let path = config_dir().join("recent_elfs.json");
+    if let Ok(data) = std::fs::read_to_string(&path) {
+        serde_json::from_str(&data).unwrap_or_default()
+    } else {
+        vec![]
     }
+}
 
-    let probe = probes[0]
-        .open()
-        .map_err(|e| ConnectError::Retry(format!("Failed to open probe: {e}")))?;
+fn save_recent_elfs(recent: &[RecentElf]) -> Result<(), AppError> {
+    save_config_json("recent_elfs.json", recent)
+}
 
-    emit_rtt_status(app, "info", "Probe found. Attaching to target...");
+/// Move `elf_path` to the front of the recent list, keeping at most `MAX_RECENT_ELFS`.
+fn remember_elf(elf_path: &str) -> Result<(), AppError> {
+    let mut recent = load_recent_elfs();
+    recent.retain(|r| r.path != elf_path);
+    recent.insert(
+        0,
+        RecentElf {
+            path: elf_path.to_string(),
+            mtime: file_mtime(std::path::Path::new(elf_path)),
+        },
+    );
+    recent.truncate(MAX_RECENT_ELFS);
+    save_recent_elfs(&recent)
+}
 
-    let target = probe_rs::config::get_target_by_name(chip)
-        .map_err(|e| ConnectError::Fatal(format!("Unknown chip '{chip}': {e}")))?;
+/// Frontend selection and window geometry restored on the next launch.
+/// Every field defaults, so a file from an older or newer build still loads.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct UiState {
+    /// Source running at exit: "rtt", "mock" or "swo"
+    last_source: Option<String>,
+    profile: Option<String>,
+    probe_serial: Option<String>,
+    enabled_levels: Option<Vec<String>>,
+    excluded_tags: Vec<String>,
+    search_mode: Option<String>,
+    /// "device" or "host", see `set_time_base`
+    time_base: Option<String>,
+    /// Exports and copies: "lf", "crlf" or "native"
+    line_ending: Option<String>,
+    /// Exports and copies escape non-ASCII text, see `AsciiEscape`
+    ascii_only: bool,
+    /// See `set_error_threshold`
+    error_threshold: Option<u32>,
+    /// "poll" or "block", see `set_read_mode`
+    read_mode: Option<String>,
+    /// See `set_status_in_log`
+    status_in_log: bool,
+    /// See `set_emit_batching`; None for automatic
+    emit_batch_ms: Option<u32>,
+    emit_batch_size: Option<u32>,
+    /// Written by the backend on close, never by the frontend
+    window: Option<WindowGeometry>,
+    /// Fields this build doesn't know about, kept so a downgrade doesn't drop them
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
 
-    let session = probe
-        .attach(target, probe_rs::Permissions::default())
-        .map_err(|e| ConnectError::Retry(format!("Cannot attach (device off?): {e}")))?;
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct WindowGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    maximized: bool,
+}
 
-    Ok((session, core_idx))
+fn load_ui_state_from_disk() -> UiState {
+    let path = config_dir().join("ui_state.json");
+    if let Ok(data) = std::fs::read_to_string(&path) {
+        serde_json::from_str(&data).unwrap_or_default()
+    } else {
+        UiState::default()
+    }
 }
 
-fn attach_rtt(
-    session: &mut probe_rs::Session,
-    core_idx: usize,
-    scan_region: &probe_rs::rtt::ScanRegion,
-    app: &AppHandle,
-) -> Result<probe_rs::rtt::Rtt, ConnectError> {
-    let mut core = session
-        .core(core_idx)
-        .map_err(|e| ConnectError::Retry(format!("Cannot access core {core_idx}: {e}")))?;
+fn save_ui_state_to_disk(ui: &UiState) -> Result<(), AppError> {
+    save_config_json("ui_state.json", ui)
+}
 
-    emit_rtt_status(
-        app,
-        "info",
-        "Target attached. Searching for RTT control block...",
-    );
+fn save_window_geometry(window: &tauri::Window) -> Result<(), AppError> {
+    let (Ok(pos), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+        return Ok(());
+    };
+    let mut ui = load_ui_state_from_disk();
+    let maximized = window.is_maximized().unwrap_or(false);
+    ui.window = Some(match (maximized, ui.window) {
+        // Keep the restored size so un-maximizing next launch looks right
+        (true, Some(prev)) => WindowGeometry { maximized, ..prev },
+        _ => WindowGeometry {
+            x: pos.x,
+            y: pos.y,
+            width: size.width,
+            height: size.height,
+            maximized,
+        },
+    });
+    save_ui_state_to_disk(&ui)
+}
 
-    let mut rtt = probe_rs::rtt::Rtt::attach_region(&mut core, scan_region)
-        .map_err(|e| ConnectError::Retry(format!("RTT not found (fw not running?): {e}")))?;
+fn restore_window_geometry(window: &tauri::WebviewWindow) {
+    let Some(geo) = load_ui_state_from_disk().window else {
+        return;
+    };
+    if geo.width == 0 || geo.height == 0 {
+        return;
+    }
+    let _ = window.set_size(tauri::PhysicalSize::new(geo.width, geo.height));
+    let _ = window.set_position(tauri::PhysicalPosition::new(geo.x, geo.y));
+    if geo.maximized {
+        let _ = window.maximize();
+    }
+}
 
-    let ch_count = rtt.up_channels().len();
-    emit_rtt_status(
-        app,
-        "info",
-        &format!("RTT connected! {ch_count} up channel(s) found."),
-    );
-    let _ = app.emit("rtt-connected", ());
+// ── Tauri commands ──
 
-    Ok(rtt)
+#[tauri::command]
+async fn load_ui_state() -> Result<UiState, AppError> {
+    Ok(load_ui_state_from_disk())
 }
 
-// ── Process raw RTT bytes into log entries ──
-
-struct RttParser {
-    line_buf: String,
-    current_terminal: u8,
+#[tauri::command]
+async fn save_ui_state(ui: UiState) -> Result<(), AppError> {
+    // The window geometry is backend-owned; the frontend never sends it
+    let window = load_ui_state_from_disk().window;
+    save_ui_state_to_disk(&UiState { window, ..ui })
+}
+
+#[tauri::command]
+async fn recent_elfs() -> Result<Vec<RecentElf>, AppError> {
+    let stored = load_recent_elfs();
+    let recent: Vec<RecentElf> = stored
+        .iter()
+        .filter(|r| std::path::Path::new(&r.path).is_file())
+        .map(|r| RecentElf {
+            path: r.path.clone(),
+            mtime: file_mtime(std::path::Path::new(&r.path)),
+        })
+        .collect();
+    if recent.len() != stored.len() {
+        save_recent_elfs(&recent)?;
+    }
+    Ok(recent)
+}
+
+#[tauri::command]
+async fn get_profiles() -> Result<Vec<Profile>, AppError> {
+    Ok(load_profiles())
+}
+
+#[tauri::command]
+async fn save_profile(profile: Profile) -> Result<Vec<Profile>, AppError> {
+    if let Some(addr) = &profile.rtt_address {
+        validate_rtt_address_str(addr).map_err(AppError::InvalidInput)?;
+    }
+    validate_both_cores(&profile)?;
+    if let Some(name) = &profile.encoding {
+        TextEncoding::parse(name).map_err(AppError::InvalidInput)?;
+    }
+    if let Some(name) = &profile.connect_sequence {
+        ConnectSequence::parse(name)?;
+    }
+    if let Some(percent @ (0 | 101..)) = profile.corruption_warn_percent {
+        return Err(AppError::InvalidInput(format!(
+            "Corruption warning threshold must be 1-100%, got {percent}"
+        )));
+    }
+    for (&terminal, name) in profile.terminal_names.iter().flatten() {
+        if terminal > 15 {
+            return Err(AppError::InvalidInput(format!(
+                "Terminal {terminal} doesn't exist; RTT has terminals 0-15"
+            )));
+        }
+        if name.trim().is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "Terminal {terminal} needs a name"
+            )));
+        }
+    }
+    for name in profile.channel_codec.values() {
+        ChannelCodec::parse(name).map_err(AppError::InvalidInput)?;
+    }
+    if profile.watch_probe_serial && profile.probe_serial.is_none() {
+        return Err(AppError::InvalidInput(
+            "Watching for the probe needs a probe serial".to_string(),
+        ));
+    }
+    if let Some(name) = &profile.channel_read_strategy {
+        ReadStrategy::parse(name)?;
+    }
+    validate_extra_rtt_blocks(&profile)?;
+    if profile
+        .rtt_init_grace_ms
+        .is_some_and(|ms| ms > MAX_RTT_INIT_GRACE_MS)
+    {
+        return Err(AppError::InvalidInput(format!(
+            "RTT init grace period can be at most {MAX_RTT_INIT_GRACE_MS} ms"
+        )));
+    }
+    if profile.idle_timeout_s == Some(0) {
+        return Err(AppError::InvalidInput(
+            "Idle timeout must be at least 1 s".to_string(),
+        ));
+    }
+    if profile.speed_khz == Some(0) {
+        return Err(AppError::InvalidInput(
+            "Probe speed must be at least 1 kHz".to_string(),
+        ));
+    }
+    if let Some(path) = &profile.target_yaml {
+        register_target_yaml(path, &profile.chip)?;
+    }
+    let mut profiles = load_profiles();
+    if let Some(existing) = profiles.iter_mut().find(|p| p.name == profile.name) {
+        *existing = profile;
+    } else {
+        profiles.push(profile);
+    }
+    save_profiles_to_disk(&profiles)?;
+    Ok(profiles)
+}
+
+#[tauri::command]
+async fn list_example_profiles() -> Result<Vec<ExampleProfile>, AppError> {
+    Ok(example_profiles())
+}
+
+/// Copy a bundled example into the user's profiles. Refuses to replace a
+/// profile of the same name, so edits to an earlier copy are kept.
+#[tauri::command]
+async fn install_example_profile(name: String) -> Result<Vec<Profile>, AppError> {
+    let example = example_profiles()
+        .into_iter()
+        .find(|e| e.profile.name == name)
+        .ok_or_else(|| AppError::InvalidInput(format!("No example profile named '{name}'")))?;
+    let mut profiles = load_profiles();
+    if profiles.iter().any(|p| p.name == name) {
+        return Err(AppError::InvalidInput(format!(
+            "A profile named '{name}' already exists"
+        )));
+    }
+    profiles.push(example.profile);
+    save_profiles_to_disk(&profiles)?;
+    Ok(profiles)
+}
+
+/// A name for a profile-to-be: trimmed, non-empty and not taken.
+fn new_profile_name(profiles: &[Profile], name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("Profile name is empty".to_string()));
+    }
+    if profiles.iter().any(|p| p.name == name) {
+        return Err(AppError::InvalidInput(format!(
+            "A profile named '{name}' already exists"
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// Rename `old` in place. Returns the new name, or None when it is the same.
+fn rename_in(profiles: &mut [Profile], old: &str, new: &str) -> Result<Option<String>, AppError> {
+    if new.trim() == old {
+        return Ok(None);
+    }
+    let new = new_profile_name(profiles, new)?;
+    let profile = profiles
+        .iter_mut()
+        .find(|p| p.name == old)
+        .ok_or_else(|| AppError::InvalidInput(format!("No profile named '{old}'")))?;
+    profile.name = new.clone();
+    Ok(Some(new))
+}
+
+/// Rename in place, keeping the profile's position and every other field. A
+/// running session's profile, and the one selected at the next launch, are
+/// renamed too.
+#[tauri::command]
+async fn rename_profile(
+    app: AppHandle,
+    old: String,
+    new: String,
+) -> Result<Vec<Profile>, AppError> {
+    let mut profiles = load_profiles();
+    let Some(new) = rename_in(&mut profiles, &old, &new)? else {
+        return Ok(profiles);
+    };
+    save_profiles_to_disk(&profiles)?;
+    let mut ui = load_ui_state_from_disk();
+    if ui.profile.as_deref() == Some(old.as_str()) {
+        ui.profile = Some(new.clone());
+        save_ui_state_to_disk(&ui)?;
+    }
+    if let Some(active) = app
+        .state::<AppState>()
+        .active_profile
+        .lock()
+        .unwrap()
+        .as_mut()
+        .filter(|p| p.name == old)
+    {
+        active.name = new;
+    }
+    Ok(profiles)
+}
+
+/// Copy every field of `name` into a new profile, added after it.
+#[tauri::command]
+async fn duplicate_profile(name: String, new_name: String) -> Result<Vec<Profile>, AppError> {
+    let mut profiles = load_profiles();
+    let new_name = new_profile_name(&profiles, &new_name)?;
+    let pos = profiles
+        .iter()
+        .position(|p| p.name == name)
+        .ok_or_else(|| AppError::InvalidInput(format!("No profile named '{name}'")))?;
+    let copy = Profile {
+        name: new_name,
+        ..profiles[pos].clone()
+    };
+    profiles.insert(pos + 1, copy);
+    save_profiles_to_disk(&profiles)?;
+    Ok(profiles)
+}
+
+#[tauri::command]
+async fn delete_profile(name: String) -> Result<Vec<Profile>, AppError> {
+    let mut profiles = load_profiles();
+    profiles.retain(|p| p.name != name);
+    save_profiles_to_disk(&profiles)?;
+    Ok(profiles)
+}
+
+/// Only emit entries from these virtual terminals. An empty list clears the filter.
+#[tauri::command]
+async fn set_terminal_filter(app: AppHandle, terminals: Vec<u8>) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    state.emit_filter.lock().unwrap().terminals = if terminals.is_empty() {
+        None
+    } else {
+        Some(terminals.into_iter().collect())
+    };
+    Ok(())
+}
+
+/// Only emit entries from these up channels. An empty list clears the filter.
+#[tauri::command]
+async fn set_channel_filter(app: AppHandle, channels: Vec<usize>) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    state.emit_filter.lock().unwrap().channels = if channels.is_empty() {
+        None
+    } else {
+        Some(channels.into_iter().collect())
+    };
+    Ok(())
+}
+
+const FILTER_LEVELS: &[&str] = &["error", "warn", "info", "debug", "raw"];
+
+/// Save (or replace) a named filter preset. Doesn't apply it.
+#[tauri::command]
+async fn save_filter_preset(name: String, filter: LogFilter) -> Result<FilterPresets, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Preset name can't be empty".to_string(),
+        ));
+    }
+    if let Some(level) = filter
+        .levels
+        .iter()
+        .find(|l| !FILTER_LEVELS.contains(&l.as_str()))
+    {
+        return Err(AppError::InvalidInput(format!("Unknown level: {level}")));
+    }
+    let mut presets = load_filter_presets();
+    presets.presets.insert(name.to_string(), filter);
+    save_filter_presets_to_disk(&presets)?;
+    Ok(presets)
+}
+
+#[tauri::command]
+async fn list_filter_presets() -> Result<FilterPresets, AppError> {
+    Ok(load_filter_presets())
+}
+
+/// Replace every emit-time filter (levels, tags, terminals, channels) with
+/// the named preset and remember it for the next start. No name clears them.
+#[tauri::command]
+async fn apply_filter_preset(app: AppHandle, name: Option<String>) -> Result<LogFilter, AppError> {
+    let mut presets = load_filter_presets();
+    let filter =
+        match &name {
+            Some(name) => presets.presets.get(name).cloned().ok_or_else(|| {
+                AppError::InvalidInput(format!("No filter preset named '{name}'"))
+            })?,
+            None => LogFilter::default(),
+        };
+    *app.state::<AppState>().emit_filter.lock().unwrap() = EmitFilter::from_preset(&filter);
+    presets.active = name;
+    save_filter_presets_to_disk(&presets)?;
+    Ok(filter)
+}
+
+#[tauri::command]
+async fn delete_filter_preset(app: AppHandle, name: String) -> Result<FilterPresets, AppError> {
+    let mut presets = load_filter_presets();
+    presets.presets.remove(&name);
+    if presets.active.as_deref() == Some(name.as_str()) {
+        presets.active = None;
+        *app.state::<AppState>().emit_filter.lock().unwrap() = EmitFilter::default();
+    }
+    save_filter_presets_to_disk(&presets)?;
+    Ok(presets)
+}
+
+const DEFAULT_ERROR_THRESHOLD: u32 = 3;
+const MAX_ERROR_THRESHOLD: u32 = 100;
+
+/// How many failed reads in a row (about 100 ms apart) count as a lost
+/// connection. Higher rides out glitches on a flaky cable without a
+/// reconnect, but a real disconnect takes longer to notice. Applies live.
+#[tauri::command]
+async fn set_error_threshold(app: AppHandle, n: u32) -> Result<(), AppError> {
+    if !(1..=MAX_ERROR_THRESHOLD).contains(&n) {
+        return Err(AppError::InvalidInput(format!(
+            "Error threshold must be 1 to {MAX_ERROR_THRESHOLD}"
+        )));
+    }
+    app.state::<AppState>()
+        .error_threshold
+        .store(n, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Choose how the read loops wait for data ("poll" | "block", see `ReadMode`).
+/// Applies live to RTT, SWO and named-pipe sessions.
+#[tauri::command]
+async fn set_read_mode(app: AppHandle, mode: String) -> Result<(), AppError> {
+    let mode = match mode.as_str() {
+        "poll" => ReadMode::Poll,
+        "block" => ReadMode::Block,
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Unknown read mode: {mode} (expected poll or block)"
+            )))
+        }
+    };
+    *app.state::<AppState>().read_mode.lock().unwrap() = mode;
+    Ok(())
+}
+
+/// Tune how the UI coalesces lines under load, advised on every `rtt-stats`:
+/// render every `batch_ms` (0 = each line as it arrives; None = pick from the
+/// line rate) or as soon as `batch_size` lines are waiting. Lower for latency
+/// on a fast machine, higher for a slow webview under a flood. Applies live.
+/// Values are clamped to at most `MAX_EMIT_BATCH_MS` and 1 to
+/// `MAX_EMIT_BATCH_SIZE` lines; returns what was applied.
+#[tauri::command]
+async fn set_emit_batching(
+    app: AppHandle,
+    batch_ms: Option<u32>,
+    batch_size: u32,
+) -> Result<EmitBatching, AppError> {
+    let batching = EmitBatching {
+        ms: batch_ms.map(|ms| ms.min(MAX_EMIT_BATCH_MS)),
+        size: batch_size.clamp(1, MAX_EMIT_BATCH_SIZE),
+    };
+    *app.state::<AppState>().emit_batching.lock().unwrap() = batching;
+    Ok(batching)
+}
+
+/// Pick the clock ("device" | "host") behind `display_time`, deltas and burst
+/// markers. Applies to lines read from now on.
+#[tauri::command]
+async fn set_time_base(app: AppHandle, base: String) -> Result<(), AppError> {
+    let base = match base.as_str() {
+        "device" => TimeBase::Device,
+        "host" => TimeBase::Host,
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Unknown time base: {base} (expected \"device\" or \"host\")"
+            )))
+        }
+    };
+    *app.state::<AppState>().time_base.lock().unwrap() = base;
+    Ok(())
+}
+
+/// Also add status lines ("Target attached", "Connection lost") to the log,
+/// where exports and search see them. Off by default: they only go out on
+/// `rtt-status`. Applies to lines emitted from now on.
+#[tauri::command]
+async fn set_status_in_log(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    app.state::<AppState>()
+        .status_routing
+        .lock()
+        .unwrap()
+        .in_log = enabled;
+    Ok(())
+}
+
+/// How long quitting waits for the RTT thread to finish its last lines
+const SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// On quit: stop the source and wait (briefly) for the RTT thread to emit
+/// its partial lines and close the SQLite recording, so the tail is on disk.
+fn shutdown(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    state.stop_flag.store(true, Ordering::Relaxed);
+    let deadline = std::time::Instant::now() + SHUTDOWN_GRACE;
+    while state.rtt_running.load(Ordering::Relaxed) && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    // Stuck thread (probe hung mid-read): commit what we have anyway
+    state.sqlite_log.lock().unwrap().take();
+}
+
+#[tauri::command]
+async fn stop_source(app: AppHandle) -> Result<String, AppError> {
+    let state = app.state::<AppState>();
+    state.stop_flag.store(true, Ordering::Relaxed);
+    let _ = app.emit("rtt-stopped", ());
+    Ok("Stopped".to_string())
+}
+
+/// Give `entry` the next id and send it as `rtt-log`, to remote viewers and
+/// into the search index. Only the id and the `rtt-log` send happen under
+/// `EMIT_LOCK`: the webview's gap detection needs ids in order, while the
+/// index, views and forwarding sinks cope with entries from two sources
+/// overtaking each other, and shouldn't hold every source up behind them.
+fn emit_log(app: &AppHandle, entry: &mut LogEntry) -> tauri::Result<()> {
+    let sent = {
+        let mut dropped = EMIT_LOCK.lock().unwrap();
+        entry.id = SEQ.fetch_add(1, Ordering::Relaxed);
+        entry.dropped_before = std::mem::take(&mut *dropped);
+        app.emit("rtt-log", &*entry)
+    };
+    let entry = &*entry;
+    let state = app.state::<AppState>();
+    state.search_index.lock().unwrap().insert(entry);
+    tap_entry(app, entry);
+    if let Some(tx) = state.ws_server.lock().unwrap().as_ref().map(|ws| &ws.tx) {
+        if tx.receiver_count() > 0 {
+            if let Ok(json) = serde_json::to_string(entry) {
+                let _ = tx.send(json.into());
+            }
+        }
+    }
+    if let Some(syslog) = state.syslog.lock().unwrap().as_ref() {
+        syslog.send(entry);
+    }
+    if let Some(mqtt) = state.mqtt.lock().unwrap().as_ref() {
+        mqtt.send(entry);
+    }
+    sent
+}
+
+/// Live views and the flight recorder see every parsed entry, including
+/// those the emit filter keeps off `rtt-log`.
+fn tap_entry(app: &AppHandle, entry: &LogEntry) {
+    emit_to_views(app, entry);
+    let state = app.state::<AppState>();
+    let mut guard = state.flight_recorder.lock().unwrap();
+    let Some(recorder) = guard.as_mut() else {
+        return;
+    };
+    let Some(event) = recorder.record(entry) else {
+        return;
+    };
+    if let FlightRecorderEvent::Fired { .. } = event {
+        // Freeze on time even if nothing more arrives after the trigger
+        let (app, after) = (app.clone(), recorder.window / 4);
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(after).await;
+            let state = app.state::<AppState>();
+            let event = (state.flight_recorder.lock().unwrap().as_mut())
+                .and_then(|r| r.tick(std::time::Instant::now()));
+            if let Some(event) = event {
+                let _ = app.emit("flight-recorder", event);
+            }
+        });
+    }
+    drop(guard);
+    let _ = app.emit("flight-recorder", event);
+}
+
+/// An entry the emit filter dropped still uses up an id, so the next emitted
+/// entry's `dropped_before` accounts for the gap.
+fn skip_log_id(entry: &mut LogEntry) {
+    let mut dropped = EMIT_LOCK.lock().unwrap();
+    entry.id = SEQ.fetch_add(1, Ordering::Relaxed);
+    *dropped = dropped.saturating_add(1);
+}
+
+/// Payload of `rtt-status`: a message from the app itself ("Target attached",
+/// "Connection lost") rather than the device.
+#[derive(Clone, Serialize)]
+struct StatusMessage<'a> {
+    level: &'a str,
+    message: &'a str,
+    /// Unix ms
+    host_timestamp: u64,
+}
+
+/// Where one status line goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatusRoute {
+    Hidden,
+    /// `rtt-status` only
+    Bar,
+    /// `rtt-status` and an entry in the log
+    BarAndLog,
+}
+
+/// The settings that decide where status lines go, kept together so every
+/// line is routed by the same rule whichever thread sends it.
+#[derive(Debug, Clone, Copy, Default)]
+struct StatusRouting {
+    /// `set_status_in_log`
+    in_log: bool,
+    /// The running session's `Profile.quiet_connect`
+    quiet_connect: bool,
+}
+
+impl StatusRouting {
+    /// `connect` marks connection progress ("Target attached...").
+    fn route(&self, level: &str, connect: bool) -> StatusRoute {
+        if connect && level == "info" && self.quiet_connect {
+            StatusRoute::Hidden
+        } else if self.in_log {
+            StatusRoute::BarAndLog
+        } else {
+            StatusRoute::Bar
+        }
+    }
+}
+
+/// Status lines go out on `rtt-status` only, so exports, search and the
+/// recording hold device output. `set_status_in_log` also puts them in the
+/// log as entries tagged "rtt".
+fn emit_rtt_status(app: &AppHandle, level: &str, msg: &str) {
+    send_status(app, level, msg, false);
+}
+
+/// Connection progress, which `Profile.quiet_connect` can hide at info level.
+fn emit_connect_status(app: &AppHandle, level: &str, msg: &str) {
+    send_status(app, level, msg, true);
+}
+
+fn send_status(app: &AppHandle, level: &str, msg: &str, connect: bool) {
+    let route = app
+        .state::<AppState>()
+        .status_routing
+        .lock()
+        .unwrap()
+        .route(level, connect);
+    if route == StatusRoute::Hidden {
+        return;
+    }
+    let _ = app.emit(
+        "rtt-status",
+        StatusMessage {
+            level,
+            message: msg,
+            host_timestamp: host_now_ms(),
+        },
+    );
+    if route != StatusRoute::BarAndLog {
+        return;
+    }
+    let _ = emit_log(
+        app,
+        &mut LogEntry {
+            device_timestamp: None,
+            level: level.to_string(),
+            tag: Some("rtt".to_string()),
+            tag_color: Some(tag_color_index("rtt")),
+            message: msg.to_string(),
+            raw: msg.to_string(),
+            ..Default::default()
+        },
+    );
+}
+
+/// Drop a user note ("starting test case 3") into the log. Host-side only;
+/// it bypasses the emit filter so it always lands in-line and in exports.
+#[tauri::command]
+async fn insert_marker(app: AppHandle, text: String) -> Result<(), AppError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(AppError::InvalidInput("Marker text is empty".to_string()));
+    }
+    let now = host_now_ms();
+    let mut entry = LogEntry {
+        level: "marker".to_string(),
+        message: text.to_string(),
+        raw: text.to_string(),
+        host_timestamp: Some(now),
+        display_time: Some(now * 1000),
+        ..Default::default()
+    };
+    emit_log(&app, &mut entry).map_err(|e| AppError::Other(format!("Failed to emit marker: {e}")))
+}
+
+// ── Parse RTT address from optional hex string ──
+
+fn parse_hex_u64(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    u64::from_str_radix(digits, 16).ok()
+}
+
+/// Check a user-entered control block address. The message says what's wrong
+/// so it can go straight into the profile form.
+fn validate_rtt_address_str(s: &str) -> Result<u64, String> {
+    let trimmed = s.trim();
+    let digits = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    if digits.is_empty() {
+        return Err("RTT address is empty".to_string());
+    }
+    if let Some(bad) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "RTT address '{trimmed}' has non-hex character '{bad}'"
+        ));
+    }
+    let addr = u64::from_str_radix(digits, 16)
+        .map_err(|_| format!("RTT address '{trimmed}' is too large"))?;
+    if addr == 0 {
+        return Err("RTT address 0 is never valid — leave it empty to scan RAM".to_string());
+    }
+    if addr % 4 != 0 {
+        return Err(format!(
+            "RTT address {addr:#010X} is not word-aligned — the control block always is"
+        ));
+    }
+    Ok(addr)
+}
+
+#[tauri::command]
+async fn validate_rtt_address(address: String) -> Result<u64, AppError> {
+    validate_rtt_address_str(&address).map_err(AppError::InvalidInput)
+}
+
+fn parse_scan_region(addr: &Option<String>) -> Result<probe_rs::rtt::ScanRegion, AppError> {
+    match addr {
+        Some(s) => validate_rtt_address_str(s)
+            .map(probe_rs::rtt::ScanRegion::Exact)
+            .map_err(AppError::InvalidInput),
+        None => Ok(probe_rs::rtt::ScanRegion::Ram),
+    }
+}
+
+/// Load a custom target description into probe-rs's registry. A family with
+/// the same name is replaced, so registering the same file again is harmless.
+fn register_target_yaml(path: &str, chip: &str) -> Result<(), AppError> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| AppError::Io(format!("Cannot open target file {path}: {e}")))?;
+    let family = probe_rs::config::add_target_from_yaml(file)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid target file {path}: {e}")))?;
+    CHIP_FAMILIES.lock().unwrap().take();
+    probe_rs::config::get_target_by_name(chip).map_err(|_| {
+        AppError::InvalidInput(format!(
+            "Target file {path} (family '{family}') has no chip '{chip}'"
+        ))
+    })?;
+    Ok(())
+}
+
+/// Labels end up in CSS class names and file exports, so keep them plain.
+fn validate_extra_rtt_blocks(profile: &Profile) -> Result<(), AppError> {
+    if profile.extra_rtt_blocks.is_empty() {
+        return Ok(());
+    }
+    if profile.rtt_address.is_none() {
+        return Err(AppError::InvalidInput(
+            "Extra control blocks need the main block's RTT address too; a RAM scan would find any of them".to_string(),
+        ));
+    }
+    if profile.both_cores {
+        return Err(AppError::InvalidInput(
+            "Extra control blocks can't be combined with reading both cores".to_string(),
+        ));
+    }
+    let main = profile.rtt_address.as_deref().and_then(parse_hex_u64);
+    for (label, addr) in &profile.extra_rtt_blocks {
+        if label.is_empty()
+            || label.len() > 16
+            || !label
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Control block label '{label}' must be 1-16 letters, digits, '-' or '_'"
+            )));
+        }
+        let addr = validate_rtt_address_str(addr).map_err(AppError::InvalidInput)?;
+        if Some(addr) == main {
+            return Err(AppError::InvalidInput(format!(
+                "Control block '{label}' is the main block at {addr:#010X}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Attach to each of `Profile.extra_rtt_blocks` at its exact address. No RAM
+/// scan fallback: it would just find the main block again. A block that isn't
+/// there is skipped with a warning.
+fn attach_extra_rtt_blocks(
+    session: &mut probe_rs::Session,
+    core_idx: usize,
+    profile: &Profile,
+    sink: LogSink,
+) -> Vec<RttCore> {
+    let mut blocks = Vec::new();
+    for (label, addr) in &profile.extra_rtt_blocks {
+        let Some(addr) = parse_hex_u64(addr) else {
+            continue;
+        };
+        let attached = session
+            .core(core_idx)
+            .map_err(|e| AppError::Other(format!("Cannot access core {core_idx}: {e}")))
+            .and_then(|mut core| {
+                probe_rs::rtt::Rtt::attach_region(
+                    &mut core,
+                    &probe_rs::rtt::ScanRegion::Exact(addr),
+                )
+                .map_err(|e| {
+                    sink.last_error("RTT control block not found", error_chain(&e));
+                    AppError::RttNotFound(e.to_string())
+                })
+            });
+        match attached {
+            Ok(rtt) => {
+                sink.status(
+                    "info",
+                    &format!(
+                        "Control block '{label}' at {addr:#010X}, {} up channel(s).",
+                        rtt.up_channels.len()
+                    ),
+                );
+                blocks.push(RttCore {
+                    index: core_idx,
+                    name: Some(label.clone()),
+                    rtt,
+                });
+            }
+            Err(e) => sink.status(
+                "warn",
+                &format!("Control block '{label}' at {addr:#010X}: {e}. Skipping it."),
+            ),
+        }
+    }
+    blocks
+}
+
+/// nRF5340 network core, readable alongside the app core with `Profile.both_cores`
+const NRF5340_NET_CORE: usize = 1;
+
+fn validate_both_cores(profile: &Profile) -> Result<(), AppError> {
+    if !profile.both_cores {
+        return Ok(());
+    }
+    if !profile.chip.to_lowercase().starts_with("nrf5340") {
+        return Err(AppError::InvalidInput(format!(
+            "Reading both cores is only supported on the nRF5340, not {}",
+            profile.chip
+        )));
+    }
+    if profile.core.unwrap_or(0) != 0 {
+        return Err(AppError::InvalidInput(
+            "Reading both cores needs core 0 (app) as the profile's core".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// ── Open probe + attach session + core ──
+
+/// Another debugger (IDE, GDB server, zombie process) holding the probe only
+/// shows up as a busy / access-denied OS error, never as its own variant.
+fn probe_open_error(err: &probe_rs::probe::DebugProbeError) -> Option<AppError> {
+    use probe_rs::probe::{DebugProbeError, ProbeCreationError};
+    use std::io::ErrorKind;
+
+    let in_use = || {
+        AppError::ProbeInUse(
+            "Probe in use by another program — close your IDE/GDB server".to_string(),
+        )
+    };
+    let io = match err {
+        DebugProbeError::Usb(e)
+        | DebugProbeError::ProbeCouldNotBeCreated(ProbeCreationError::Usb(e)) => e,
+        DebugProbeError::ProbeCouldNotBeCreated(ProbeCreationError::CouldNotOpen) => {
+            return Some(in_use());
+        }
+        _ => return None,
+    };
+    match io.kind() {
+        // On Linux this is nearly always missing udev rules rather than a second user
+        ErrorKind::PermissionDenied if cfg!(target_os = "linux") => Some(AppError::Io(
+            "Permission denied opening probe — check your udev rules".to_string(),
+        )),
+        ErrorKind::PermissionDenied | ErrorKind::ResourceBusy => Some(in_use()),
+        // ERROR_SHARING_VIOLATION
+        _ if cfg!(windows) && io.raw_os_error() == Some(32) => Some(in_use()),
+        _ => None,
+    }
+}
+
+/// A locked nRF can only be attached after an erase, which probe-rs refuses
+/// without the erase-all permission — that refusal is how protection shows up.
+fn protection_error(err: &probe_rs::Error) -> Option<AppError> {
+    use probe_rs::architecture::arm::ArmError;
+
+    matches!(
+        err,
+        probe_rs::Error::MissingPermissions(_) | probe_rs::Error::Arm(ArmError::MissingPermissions(_))
+    )
+    .then(|| {
+        AppError::Protected(
+            "Target is read-back protected (APPROTECT). Recover it to erase the chip and unlock debugging.".to_string(),
+        )
+    })
+}
+
+/// Point the selected core at an explicit DAP access port.
+fn apply_access_port(
+    target: &mut probe_rs::Target,
+    core_idx: usize,
+    ap: u8,
+) -> Result<(), AppError> {
+    let name = target.name.clone();
+    let core = target.cores.get_mut(core_idx).ok_or_else(|| {
+        AppError::InvalidInput(format!("Core {core_idx} does not exist on {name}"))
+    })?;
+    match &mut core.core_access_options {
+        probe_rs_target::CoreAccessOptions::Arm(opts) => {
+            opts.ap = ap;
+            Ok(())
+        }
+        _ => Err(AppError::InvalidInput(format!(
+            "Access port selection only applies to ARM targets ({name} is not)"
+        ))),
+    }
+}
+
+/// SWD line reset: more than 50 cycles with SWDIO high
+const SWJ_LINE_RESET: (u8, u64) = (51, 0x0007_FFFF_FFFF_FFFF);
+
+/// Pre-attach SWJ sequences for SWJ-DPs the standard probe-rs init can't wake.
+/// probe-rs only lets targets customise DP setup from inside the crate, so the
+/// sequence is played on the raw probe first; the regular attach that follows
+/// then finds the DP already in SWD mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConnectSequence {
+    /// Deprecated JTAG-to-SWD switch code (0xEDB6) from early SWJ-DP revisions
+    LegacySwd,
+    /// Put the DP to sleep from either JTAG or SWD, then wake it into SWD with
+    /// the selection alert — for SWD v2 parts that boot in dormant state
+    DormantWakeup,
+}
+
+impl ConnectSequence {
+    const NAMES: [&'static str; 2] = ["legacy-swd", "dormant-wakeup"];
+
+    fn parse(name: &str) -> Result<Self, AppError> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "legacy-swd" => Ok(Self::LegacySwd),
+            "dormant-wakeup" => Ok(Self::DormantWakeup),
+            _ => Err(AppError::InvalidInput(format!(
+                "Unknown connect sequence '{name}' (expected one of: {})",
+                Self::NAMES.join(", ")
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::LegacySwd => Self::NAMES[0],
+            Self::DormantWakeup => Self::NAMES[1],
+        }
+    }
+
+    /// (bit count, bits sent LSB first) in the order they go on the wire
+    fn steps(self) -> &'static [(u8, u64)] {
+        match self {
+            Self::LegacySwd => &[SWJ_LINE_RESET, (16, 0xEDB6), SWJ_LINE_RESET, (2, 0)],
+            Self::DormantWakeup => &[
+                // JTAG to dormant; the line reset doubles as TAP reset
+                SWJ_LINE_RESET,
+                (31, 0x33BB_BBBA),
+                // SWD to dormant, in case the DP was in SWD instead
+                SWJ_LINE_RESET,
+                (16, 0xE3BC),
+                // Selection alert, then the SWD activation code
+                (8, 0xFF),
+                (64, 0x8685_2D95_6209_F392),
+                (64, 0x19BC_0EA2_E3DD_AFE9),
+                (12, 0x1A0),
+                SWJ_LINE_RESET,
+                (2, 0),
+            ],
+        }
+    }
+
+    /// Play the sequence over SWD. Fails with `InvalidInput` when the target or
+    /// probe can't take it: non-ARM chips, or probes like ST-Link whose firmware
+    /// doesn't expose raw SWJ sequences.
+    fn play(
+        self,
+        probe: &mut probe_rs::probe::Probe,
+        target: &probe_rs::Target,
+    ) -> Result<(), AppError> {
+        use probe_rs::probe::{DebugProbeError, WireProtocol};
+
+        if target.architecture() != probe_rs::Architecture::Arm {
+            return Err(AppError::InvalidInput(format!(
+                "Connect sequence '{}' only applies to ARM targets ({} is not)",
+                self.name(),
+                target.name
+            )));
+        }
+        let probe_name = probe.get_name();
+        let unsupported = || {
+            AppError::InvalidInput(format!(
+                "Connect sequence '{}' is not supported by {probe_name}: it can't send raw SWJ sequences",
+                self.name()
+            ))
+        };
+        probe
+            .select_protocol(WireProtocol::Swd)
+            .map_err(|e| AppError::InvalidInput(format!("{probe_name} can't use SWD: {e}")))?;
+        let dap = probe.try_as_dap_probe().ok_or_else(unsupported)?;
+        for &(len, bits) in self.steps() {
+            dap.swj_sequence(len, bits).map_err(|e| match e {
+                DebugProbeError::NotImplemented { .. }
+                | DebugProbeError::CommandNotSupportedByProbe { .. } => unsupported(),
+                e => AppError::Other(format!("Connect sequence '{}' failed: {e}", self.name())),
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Fatal session error: log it in-line and let the frontend react by kind.
+fn emit_rtt_error(app: &AppHandle, err: &AppError) {
+    emit_rtt_status(app, "error", err.message());
+    let _ = app.emit("rtt-error", err);
+}
+
+const DEFAULT_RTT_ID: &str = "SEGGER RTT";
+
+/// Read the ID string at the start of a control block.
+fn read_control_block_id(core: &mut probe_rs::Core<'_>, addr: u64) -> Option<String> {
+    let mut id = [0u8; 16];
+    core.read(addr, &mut id).ok()?;
+    let len = id.iter().position(|&b| b == 0).unwrap_or(id.len());
+    Some(String::from_utf8_lossy(&id[..len]).into_owned())
+}
+
+/// Look for a control block carrying a custom ID in the same memory the
+/// RTT attach scans. Its ID field is NUL-padded to 16 bytes.
+fn find_control_block_id(
+    core: &mut probe_rs::Core<'_>,
+    scan_region: &probe_rs::rtt::ScanRegion,
+    id: &str,
+) -> Option<u64> {
+    use probe_rs::rtt::ScanRegion;
+
+    let mut pattern = id.as_bytes().to_vec();
+    if pattern.len() < 16 {
+        pattern.push(0);
+    }
+    let ranges: Vec<std::ops::Range<u64>> = match scan_region {
+        ScanRegion::Exact(addr) => std::iter::once(*addr..*addr + 16).collect(),
+        ScanRegion::Ram => core
+            .memory_regions()
+            .filter_map(probe_rs::config::MemoryRegion::as_ram_region)
+            .map(|r| r.range.clone())
+            .collect(),
+        ScanRegion::Ranges(ranges) => ranges.clone(),
+    };
+    ranges.into_iter().find_map(|range| {
+        let mut mem = vec![0u8; usize::try_from(range.end.checked_sub(range.start)?).ok()?];
+        core.read(range.start, &mut mem).ok()?;
+        let offset = mem.windows(pattern.len()).position(|w| w == pattern)?;
+        Some(range.start + offset as u64)
+    })
+}
+
+/// A board that was just powered or reset attaches before its firmware has
+/// written the control block; look again this soon rather than after 3 s
+const RTT_INIT_RETRY: std::time::Duration = std::time::Duration::from_millis(100);
+const DEFAULT_RTT_INIT_GRACE_MS: u64 = 1000;
+/// Keeps a typo from holding the RTT thread past a stop request for long
+const MAX_RTT_INIT_GRACE_MS: u64 = 10_000;
+
+/// `attach_region`, retried every `RTT_INIT_RETRY` for `grace` while the block
+/// is missing or half-written.
+fn attach_rtt_region(
+    core: &mut probe_rs::Core<'_>,
+    scan_region: &probe_rs::rtt::ScanRegion,
+    grace: std::time::Duration,
+) -> Result<probe_rs::rtt::Rtt, probe_rs::rtt::Error> {
+    use probe_rs::rtt::Error;
+
+    let started = std::time::Instant::now();
+    loop {
+        match probe_rs::rtt::Rtt::attach_region(core, scan_region) {
+            Err(Error::ControlBlockNotFound | Error::ControlBlockCorrupted(_))
+                if started.elapsed() + RTT_INIT_RETRY <= grace =>
+            {
+                std::thread::sleep(RTT_INIT_RETRY)
+            }
+            result => return result,
+        }
+    }
+}
+
+/// `quiet` leaves out the progress lines while the target boot-loops.
+fn attach_rtt(
+    session: &mut probe_rs::Session,
+    core_idx: usize,
+    scan_region: &probe_rs::rtt::ScanRegion,
+    rtt_id: Option<&str>,
+    grace: std::time::Duration,
+    sink: LogSink<'_>,
+    quiet: bool,
+) -> Result<probe_rs::rtt::Rtt, AppError> {
+    use probe_rs::rtt::ScanRegion;
+
+    let mut core = session
+        .core(core_idx)
+        .map_err(|e| AppError::Other(format!("Cannot access core {core_idx}: {e}")))?;
+
+    if !quiet {
+        sink.connect_status(
+            "info",
+            "Target attached. Searching for RTT control block...",
+        );
+    }
+
+    let mut rtt = match attach_rtt_region(&mut core, scan_region, grace) {
+        Ok(rtt) => rtt,
+        Err(e) => {
+            sink.last_error("RTT control block not found", error_chain(&e));
+            // probe-rs only recognises the default ID, so a custom one can be
+            // located but not attached to — say so instead of retrying forever.
+            if let Some(id) = rtt_id.filter(|id| *id != DEFAULT_RTT_ID) {
+                if let Some(addr) = find_control_block_id(&mut core, scan_region, id) {
+                    return Err(AppError::InvalidInput(format!(
+                        "Control block \"{id}\" found at {addr:#010X}, but only \"{DEFAULT_RTT_ID}\" blocks can be attached. Build the firmware with the default RTT ID."
+                    )));
+                }
+            }
+            // A stale profile address (firmware rebuilt, block moved): look through all RAM
+            let ScanRegion::Exact(configured) = scan_region else {
+                return Err(AppError::RttNotFound(format!(
+                    "RTT not found (fw not running?): {e}"
+                )));
+            };
+            if !quiet {
+                sink.connect_status(
+                    "info",
+                    &format!("No control block at {configured:#010X}, scanning RAM..."),
+                );
+            }
+            let rtt =
+                probe_rs::rtt::Rtt::attach_region(&mut core, &ScanRegion::Ram).map_err(|e| {
+                    sink.last_error("RTT control block not found", error_chain(&e));
+                    AppError::RttNotFound(format!("RTT not found (fw not running?): {e}"))
+                })?;
+            sink.status(
+                "warn",
+                &format!(
+                    "Profile RTT address {configured:#010X} is stale; control block is at {:#010X}. Update the profile.",
+                    rtt.ptr()
+                ),
+            );
+            rtt
+        }
+    };
+
+    let ch_count = rtt.up_channels().len();
+    let matched_id =
+        read_control_block_id(&mut core, rtt.ptr()).unwrap_or_else(|| DEFAULT_RTT_ID.to_string());
+    if !quiet {
+        sink.connect_status(
+            "info",
+            &format!(
+                "RTT connected! \"{matched_id}\" at {:#010X}, {ch_count} up channel(s) found.",
+                rtt.ptr()
+            ),
+        );
+    }
+    if let LogSink::App(app) = sink {
+        let _ = app.emit("rtt-connected", ());
+    }
+
+    Ok(rtt)
+}
+
+// ── Reset reason ──
+
+/// Where a chip family latches why it last reset, and what each bit means.
+struct ResetReasonRegister {
+    /// Lowercase chip-name prefixes this layout applies to
+    chips: &'static [&'static str],
+    address: u64,
+    bits: &'static [(u32, &'static str)],
+    /// Meaning of a register with none of the known bits set
+    none_set: &'static str,
+}
+
+const RESET_REASON_REGISTERS: &[ResetReasonRegister] = &[
+    ResetReasonRegister {
+        chips: &["nrf52"],
+        address: 0x4000_0400, // POWER.RESETREAS
+        bits: &[
+            (0, "reset pin"),
+            (1, "watchdog"),
+            (2, "software"),
+            (3, "CPU lockup"),
+            (16, "wake from System OFF (GPIO)"),
+            (17, "wake from System OFF (LPCOMP)"),
+            (18, "debug interface"),
+            (19, "wake from System OFF (NFC)"),
+            (20, "wake from System OFF (VBUS)"),
+        ],
+        none_set: "power-on or brownout",
+    },
+    ResetReasonRegister {
+        chips: &["nrf5340"],
+        address: 0x5000_5400, // RESET.RESETREAS (application core)
+        bits: &[
+            (0, "reset pin"),
+            (1, "watchdog 0"),
+            (2, "CTRL-AP"),
+            (3, "software"),
+            (4, "CPU lockup"),
+            (5, "wake from System OFF"),
+            (6, "wake from System OFF (LPCOMP)"),
+            (7, "debug interface"),
+            (8, "network core software"),
+            (9, "network core lockup"),
+            (10, "network core watchdog"),
+            (23, "network core force-off"),
+            (24, "wake from System OFF (NFC)"),
+            (25, "watchdog 1"),
+            (26, "wake from System OFF (VBUS)"),
+            (27, "network core CTRL-AP"),
+        ],
+        none_set: "power-on or brownout",
+    },
+    ResetReasonRegister {
+        chips: &["nrf91"],
+        address: 0x5000_5400, // POWER.RESETREAS
+        bits: &[
+            (0, "reset pin"),
+            (1, "watchdog"),
+            (2, "wake from System OFF"),
+            (3, "debug interface"),
+            (4, "software"),
+            (5, "CPU lockup"),
+            (6, "CTRL-AP"),
+        ],
+        none_set: "power-on or brownout",
+    },
+    ResetReasonRegister {
+        chips: &["stm32f2", "stm32f4", "stm32f7"],
+        address: 0x4002_3874, // RCC_CSR
+        bits: &[
+            (25, "brownout"),
+            (26, "reset pin"),
+            (27, "power-on"),
+            (28, "software"),
+            (29, "independent watchdog"),
+            (30, "window watchdog"),
+            (31, "low-power"),
+        ],
+        none_set: "unknown",
+    },
+    ResetReasonRegister {
+        chips: &["stm32f0", "stm32f1", "stm32f3"],
+        address: 0x4002_1024, // RCC_CSR
+        bits: &[
+            (25, "option byte load"),
+            (26, "reset pin"),
+            (27, "power-on"),
+            (28, "software"),
+            (29, "independent watchdog"),
+            (30, "window watchdog"),
+            (31, "low-power"),
+        ],
+        none_set: "unknown",
+    },
+    ResetReasonRegister {
+        chips: &["stm32l4"],
+        address: 0x4002_1094, // RCC_CSR
+        bits: &[
+            (24, "firewall"),
+            (25, "option byte load"),
+            (26, "reset pin"),
+            (27, "brownout"),
+            (28, "software"),
+            (29, "independent watchdog"),
+            (30, "window watchdog"),
+            (31, "low-power"),
+        ],
+        none_set: "unknown",
+    },
+    ResetReasonRegister {
+        chips: &["stm32wb"],
+        address: 0x5800_0094, // RCC_CSR
+        bits: &[
+            (25, "option byte load"),
+            (26, "reset pin"),
+            (27, "brownout"),
+            (28, "software"),
+            (29, "independent watchdog"),
+            (30, "window watchdog"),
+            (31, "low-power"),
+        ],
+        none_set: "unknown",
+    },
+    ResetReasonRegister {
+        chips: &["stm32g0"],
+        address: 0x4002_1060, // RCC_CSR
+        bits: &[
+            (25, "option byte load"),
+            (26, "reset pin"),
+            (27, "power-on / brownout"),
+            (28, "software"),
+            (29, "independent watchdog"),
+            (30, "window watchdog"),
+            (31, "low-power"),
+        ],
+        none_set: "unknown",
+    },
+];
+
+/// Decode the reset reason for chips we have a register map for. The
+/// register is only read — firmware that never clears it reports every
+/// reason since power-on.
+fn read_reset_reason(core: &mut probe_rs::Core<'_>, chip: &str, core_idx: usize) -> Option<String> {
+    let chip = chip.to_lowercase();
+    let reg = RESET_REASON_REGISTERS
+        .iter()
+        .find(|r| r.chips.iter().any(|prefix| chip.starts_with(prefix)))?;
+    // The nRF5340 network core has its own RESET block; only the app core map is known
+    if chip.starts_with("nrf5340") && core_idx != 0 {
+        return None;
+    }
+    let value = core.read_word_32(reg.address).ok()?;
+    let reasons: Vec<&str> = reg
+        .bits
+        .iter()
+        .filter(|(bit, _)| value & (1 << bit) != 0)
+        .map(|&(_, name)| name)
+        .collect();
+    Some(if reasons.is_empty() {
+        reg.none_set.to_string()
+    } else {
+        reasons.join(", ")
+    })
+}
+
+// ── Device ID ──
+
+/// Where a chip family keeps its factory-programmed unique ID.
+struct DeviceIdLocation {
+    /// Lowercase chip-name prefixes this applies to
+    chips: &'static [&'static str],
+    /// Word addresses of the ID, most significant first
+    words: &'static [u64],
+    /// Register name in the reference manual
+    register: &'static str,
+}
+
+const DEVICE_ID_LOCATIONS: &[DeviceIdLocation] = &[
+    DeviceIdLocation {
+        chips: &["nrf51", "nrf52"],
+        words: &[0x1000_0064, 0x1000_0060],
+        register: "FICR.DEVICEID",
+    },
+    DeviceIdLocation {
+        // Application core FICR; see `NRF5340_NET_DEVICE_ID`
+        chips: &["nrf5340", "nrf91"],
+        words: &[0x00FF_0208, 0x00FF_0204],
+        register: "FICR.INFO.DEVICEID",
+    },
+    DeviceIdLocation {
+        chips: &["stm32f0", "stm32f3"],
+        words: &[0x1FFF_F7B4, 0x1FFF_F7B0, 0x1FFF_F7AC],
+        register: "UID",
+    },
+    DeviceIdLocation {
+        chips: &["stm32f1"],
+        words: &[0x1FFF_F7F0, 0x1FFF_F7EC, 0x1FFF_F7E8],
+        register: "UID",
+    },
+    DeviceIdLocation {
+        chips: &["stm32f2", "stm32f4"],
+        words: &[0x1FFF_7A18, 0x1FFF_7A14, 0x1FFF_7A10],
+        register: "UID",
+    },
+    DeviceIdLocation {
+        chips: &["stm32f7"],
+        words: &[0x1FF0_F428, 0x1FF0_F424, 0x1FF0_F420],
+        register: "UID",
+    },
+    DeviceIdLocation {
+        chips: &["stm32h7"],
+        words: &[0x1FF1_E808, 0x1FF1_E804, 0x1FF1_E800],
+        register: "UID",
+    },
+    DeviceIdLocation {
+        chips: &["stm32l4", "stm32g0", "stm32g4", "stm32wb"],
+        words: &[0x1FFF_7598, 0x1FFF_7594, 0x1FFF_7590],
+        register: "UID",
+    },
+    DeviceIdLocation {
+        // Not contiguous on the L0
+        chips: &["stm32l0"],
+        words: &[0x1FF8_0064, 0x1FF8_0054, 0x1FF8_0050],
+        register: "UID",
+    },
+    DeviceIdLocation {
+        chips: &["atsamd21", "atsamd11", "atsamd10"],
+        words: &[0x0080_A00C, 0x0080_A040, 0x0080_A044, 0x0080_A048],
+        register: "serial number",
+    },
+    DeviceIdLocation {
+        chips: &["atsamd51", "atsame5"],
+        words: &[0x0080_61FC, 0x0080_6010, 0x0080_6014, 0x0080_6018],
+        register: "serial number",
+    },
+];
+
+/// The nRF5340 network core has its own FICR
+const NRF5340_NET_DEVICE_ID: &[u64] = &[0x01FF_0208, 0x01FF_0204];
+
+/// Word addresses and register name of `chip`'s unique ID, as seen from core
+/// `core_idx`. Errors name the supported families.
+fn device_id_location(
+    chip: &str,
+    core_idx: usize,
+) -> Result<(&'static [u64], &'static str), AppError> {
+    let lower = chip.to_lowercase();
+    let location = DEVICE_ID_LOCATIONS
+        .iter()
+        .find(|l| l.chips.iter().any(|prefix| lower.starts_with(prefix)))
+        .ok_or_else(|| {
+            let supported: Vec<&str> = DEVICE_ID_LOCATIONS
+                .iter()
+                .flat_map(|l| l.chips.iter().copied())
+                .collect();
+            AppError::InvalidInput(format!(
+                "Don't know where {chip} keeps its unique ID (supported: {})",
+                supported.join(", ")
+            ))
+        })?;
+    if lower.starts_with("nrf5340") && core_idx != 0 {
+        return Ok((NRF5340_NET_DEVICE_ID, location.register));
+    }
+    Ok((location.words, location.register))
+}
+
+/// The ID as one hex string, most significant word first.
+fn format_device_id(words: &[u32]) -> String {
+    words.iter().map(|w| format!("{w:08X}")).collect()
+}
+
+/// On connect, for `Profile.show_device_id`. A log entry rather than a status
+/// line, so exports say which physical chip they came from.
+fn emit_device_id(sink: LogSink, core: &mut probe_rs::Core<'_>, chip: &str, core_idx: usize) {
+    let text = match device_id_location(chip, core_idx).and_then(|(addresses, register)| {
+        let words = addresses
+            .iter()
+            .map(|&address| core.read_word_32(address))
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|e| AppError::Other(format!("Failed to read {register}: {e}")))?;
+        Ok(format!(
+            "Device ID ({register}): {}",
+            format_device_id(&words)
+        ))
+    }) {
+        Ok(text) => text,
+        Err(e) => {
+            sink.status("warn", &format!("No device ID: {e}"));
+            return;
+        }
+    };
+    sink.emit_host(LogEntry {
+        level: "info".to_string(),
+        tag: Some("device".to_string()),
+        tag_color: Some(tag_color_index("device")),
+        message: text.clone(),
+        raw: text,
+        host_timestamp: Some(host_now_ms()),
+        ..Default::default()
+    });
+}
+
+/// Factory-programmed unique ID of the attached chip (nRF FICR DEVICEID,
+/// STM32 UID, SAM serial number), as hex, most significant word first. Tells
+/// which physical board a session is on.
+#[tauri::command]
+async fn read_device_id(app: AppHandle, chip: String) -> Result<String, AppError> {
+    let core_idx = app
+        .state::<AppState>()
+        .active_profile
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|p| p.core)
+        .unwrap_or(0);
+    let (addresses, _) = device_id_location(&chip, core_idx)?;
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let regions = addresses.iter().map(|&address| (address, 4)).collect();
+    send_rtt_command(&app, RttCommand::ReadMemory(regions, tx))?;
+    let words: Vec<u32> = await_rtt_reply(rx)
+        .await?
+        .iter()
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect();
+    Ok(format_device_id(&words))
+}
+
+// ── Bootloader entry ──
+
+/// Where a magic word goes before a reset makes the bootloader stay in DFU
+enum BootloaderFlag {
+    /// Fixed register that survives a soft reset
+    Register(u64),
+    /// Last word of the first RAM region, which the bootloader checks before
+    /// clearing RAM (its address depends on the part's RAM size)
+    RamEnd,
+}
+
+/// How to restart a chip family into its bootloader from the debugger.
+/// This only works when the matching bootloader is flashed; the reset is
+/// otherwise an ordinary one.
+struct BootloaderEntry {
+    /// Lowercase chip-name prefixes this applies to
+    chips: &'static [&'static str],
+    flag: BootloaderFlag,
+    value: u32,
+    /// Shown in the status line so users can tell which bootloader to expect
+    bootloader: &'static str,
+}
+
+const BOOTLOADER_ENTRIES: &[BootloaderEntry] = &[
+    BootloaderEntry {
+        // POWER.GPREGRET, BOOTLOADER_DFU_START in the nRF5 SDK; the Adafruit
+        // nRF52 bootloader takes the same value as "enter OTA DFU"
+        chips: &["nrf51", "nrf52"],
+        flag: BootloaderFlag::Register(0x4000_051C),
+        value: 0xB1,
+        bootloader: "nRF5 SDK DFU bootloader",
+    },
+    BootloaderEntry {
+        // DBL_TAP_MAGIC of the UF2 (Adafruit/Microsoft) SAMD bootloader
+        chips: &["atsamd21", "atsamd51", "atsame5"],
+        flag: BootloaderFlag::RamEnd,
+        value: 0xF016_69EF,
+        bootloader: "UF2 bootloader",
+    },
+];
+
+/// Resolved bootloader request, ready for the RTT thread.
+struct BootloaderWrite {
+    address: u64,
+    value: u32,
+}
+
+/// Look up `chip` in `BOOTLOADER_ENTRIES`. Errors name the supported families.
+fn bootloader_write(chip: &str) -> Result<(BootloaderWrite, &'static str), AppError> {
+    let lower = chip.to_lowercase();
+    let entry = BOOTLOADER_ENTRIES
+        .iter()
+        .find(|e| e.chips.iter().any(|prefix| lower.starts_with(prefix)))
+        .ok_or_else(|| {
+            let supported: Vec<&str> = BOOTLOADER_ENTRIES
+                .iter()
+                .flat_map(|e| e.chips.iter().copied())
+                .collect();
+            AppError::InvalidInput(format!(
+                "Don't know how to enter the bootloader on {chip} (supported: {})",
+                supported.join(", ")
+            ))
+        })?;
+    let address = match entry.flag {
+        BootloaderFlag::Register(address) => address,
+        BootloaderFlag::RamEnd => {
+            let target = probe_rs::config::get_target_by_name(chip)
+                .map_err(|e| AppError::UnknownChip(format!("Unknown chip '{chip}': {e}")))?;
+            let ram = target
+                .memory_map
+                .iter()
+                .find_map(|region| match region {
+                    probe_rs::config::MemoryRegion::Ram(ram) => Some(ram.range.clone()),
+                    _ => None,
+                })
+                .ok_or_else(|| AppError::Other(format!("{chip} has no RAM in its memory map")))?;
+            ram.end - 4
+        }
+    };
+    Ok((
+        BootloaderWrite {
+            address,
+            value: entry.value,
+        },
+        entry.bootloader,
+    ))
+}
+
+/// Write the flag and reset. The session then loses RTT and goes back to
+/// reconnecting, which picks the application up again once DFU is done.
+fn enter_bootloader_on_core(
+    core: &mut probe_rs::Core<'_>,
+    write: &BootloaderWrite,
+) -> Result<(), AppError> {
+    core.write_word_32(write.address, write.value)
+        .map_err(|e| AppError::Other(format!("Failed to write bootloader flag: {e}")))?;
+    core.reset()
+        .map_err(|e| AppError::Other(format!("Reset failed: {e}")))
+}
+
+// ── Compressed channels ──
+
+/// Decompression for an up channel (`Profile.channel_codec`). The firmware
+/// sends frames of `[length: u16 LE][payload]`, each written with one
+/// `SEGGER_RTT_Write` call so that a full buffer skips a frame whole instead
+/// of cutting it in two:
+/// - "lz4": the payload is one LZ4 block (`LZ4_compress_default`, or
+///   `LZ4_compress_fast_continue`; the last 64 KiB of output is kept as the
+///   dictionary)
+/// - "heatshrink" or "heatshrink:W,L": the payload is a complete heatshrink
+///   stream, the encoder finished and reset per frame, with a window of W and
+///   a lookahead of L bits (default 8,4)
+///
+/// The length prefix is the only framing, so there is nothing to resync on:
+/// a frame cut short (`SEGGER_RTT_MODE_NO_BLOCK_TRIM`) or a corrupt length
+/// garbles every later frame until the channel is attached again. Compressed
+/// channels need the skip or blocking mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChannelCodec {
+    Lz4,
+    Heatshrink { window_bits: u8, lookahead_bits: u8 },
+}
+
+/// LZ4's largest match offset, so also the dictionary it can refer back into
+const LZ4_HISTORY_BYTES: usize = 64 * 1024;
+
+impl ChannelCodec {
+    /// `None` for "none"
+    fn parse(name: &str) -> Result<Option<Self>, String> {
+        let name = name.trim().to_ascii_lowercase();
+        match name.split_once(':') {
+            None if name == "none" => Ok(None),
+            None if name == "lz4" => Ok(Some(Self::Lz4)),
+            None if name == "heatshrink" => Ok(Some(Self::Heatshrink {
+                window_bits: 8,
+                lookahead_bits: 4,
+            })),
+            Some(("heatshrink", params)) => {
+                let bits = params
+                    .split_once(',')
+                    .and_then(|(w, l)| Some((w.trim().parse().ok()?, l.trim().parse().ok()?)));
+                match bits {
+                    Some((w @ 4..=15, l)) if (3..w).contains(&l) => Ok(Some(Self::Heatshrink {
+                        window_bits: w,
+                        lookahead_bits: l,
+                    })),
+                    _ => Err(format!(
+                        "Invalid heatshrink parameters '{params}': expected W,L with window 4-15 bits and lookahead 3 to W-1"
+                    )),
+                }
+            }
+            _ => Err(format!(
+                "Unknown channel codec '{name}' (expected none, lz4 or heatshrink)"
+            )),
+        }
+    }
+}
+
+/// Reassembles frames across reads and decompresses them.
+struct FrameDecoder {
+    codec: ChannelCodec,
+    /// Bytes of a frame that hasn't fully arrived yet
+    partial: Vec<u8>,
+    /// Recent LZ4 output, for blocks that refer back into earlier ones
+    history: Vec<u8>,
+}
+
+impl FrameDecoder {
+    fn new(codec: ChannelCodec) -> Self {
+        Self {
+            codec,
+            partial: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Decompressed bytes of every frame completed by `bytes`, and the size of
+    /// frames that failed to decode (dropped, and counted as corrupt).
+    fn push(&mut self, bytes: &[u8]) -> (Vec<u8>, u64) {
+        self.partial.extend_from_slice(bytes);
+        let mut out = Vec::new();
+        let mut bad = 0u64;
+        let mut pos = 0;
+        while let Some(header) = self.partial.get(pos..pos + 2) {
+            let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+            let Some(payload) = self.partial.get(pos + 2..pos + 2 + len) else {
+                break;
+            };
+            let decoded = match self.codec {
+                ChannelCodec::Lz4 => {
+                    let start = self.history.len();
+                    let result = lz4_block_decode(payload, &mut self.history)
+                        .map(|()| self.history[start..].to_vec());
+                    if result.is_err() {
+                        self.history.truncate(start);
+                    }
+                    let excess = self.history.len().saturating_sub(LZ4_HISTORY_BYTES);
+                    self.history.drain(..excess);
+                    result
+                }
+                ChannelCodec::Heatshrink {
+                    window_bits,
+                    lookahead_bits,
+                } => Ok(heatshrink_decode(payload, window_bits, lookahead_bits)),
+            };
+            match decoded {
+                Ok(bytes) => out.extend_from_slice(&bytes),
+                Err(()) => bad += len as u64,
+            }
+            pos += 2 + len;
+        }
+        self.partial.drain(..pos);
+        (out, bad)
+    }
+}
+
+/// Decode one raw LZ4 block onto the end of `out`, whose existing contents are
+/// the dictionary. Fails on a malformed block or an offset reaching past `out`.
+fn lz4_block_decode(block: &[u8], out: &mut Vec<u8>) -> Result<(), ()> {
+    // 15 in a token nibble means "more length bytes follow", each adding up to 255
+    fn length(block: &[u8], pos: &mut usize, nibble: u8) -> Result<usize, ()> {
+        let mut len = nibble as usize;
+        if nibble == 15 {
+            loop {
+                let b = *block.get(*pos).ok_or(())?;
+                *pos += 1;
+                len += b as usize;
+                if b != 255 {
+                    break;
+                }
+            }
+        }
+        Ok(len)
+    }
+
+    let mut pos = 0;
+    while pos < block.len() {
+        let token = block[pos];
+        pos += 1;
+        let literals = length(block, &mut pos, token >> 4)?;
+        out.extend_from_slice(block.get(pos..pos + literals).ok_or(())?);
+        pos += literals;
+        // The last sequence is literals only
+        if pos == block.len() {
+            break;
+        }
+        let offset =
+            u16::from_le_bytes([*block.get(pos).ok_or(())?, *block.get(pos + 1).ok_or(())?])
+                as usize;
+        pos += 2;
+        if offset == 0 || offset > out.len() {
+            return Err(());
+        }
+        let match_len = length(block, &mut pos, token & 15)? + 4;
+        // Byte by byte: a match may overlap the bytes it is producing
+        let from = out.len() - offset;
+        for i in 0..match_len {
+            out.push(out[from + i]);
+        }
+    }
+    Ok(())
+}
+
+/// Decode a complete heatshrink stream. Tokens are MSB-first: a 1 bit and a
+/// literal byte, or a 0 bit, a `window_bits` offset and a `lookahead_bits`
+/// count (both stored minus one). Padding bits at the end are ignored.
+fn heatshrink_decode(data: &[u8], window_bits: u8, lookahead_bits: u8) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() * 2);
+    let total_bits = data.len() * 8;
+    let mut bit = 0;
+    let mut read = |n: u8| -> Option<usize> {
+        if bit + n as usize > total_bits {
+            return None;
+        }
+        let mut value = 0;
+        for _ in 0..n {
+            value = (value << 1) | ((data[bit / 8] >> (7 - bit % 8)) & 1) as usize;
+            bit += 1;
+        }
+        Some(value)
+    };
+    while let Some(tag) = read(1) {
+        if tag == 1 {
+            let Some(byte) = read(8) else { break };
+            out.push(byte as u8);
+            continue;
+        }
+        let (Some(index), Some(count)) = (read(window_bits), read(lookahead_bits)) else {
+            break;
+        };
+        let offset = index + 1;
+        for _ in 0..=count {
+            // The window starts out zeroed
+            let byte = out.len().checked_sub(offset).map_or(0, |i| out[i]);
+            out.push(byte);
+        }
+    }
+    out
+}
+
+// ── Process raw RTT bytes into log entries ──
+
+/// Parser settings taken from the profile at connect time.
+#[derive(Debug, Clone, Default)]
+struct ParseOptions {
+    include_hex: bool,
+    burst_gap_ms: Option<u64>,
+    keep_tag_in_message: bool,
+    merge_continuations: bool,
+    binary_channels: Vec<usize>,
+    base64_channels: Vec<usize>,
+    encoding: TextEncoding,
+    parse_kv: bool,
+    /// `None` when `Profile.skip_corruption_check` is set
+    corruption_warn_percent: Option<u8>,
+    keep_partial_first_line: bool,
+    keep_blank_lines: bool,
+    terminal_names: HashMap<u8, String>,
+    channel_codecs: HashMap<usize, ChannelCodec>,
+}
+
+/// How line bytes become `message` text.
+#[derive(Debug, Clone, Copy, Default)]
+enum TextEncoding {
+    /// Invalid sequences become U+FFFD
+    #[default]
+    Utf8,
+    /// 7-bit only; anything above 0x7F becomes U+FFFD
+    Ascii,
+    /// Single- and multi-byte codepages. "latin1" resolves to windows-1252,
+    /// a superset that also maps 0x80–0x9F.
+    Other(&'static encoding_rs::Encoding),
+}
+
+impl TextEncoding {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "utf8" | "utf-8" => Ok(Self::Utf8),
+            "ascii" | "us-ascii" => Ok(Self::Ascii),
+            label => encoding_rs::Encoding::for_label(label.as_bytes())
+                .map(Self::Other)
+                .ok_or_else(|| format!("Unknown text encoding '{name}'")),
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> String {
+        match self {
+            Self::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            Self::Ascii => bytes
+                .iter()
+                .map(|&b| if b.is_ascii() { b as char } else { '\u{FFFD}' })
+                .collect(),
+            Self::Other(encoding) => encoding.decode_without_bom_handling(bytes).0.into_owned(),
+        }
+    }
+}
+
+const DEFAULT_BURST_GAP_MS: u64 = 1000;
+
+/// Most continuation lines folded into one entry; the rest come through as raw lines
+const MAX_CONTINUATION_LINES: u32 = 64;
+
+/// Bytes per hex row on a binary channel
+const BINARY_ROW_BYTES: usize = 16;
+
+/// Up channels known to carry a binary protocol rather than text: SEGGER
+/// SystemView ("SysView") and J-Scope in RTT mode ("JScope_<format>").
+/// Their bytes would otherwise be taken for terminal switches and escapes.
+fn is_binary_channel(name: Option<&str>) -> bool {
+    name.is_some_and(|n| n == "SysView" || n.starts_with("JScope"))
+}
+
+impl ParseOptions {
+    fn from_profile(profile: &Profile) -> Self {
+        Self {
+            include_hex: profile.include_hex,
+            burst_gap_ms: profile.burst_gap_ms,
+            keep_tag_in_message: profile.keep_tag_in_message,
+            merge_continuations: profile.merge_continuations,
+            binary_channels: profile.binary_channels.clone(),
+            base64_channels: profile.base64_channels.clone(),
+            parse_kv: profile.parse_kv,
+            keep_partial_first_line: profile.keep_partial_first_line,
+            keep_blank_lines: profile.keep_blank_lines,
+            terminal_names: profile.terminal_names.clone().unwrap_or_default(),
+            // Checked when the profile was saved
+            channel_codecs: profile
+                .channel_codec
+                .iter()
+                .filter_map(|(&ch, name)| Some((ch, ChannelCodec::parse(name).ok()??)))
+                .collect(),
+            corruption_warn_percent: (!profile.skip_corruption_check).then(|| {
+                profile
+                    .corruption_warn_percent
+                    .unwrap_or(DEFAULT_CORRUPTION_WARN_PERCENT)
+            }),
+            // Checked when the profile was saved
+            encoding: profile
+                .encoding
+                .as_deref()
+                .and_then(|name| TextEncoding::parse(name).ok())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// "2024-05-01T12:34:56.789Z" for milliseconds since the Unix epoch.
+fn rfc3339_utc(ms: u64) -> String {
+    let days = (ms / 86_400_000) as i64;
+    let day_ms = ms % 86_400_000;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        day_ms / 3_600_000,
+        day_ms / 60_000 % 60,
+        day_ms / 1000 % 60,
+        day_ms % 1000
+    )
+}
+
+fn host_now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Standard base64 with padding (RFC 4648).
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Where parsed entries go.
+#[derive(Clone, Copy)]
+enum LogSink<'a> {
+    /// `rtt-log` events to the webview, through the emit filter
+    App(&'a AppHandle),
+    /// One JSON object per line on stdout (headless mode)
+    Stdout,
+    /// Kept in memory
+    #[cfg(test)]
+    Collect(&'a std::cell::RefCell<Vec<LogEntry>>),
+}
+
+impl LogSink<'_> {
+    /// Assigns `entry.id`. Err means the consumer is gone (webview closed, stdout pipe broken).
+    fn emit(&self, entry: &mut LogEntry, stats: &mut RttStats) -> Result<(), ()> {
+        match self {
+            LogSink::App(app) => {
+                let state = app.state::<AppState>();
+                if !state.emit_filter.lock().unwrap().allows(entry) {
+                    skip_log_id(entry);
+                    // Views apply their own filters instead
+                    tap_entry(app, entry);
+                    with_sqlite_log(app, |db| db.push(entry));
+                    stats.filtered += 1;
+                    return Ok(());
+                }
+                let sent = emit_log(app, entry);
+                with_sqlite_log(app, |db| db.push(entry));
+                if sent.is_err() {
+                    PERF.emit_failures.fetch_add(1, Ordering::Relaxed);
+                    return Err(());
+                }
+            }
+            LogSink::Stdout => {
+                entry.id = SEQ.fetch_add(1, Ordering::Relaxed);
+                use std::io::Write;
+                let json = serde_json::to_string(entry).map_err(|_| ())?;
+                let mut out = std::io::stdout().lock();
+                writeln!(out, "{json}")
+                    .and_then(|_| out.flush())
+                    .map_err(|_| ())?;
+            }
+            #[cfg(test)]
+            LogSink::Collect(entries) => {
+                entry.id = SEQ.fetch_add(1, Ordering::Relaxed);
+                entries.borrow_mut().push(entry.clone());
+            }
+        }
+        PERF.events_emitted.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// The GUI behind the sink; session features that only make sense with
+    /// one (GDB, watches, triggers, events) are skipped without it.
+    fn app(&self) -> Option<&AppHandle> {
+        match self {
+            LogSink::App(app) => Some(app),
+            _ => None,
+        }
+    }
+
+    fn time_base(&self) -> TimeBase {
+        match self {
+            LogSink::App(app) => *app.state::<AppState>().time_base.lock().unwrap(),
+            LogSink::Stdout => TimeBase::Device,
+            #[cfg(test)]
+            LogSink::Collect(_) => TimeBase::Device,
+        }
+    }
+
+    /// See `set_error_threshold`; the default without a GUI
+    fn error_threshold(&self) -> u32 {
+        self.app().map_or(DEFAULT_ERROR_THRESHOLD, |app| {
+            app.state::<AppState>()
+                .error_threshold
+                .load(Ordering::Relaxed)
+        })
+    }
+
+    /// See `set_read_mode`; the default without a GUI
+    fn read_mode(&self) -> ReadMode {
+        self.app().map_or(ReadMode::default(), |app| {
+            *app.state::<AppState>().read_mode.lock().unwrap()
+        })
+    }
+
+    /// See `set_emit_batching`; the default without a GUI
+    fn emit_batching(&self) -> EmitBatching {
+        self.app().map_or(EmitBatching::default(), |app| {
+            *app.state::<AppState>().emit_batching.lock().unwrap()
+        })
+    }
+
+    /// A host-side entry such as the device ID. Bypasses the emit filter, like markers.
+    fn emit_host(&self, mut entry: LogEntry) {
+        match self {
+            LogSink::App(app) => {
+                let _ = emit_log(app, &mut entry);
+            }
+            _ => {
+                let _ = self.emit(&mut entry, &mut RttStats::default());
+            }
+        }
+    }
+
+    fn status(&self, level: &str, msg: &str) {
+        match self {
+            LogSink::App(app) => emit_rtt_status(app, level, msg),
+            LogSink::Stdout => eprintln!("[{level}] {msg}"),
+            #[cfg(test)]
+            LogSink::Collect(_) => {}
+        }
+    }
+
+    /// Like `status`, for connection progress (see `emit_connect_status`).
+    fn connect_status(&self, level: &str, msg: &str) {
+        match self {
+            LogSink::App(app) => emit_connect_status(app, level, msg),
+            _ => self.status(level, msg),
+        }
+    }
+
+    /// Record a connection failure for `last_error_detail`. Headless mode
+    /// reports the error itself when it exits.
+    fn last_error(&self, step: &str, chain: Vec<String>) {
+        if let LogSink::App(app) = self {
+            set_last_error(app, step, chain);
+        }
+    }
+}
+
+/// Where `process_text` is between payload and control bytes. Control bytes
+/// are split off before any decoding, so the text encoding only ever sees the
+/// payload of a whole line.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TextState {
+    Payload,
+    /// After 0xFF: a terminal digit, or else the 0xFF was payload
+    TerminalSwitch,
+    /// After ESC
+    Escape,
+    /// Inside an ANSI CSI sequence (ESC '['), before its final byte
+    Csi,
+}
+
+/// SEGGER_RTT_SetTerminal sends 0xFF then '0'-'9' or 'A'-'F'.
+fn terminal_switch_digit(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+struct RttParser {
+    channel: usize,
+    options: ParseOptions,
+    /// Text bytes of the current line, decoded once it ends
+    line_buf: Vec<u8>,
+    /// Every byte of the current line as received, control bytes included
+    line_bytes: Vec<u8>,
+    current_terminal: u8,
+    /// Control sequence in progress, carried over when one is split between reads
+    text_state: TextState,
+    /// Zero point of `host_monotonic_ns`; the read loop shares one across channels
+    session_start: std::time::Instant,
+    /// Monotonic time of the previous line (µs), for deltas and burst detection
+    last_host_us: Option<u64>,
+    /// Device time of the previous line (µs), if it had one on the device base
+    last_device_us: Option<u64>,
+    /// Stamped on every entry in sessions reading more than one control block
+    core_name: Option<String>,
+    /// Name from the control block, refreshed on every attach
+    channel_name: Option<String>,
+    /// Log trigger pattern, copied from `SessionState.trigger`
+    trigger: Option<Regex>,
+    /// First line that matched `trigger`, already emitted; the read loop acts on it
+    fired: Option<LogEntry>,
+    /// Error entry still collecting continuation lines, emitted by the next parsed
+    /// line, the line cap, or `flush_pending` once the channel goes quiet
+    pending: Option<LogEntry>,
+    /// Mark entries as `backlog`
+    backlog: bool,
+    /// Text bytes seen since the read loop last checked for corruption
+    text_bytes: u64,
+    /// Of those, control bytes with no meaning here plus undecodable sequences
+    corrupt_bytes: u64,
+    /// The next line is the first since attaching, and may have started before
+    first_line_after_attach: bool,
+    /// Attached but not read yet: only bytes this first read finds already
+    /// buffered can start mid-line
+    awaiting_first_read: bool,
+    /// Set up on the first read of a channel with a codec
+    decoder: Option<FrameDecoder>,
 }
 
 impl RttParser {
+    fn new(channel: usize, options: ParseOptions) -> Self {
+        Self {
+            channel,
+            options,
+            line_buf: Vec::new(),
+            line_bytes: Vec::new(),
+            current_terminal: 0,
+            text_state: TextState::Payload,
+            session_start: std::time::Instant::now(),
+            last_host_us: None,
+            last_device_us: None,
+            core_name: None,
+            channel_name: None,
+            trigger: None,
+            fired: None,
+            pending: None,
+            backlog: false,
+            text_bytes: 0,
+            corrupt_bytes: 0,
+            first_line_after_attach: false,
+            awaiting_first_read: false,
+            decoder: None,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.line_buf.clear();
+        self.line_bytes.clear();
+        self.text_state = TextState::Payload;
+        self.pending = None;
+        self.first_line_after_attach = false;
+        self.awaiting_first_read = false;
+        self.decoder = None;
+    }
+
+    /// Send an entry to the sink and check it against the trigger.
+    fn finish(
+        &mut self,
+        mut entry: LogEntry,
+        stats: &mut RttStats,
+        sink: LogSink<'_>,
+    ) -> Result<(), ()> {
+        sink.emit(&mut entry, stats)?;
+        if self.fired.is_none()
+            && self
+                .trigger
+                .as_ref()
+                .is_some_and(|re| re.is_match(&entry.raw))
+        {
+            self.fired = Some(entry);
+        }
+        Ok(())
+    }
+
+    /// Emit the error entry held for continuation lines, if any.
+    fn flush_pending(&mut self, stats: &mut RttStats, sink: LogSink<'_>) -> Result<(), ()> {
+        match self.pending.take() {
+            Some(entry) => self.finish(entry, stats, sink),
+            None => Ok(()),
+        }
+    }
+
+    /// End of stream: emit a last line that never got its newline, and any
+    /// error entry still collecting continuations.
+    fn flush(&mut self, stats: &mut RttStats, sink: LogSink<'_>) -> Result<(), ()> {
+        if !self.is_binary() && !self.line_buf.is_empty() {
+            self.process_text(b"\n", stats, sink)?;
+        }
+        self.flush_pending(stats, sink)
+    }
+
+    /// Fill in host time, `display_time`, `delta_us` and `burst_start`.
+    fn stamp_times(&mut self, entry: &mut LogEntry, base: TimeBase) {
+        let host_ms = host_now_ms();
+        let host_us = host_ms * 1000;
+        let mono_ns = self.session_start.elapsed().as_nanos() as u64;
+        let mono_us = mono_ns / 1000;
+        let device_us = match base {
+            TimeBase::Device => entry
+                .device_timestamp
+                .as_deref()
+                .and_then(device_timestamp_us),
+            TimeBase::Host => None,
+        };
+        // Only compare like with like; a line on the other clock falls back to host
+        // time, measured monotonically so a wall-clock step can't fake a gap.
+        // A device clock going backwards (reboot) has no delta and starts a burst.
+        let delta = match (self.last_device_us, device_us) {
+            (Some(prev), Some(cur)) => cur.checked_sub(prev),
+            _ => self.last_host_us.map(|prev| mono_us.saturating_sub(prev)),
+        };
+        let gap_us = self.options.burst_gap_ms.unwrap_or(DEFAULT_BURST_GAP_MS) * 1000;
+
+        entry.host_timestamp = Some(host_ms);
+        entry.host_wallclock = Some(rfc3339_utc(host_ms));
+        entry.host_monotonic_ns = Some(mono_ns);
+        entry.display_time = Some(device_us.unwrap_or(host_us));
+        entry.delta_us = delta;
+        entry.burst_start = delta.is_none_or(|d| d > gap_us);
+        self.last_host_us = Some(mono_us);
+        self.last_device_us = device_us;
+    }
+
+    fn is_binary(&self) -> bool {
+        is_binary_channel(self.channel_name.as_deref())
+            || self.options.binary_channels.contains(&self.channel)
+    }
+
+    /// Binary channels: one raw entry per 16-byte row of hex, tagged with the
+    /// channel name. No line splitting, terminal switches or escapes.
+    fn process_binary(
+        &mut self,
+        bytes: &[u8],
+        stats: &mut RttStats,
+        sink: LogSink<'_>,
+    ) -> Result<(), ()> {
+        for row in bytes.chunks(BINARY_ROW_BYTES) {
+            let hex = hex_bytes(row);
+            let mut entry = LogEntry {
+                level: "raw".to_string(),
+                tag: self.channel_name.clone(),
+                tag_color: self.channel_name.as_deref().map(tag_color_index),
+                message: hex.clone(),
+                raw: hex,
+                channel: Some(self.channel),
+                core_name: self.core_name.clone(),
+                ..Default::default()
+            };
+            self.stamp_times(&mut entry, sink.time_base());
+            stats.lines += 1;
+            sink.emit(&mut entry, stats)?;
+        }
+        Ok(())
+    }
+
+    /// Base64 channels: one entry per read with the bytes in `raw_b64`, as
+    /// they came. Nothing is decoded, split or dropped.
+    fn process_base64(
+        &mut self,
+        bytes: &[u8],
+        stats: &mut RttStats,
+        sink: LogSink<'_>,
+    ) -> Result<(), ()> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let summary = format!("{} bytes", bytes.len());
+        self.emit_stamped(
+            LogEntry {
+                level: "raw".to_string(),
+                tag: self.channel_name.clone(),
+                tag_color: self.channel_name.as_deref().map(tag_color_index),
+                message: summary.clone(),
+                raw: summary,
+                raw_b64: Some(base64_encode(bytes)),
+                channel: Some(self.channel),
+                core_name: self.core_name.clone(),
+                ..Default::default()
+            },
+            stats,
+            sink,
+        )
+    }
+
+    /// Emit an entry made outside line parsing, on this parser's clock.
+    fn emit_stamped(
+        &mut self,
+        mut entry: LogEntry,
+        stats: &mut RttStats,
+        sink: LogSink<'_>,
+    ) -> Result<(), ()> {
+        self.stamp_times(&mut entry, sink.time_base());
+        stats.lines += 1;
+        sink.emit(&mut entry, stats)
+    }
+
+    /// The first read after attaching found the buffer empty: whatever comes
+    /// next was written after we attached and starts on a line boundary.
+    fn quiet_first_read(&mut self) {
+        self.awaiting_first_read = false;
+    }
+
+    /// Parse raw RTT bytes, emit log entries. Returns Err if the sink is closed.
+    fn process_bytes(
+        &mut self,
+        buf: &[u8],
+        count: usize,
+        stats: &mut RttStats,
+        sink: LogSink<'_>,
+    ) -> Result<(), ()> {
+        if std::mem::take(&mut self.awaiting_first_read) {
+            self.first_line_after_attach = self.line_buf.is_empty();
+        }
+        let decompressed;
+        let bytes = match self.options.channel_codecs.get(&self.channel) {
+            Some(&codec) => {
+                let decoder = match &mut self.decoder {
+                    Some(d) if d.codec == codec => d,
+                    slot => slot.insert(FrameDecoder::new(codec)),
+                };
+                let bad;
+                (decompressed, bad) = decoder.push(&buf[..count]);
+                self.text_bytes += bad;
+                self.corrupt_bytes += bad;
+                &decompressed[..]
+            }
+            None => &buf[..count],
+        };
+        if self.options.base64_channels.contains(&self.channel) {
+            return self.process_base64(bytes, stats, sink);
+        }
+        if self.is_binary() {
+            return self.process_binary(bytes, stats, sink);
+        }
+        self.process_text(bytes, stats, sink)
+    }
+
+    /// Split text into lines, handling terminal switches and escapes.
+    fn process_text(
+        &mut self,
+        buf: &[u8],
+        stats: &mut RttStats,
+        sink: LogSink<'_>,
+    ) -> Result<(), ()> {
+        self.text_bytes += buf.len() as u64;
+        for &b in buf {
+            // First finish a control sequence; a byte that can't continue it is payload
+            let payload = match self.text_state {
+                TextState::Payload => Some(b),
+                TextState::TerminalSwitch => {
+                    self.text_state = TextState::Payload;
+                    match terminal_switch_digit(b) {
+                        Some(terminal) => {
+                            self.current_terminal = terminal;
+                            None
+                        }
+                        // 0xFF is a letter in single-byte encodings (ÿ, я), and
+                        // in UTF-8 it decodes to U+FFFD and counts as corrupt
+                        None => {
+                            self.line_buf.push(0xFF);
+                            Some(b)
+                        }
+                    }
+                }
+                TextState::Escape if b == b'[' => {
+                    self.text_state = TextState::Csi;
+                    None
+                }
+                TextState::Escape => {
+                    self.text_state = TextState::Payload;
+                    Some(b)
+                }
+                TextState::Csi => match b {
+                    // Parameter and intermediate bytes
+                    0x20..=0x3F => None,
+                    0x40..=0x7E => {
+                        self.text_state = TextState::Payload;
+                        None
+                    }
+                    // Malformed; don't let it swallow the rest of the line
+                    _ => {
+                        self.text_state = TextState::Payload;
+                        Some(b)
+                    }
+                },
+            };
+            match payload {
+                Some(0xFF) => self.text_state = TextState::TerminalSwitch,
+                Some(0x1B) => self.text_state = TextState::Escape,
+                Some(b'\n') => {
+                    self.end_line(stats, sink)?;
+                    continue;
+                }
+                Some(b) if b < 0x20 && b != b'\r' && b != b'\t' => self.corrupt_bytes += 1,
+                Some(b) => self.line_buf.push(b),
+                None => {}
+            }
+            if self.options.include_hex {
+                self.line_bytes.push(b);
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode the finished line's payload and emit it.
+    fn end_line(&mut self, stats: &mut RttStats, sink: LogSink<'_>) -> Result<(), ()> {
+        let mut line = self.options.encoding.decode(&self.line_buf);
+        self.corrupt_bytes += line.matches('\u{FFFD}').count() as u64;
+        // DEL and C1 controls (U+0080-U+009F) only show up once decoded
+        let stray = |c: char| c.is_control() && c != '\t' && c != '\r';
+        if line.contains(stray) {
+            self.corrupt_bytes += line.matches(stray).count() as u64;
+            line.retain(|c| !stray(c));
+        }
+        let line = line.trim_end();
+        // A BOM means the stream really starts here; without one the
+        // first line after attach may be the tail of an older print
+        let (line, maybe_partial) = match std::mem::take(&mut self.first_line_after_attach) {
+            true => match line.strip_prefix('\u{FEFF}') {
+                Some(rest) => (rest, false),
+                None => (line, true),
+            },
+            false => (line, false),
+        };
+        let bytes = std::mem::take(&mut self.line_bytes);
+        self.line_buf.clear();
+
+        if line.is_empty() && !self.options.keep_blank_lines {
+            return Ok(());
+        }
+
+        let parse_start = std::time::Instant::now();
+        let mut entry = parse_line(line, self.options.keep_tag_in_message);
+        PERF.parse_ns
+            .fetch_add(parse_start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        PERF.lines_parsed.fetch_add(1, Ordering::Relaxed);
+        // A line in the firmware's log format started cleanly
+        if maybe_partial && entry.level == "raw" {
+            if !self.options.keep_partial_first_line {
+                return Ok(());
+            }
+            entry.partial = true;
+        }
+        entry.terminal = Some(self.current_terminal);
+        entry.terminal_name = self
+            .options
+            .terminal_names
+            .get(&self.current_terminal)
+            .cloned();
+        entry.backlog = self.backlog;
+        if self.options.parse_kv {
+            entry.fields = parse_kv_fields(&entry.message);
+        }
+        entry.channel = Some(self.channel);
+        entry.core_name = self.core_name.clone();
+        self.stamp_times(&mut entry, sink.time_base());
+        if self.options.include_hex {
+            entry.raw_hex = Some(hex_bytes(&bytes));
+        }
+        stats.lines += 1;
+
+        // An unparsed line right after an error belongs to it; a
+        // kept blank line ends the block instead
+        if entry.level == "raw" && !entry.raw.is_empty() {
+            if let Some(pending) = self.pending.as_mut() {
+                pending.message.push('\n');
+                pending.message.push_str(&entry.message);
+                pending.raw.push('\n');
+                pending.raw.push_str(&entry.raw);
+                if let (Some(hex), Some(more)) =
+                    (pending.raw_hex.as_mut(), entry.raw_hex.as_deref())
+                {
+                    hex.push_str(" 0A ");
+                    hex.push_str(more);
+                }
+                pending.continuation_lines += 1;
+                if pending.continuation_lines >= MAX_CONTINUATION_LINES {
+                    self.flush_pending(stats, sink)?;
+                }
+                return Ok(());
+            }
+        }
+        self.flush_pending(stats, sink)?;
+        if self.options.merge_continuations && entry.level == "error" {
+            self.pending = Some(entry);
+            Ok(())
+        } else {
+            self.finish(entry, stats, sink)
+        }
+    }
+}
+
+// ── RTT read loop — returns when connection is lost or user stops ──
+
+const DEFAULT_CORRUPTION_WARN_PERCENT: u8 = 5;
+/// Fewer text bytes than this in one stats interval are too few to judge
+const CORRUPTION_MIN_BYTES: u64 = 256;
+/// The corruption hint repeats at most this often while the garbage lasts
+const CORRUPTION_WARN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Share of corrupt text bytes across all parsers since the last call, in
+/// percent, and reset the counts. An SWD clock too fast for the wiring or a
+/// marginal Vtref flips bits in RTT reads, which shows up as stray control
+/// bytes and broken UTF-8 long before reads fail outright.
+fn take_corruption_percent(parsers: &mut [Vec<RttParser>]) -> Option<f64> {
+    let (mut text, mut corrupt) = (0, 0);
+    for parser in parsers.iter_mut().flatten() {
+        text += std::mem::take(&mut parser.text_bytes);
+        corrupt += std::mem::take(&mut parser.corrupt_bytes);
+    }
+    (text >= CORRUPTION_MIN_BYTES).then(|| corrupt as f64 * 100.0 / text as f64)
+}
+
+fn emit_idle_disconnect(sink: LogSink, secs: u64) {
+    sink.status(
+        "warn",
+        &format!("Auto-disconnected due to inactivity: no RTT data for {secs} s. The probe is free for others."),
+    );
+}
+
+// ── Boot-loop detection ──
+
+/// This many disconnects within `BOOT_LOOP_WINDOW` count as a boot loop
+const BOOT_LOOP_RESETS: usize = 3;
+const BOOT_LOOP_WINDOW: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Payload of `rtt-boot-loop`. `resets` is 0 once the target stays up again.
+#[derive(Clone, Serialize)]
+struct BootLoopStatus {
+    resets: u64,
+    elapsed_s: u64,
+}
+
+/// Collapses a crash loop's reconnects into one warning and a counter.
+#[derive(Default)]
+struct BootLoop {
+    /// Disconnects within the last window
+    recent: std::collections::VecDeque<std::time::Instant>,
+    /// First disconnect of the loop and how many since, while looping
+    looping: Option<(std::time::Instant, u64)>,
+}
+
+impl BootLoop {
+    /// Count a disconnect. Warns when it starts a loop, updates the counter after.
+    fn record(&mut self, sink: LogSink) {
+        let now = std::time::Instant::now();
+        self.recent.push_back(now);
+        while self
+            .recent
+            .front()
+            .is_some_and(|&t| now.duration_since(t) > BOOT_LOOP_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        let started = match &mut self.looping {
+            Some((_, resets)) => {
+                *resets += 1;
+                false
+            }
+            None if self.recent.len() >= BOOT_LOOP_RESETS => {
+                self.looping = self.recent.front().map(|&t| (t, self.recent.len() as u64));
+                true
+            }
+            None => return,
+        };
+        let Some((since, resets)) = self.looping else {
+            return;
+        };
+        let elapsed_s = now.duration_since(since).as_secs().max(1);
+        if started {
+            sink.status(
+                "warn",
+                &format!("Target is boot-looping ({resets} resets in {elapsed_s}s). Hiding reconnect messages until it stays up; the count is in the toolbar."),
+            );
+        }
+        if let Some(app) = sink.app() {
+            let _ = app.emit("rtt-boot-loop", BootLoopStatus { resets, elapsed_s });
+        }
+    }
+
+    fn is_looping(&self) -> bool {
+        self.looping.is_some()
+    }
+
+    /// Once the target has stayed up for a whole window, end the loop and say so.
+    fn settle(&mut self, sink: LogSink) {
+        let Some((_, resets)) = self.looping else {
+            return;
+        };
+        if self
+            .recent
+            .back()
+            .is_some_and(|t| t.elapsed() < BOOT_LOOP_WINDOW)
+        {
+            return;
+        }
+        self.looping = None;
+        self.recent.clear();
+        sink.status(
+            "info",
+            &format!(
+                "Target stayed up for {}s after {resets} resets; boot loop over.",
+                BOOT_LOOP_WINDOW.as_secs()
+            ),
+        );
+        if let Some(app) = sink.app() {
+            let _ = app.emit(
+                "rtt-boot-loop",
+                BootLoopStatus {
+                    resets: 0,
+                    elapsed_s: 0,
+                },
+            );
+        }
+    }
+}
+
+// ── Channel read strategy ──
+
+/// Reads of one channel per pass under `ReadStrategy::Priority`, so a channel
+/// that never empties still lets commands and the stop flag through
+const PRIORITY_MAX_READS: usize = 8;
+/// Bytes per channel per pass under `ReadStrategy::Fair`
+const FAIR_READ_BUDGET: usize = 512;
+
+/// How one pass of the read loop shares its time between up channels.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum ReadStrategy {
+    /// One read of up to a full buffer per channel, the first channel moving
+    /// along each pass
+    #[default]
+    RoundRobin,
+    /// Channels in number order, each drained before the next: channel 0
+    /// first, at the cost of the others when it floods
+    Priority,
+    /// Like round robin, but at most `FAIR_READ_BUDGET` bytes per channel;
+    /// a flood waits in the target's buffer while quiet channels keep pace
+    Fair,
+}
+
+impl ReadStrategy {
+    const NAMES: [&'static str; 3] = ["round_robin", "priority", "fair"];
+
+    fn parse(name: &str) -> Result<Self, AppError> {
+        match name.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "round_robin" => Ok(Self::RoundRobin),
+            "priority" => Ok(Self::Priority),
+            "fair" => Ok(Self::Fair),
+            _ => Err(AppError::InvalidInput(format!(
+                "Unknown channel read strategy '{name}' (expected one of: {})",
+                Self::NAMES.join(", ")
+            ))),
+        }
+    }
+
+    fn from_profile(profile: &Profile) -> Self {
+        profile
+            .channel_read_strategy
+            .as_deref()
+            .and_then(|name| Self::parse(name).ok())
+            .unwrap_or_default()
+    }
+
+    /// The channel to read `k`-th of `count` in pass number `pass`.
+    fn channel_at(self, k: usize, count: usize, pass: usize) -> usize {
+        match self {
+            Self::Priority => k,
+            Self::RoundRobin | Self::Fair => (k + pass) % count,
+        }
+    }
+
+    /// (reads per channel, bytes per read) in one pass.
+    fn budget(self, buf_len: usize) -> (usize, usize) {
+        match self {
+            Self::RoundRobin => (1, buf_len),
+            Self::Priority => (PRIORITY_MAX_READS, buf_len),
+            Self::Fair => (1, FAIR_READ_BUDGET.min(buf_len)),
+        }
+    }
+}
+
+enum ReadResult {
+    Disconnected, // connection lost, should reconnect
+    Unstable,     // attached, but reads failed before any succeeded
+    Stopped,      // user requested stop
+    AppClosed,    // webview gone
+    Reattach,     // connection settings changed, attach again right away
+}
+
+/// Work for the RTT thread, which owns the attached core. Handled between reads.
+enum RttCommand {
+    /// Write a token to down channel 0 and time its echo on any up channel
+    Ping(tokio::sync::oneshot::Sender<Result<f64, AppError>>),
+    /// Start polling a variable (replaces a watch on the same symbol)
+    AddWatch(Watch),
+    RemoveWatch(String),
+    /// Arm or clear the log trigger
+    SetTrigger(Option<LogTrigger>),
+    /// Read registers and this many bytes of stack from the halted core
+    Snapshot(
+        u32,
+        tokio::sync::oneshot::Sender<Result<CoreSnapshot, AppError>>,
+    ),
+    /// Unwind the halted core's stack from its exception handler out
+    UnwindFault(
+        DebugFrameTable,
+        tokio::sync::oneshot::Sender<Result<FaultBacktrace, AppError>>,
+    ),
+    /// Listen for GDB on this local port (replaces an existing listener)
+    StartGdb(u16, tokio::sync::oneshot::Sender<Result<String, AppError>>),
+    StopGdb,
+    /// Count bytes instead of parsing them for this long, then report
+    Benchmark(
+        std::time::Duration,
+        tokio::sync::oneshot::Sender<Result<BenchmarkReport, AppError>>,
+    ),
+    /// Read how full each up buffer is, on every attached core
+    ChannelFill(tokio::sync::oneshot::Sender<Result<Vec<ChannelFill>, AppError>>),
+    /// Read DWT_CYCCNT, zeroing it first when the flag is set
+    CycleCounter(bool, tokio::sync::oneshot::Sender<Result<u32, AppError>>),
+    /// Read (address, length) regions of target memory
+    ReadMemory(
+        Vec<(u64, usize)>,
+        tokio::sync::oneshot::Sender<Result<Vec<Vec<u8>>, AppError>>,
+    ),
+    /// Read a register of this many bits, with an access of that width
+    ReadRegister(
+        u64,
+        u32,
+        tokio::sync::oneshot::Sender<Result<u64, AppError>>,
+    ),
+    /// Write (address, bits, value), halting the core around it when the flag
+    /// is set; replies with the value it replaced
+    WriteMemory(
+        u64,
+        u32,
+        u64,
+        bool,
+        tokio::sync::oneshot::Sender<Result<u64, AppError>>,
+    ),
+    /// Set the bootloader flag and reset the core
+    EnterBootloader(
+        BootloaderWrite,
+        tokio::sync::oneshot::Sender<Result<(), AppError>>,
+    ),
+}
+
+/// How long `rtt_ping` waits for the firmware to echo the token
+const PING_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+struct PendingPing {
+    token: Vec<u8>,
+    sent: std::time::Instant,
+    /// Tail of recent up-channel bytes, so a token split across reads still matches
+    seen: Vec<u8>,
+    reply: tokio::sync::oneshot::Sender<Result<f64, AppError>>,
+}
+
+/// One control block of the running session, as `list_control_blocks` reports it.
+#[derive(Debug, Clone, Serialize)]
+struct ControlBlockInfo {
+    /// Matches the `core_name` of its entries; None in single-block sessions
+    label: Option<String>,
+    core: usize,
+    address: String,
+    up_channels: Vec<Option<String>>,
+}
+
+impl ControlBlockInfo {
+    fn of(rc: &RttCore) -> Self {
+        Self {
+            label: rc.name.clone(),
+            core: rc.index,
+            address: format!("0x{:08X}", rc.rtt.ptr()),
+            up_channels: rc
+                .rtt
+                .up_channels
+                .iter()
+                .map(|ch| ch.name().map(str::to_string))
+                .collect(),
+        }
+    }
+}
+
+/// An attached control block and the core it lives on.
+struct RttCore {
+    index: usize,
+    /// Label for entries when the session reads more than one control block:
+    /// "app"/"net" for both nRF5340 cores, else from `Profile.extra_rtt_blocks`
+    name: Option<String>,
+    rtt: probe_rs::rtt::Rtt,
+}
+
+/// Read-loop state that survives reconnects within one `start_rtt`.
+struct SessionState {
+    /// Zero point of every entry's `host_monotonic_ns`
+    started: std::time::Instant,
+    /// Per attached core (same order as the read loop's cores), one parser per up channel
+    parsers: Vec<Vec<RttParser>>,
+    options: ParseOptions,
+    stats: RttStats,
+    commands: std::sync::mpsc::Receiver<RttCommand>,
+    ping: Option<PendingPing>,
+    /// Sorted by address so neighbours can share a read
+    watches: Vec<Watch>,
+    last_watch_poll: std::time::Instant,
+    trigger: Option<LogTrigger>,
+    /// Kept across reconnects; the GDB client itself is dropped with the attach
+    gdb_listener: Option<std::net::TcpListener>,
+    /// While set, reads are counted and discarded instead of parsed
+    benchmark: Option<Benchmark>,
+    /// The backlog is replayed on the first attach only; later ones would repeat it
+    backlog_done: bool,
+    /// Answered by the read loop, which can reach every core
+    fill_request: Option<tokio::sync::oneshot::Sender<Result<Vec<ChannelFill>, AppError>>>,
+    /// When the read loop last warned about garbled text
+    last_corruption_warn: Option<std::time::Instant>,
+    /// Clock auto-tune stepped down to; reported once a read succeeds at it
+    tuned_speed_khz: Option<u32>,
+    /// From `Profile.idle_timeout_s`
+    idle_timeout: Option<std::time::Duration>,
+    /// Last time any up channel had data, or when the session started
+    last_data: std::time::Instant,
+    boot_loop: BootLoop,
+    /// From `Profile.channel_read_strategy`
+    read_strategy: ReadStrategy,
+    /// Read-loop passes so far, for rotating the first channel
+    read_pass: usize,
+}
+
+impl SessionState {
+    fn new(commands: std::sync::mpsc::Receiver<RttCommand>) -> Self {
+        Self {
+            started: std::time::Instant::now(),
+            parsers: Vec::new(),
+            options: ParseOptions::default(),
+            stats: RttStats::default(),
+            commands,
+            ping: None,
+            watches: Vec::new(),
+            last_watch_poll: std::time::Instant::now(),
+            trigger: None,
+            gdb_listener: None,
+            benchmark: None,
+            backlog_done: false,
+            fill_request: None,
+            last_corruption_warn: None,
+            tuned_speed_khz: None,
+            idle_timeout: None,
+            last_data: std::time::Instant::now(),
+            boot_loop: BootLoop::default(),
+            read_strategy: ReadStrategy::default(),
+            read_pass: 0,
+        }
+    }
+
+    /// The idle timeout, in seconds, once it has run out.
+    fn idle_expired(&self) -> Option<u64> {
+        self.idle_timeout
+            .filter(|&timeout| self.last_data.elapsed() >= timeout)
+            .map(|timeout| timeout.as_secs())
+    }
+
+    /// Arm or clear the trigger on every parser. Clearing also drops pending matches.
+    fn set_trigger(&mut self, trigger: Option<LogTrigger>) {
+        for parser in self.parsers.iter_mut().flatten() {
+            parser.trigger = trigger.as_ref().map(|t| t.pattern.clone());
+            parser.fired = None;
+        }
+        self.trigger = trigger;
+    }
+
+    fn handle_commands(&mut self, rtt: &mut probe_rs::rtt::Rtt, core: &mut probe_rs::Core<'_>) {
+        while let Ok(cmd) = self.commands.try_recv() {
+            match cmd {
+                RttCommand::Ping(reply) => {
+                    if self.ping.is_some() {
+                        let _ = reply.send(Err(AppError::InvalidInput(
+                            "A ping is already in flight".to_string(),
+                        )));
+                        continue;
+                    }
+                    match send_ping_token(rtt, core) {
+                        Ok(token) => {
+                            self.ping = Some(PendingPing {
+                                token,
+                                sent: std::time::Instant::now(),
+                                seen: Vec::new(),
+                                reply,
+                            });
+                        }
+                        Err(e) => {
+                            let _ = reply.send(Err(e));
+                        }
+                    }
+                }
+                RttCommand::AddWatch(watch) => {
+                    self.watches.retain(|w| w.symbol != watch.symbol);
+                    let pos = self.watches.partition_point(|w| w.address < watch.address);
+                    self.watches.insert(pos, watch);
+                }
+                RttCommand::RemoveWatch(symbol) => {
+                    self.watches.retain(|w| w.symbol != symbol);
+                }
+                RttCommand::SetTrigger(trigger) => self.set_trigger(trigger),
+                RttCommand::Snapshot(stack_bytes, reply) => {
+                    let _ = reply.send(read_core_snapshot(core, stack_bytes));
+                }
+                RttCommand::UnwindFault(table, reply) => {
+                    let _ = reply.send(unwind_fault_on_core(core, &table));
+                }
+                RttCommand::StartGdb(port, reply) => {
+                    let result = bind_gdb_listener(core, port).map(|listener| {
+                        self.gdb_listener = Some(listener);
+                        format!("127.0.0.1:{port}")
+                    });
+                    let _ = reply.send(result);
+                }
+                RttCommand::StopGdb => self.gdb_listener = None,
+                RttCommand::Benchmark(duration, reply) => {
+                    if self.benchmark.is_some() {
+                        let _ = reply.send(Err(AppError::InvalidInput(
+                            "A benchmark is already running".to_string(),
+                        )));
+                        continue;
+                    }
+                    self.benchmark = Some(Benchmark::new(duration, reply));
+                }
+                RttCommand::ChannelFill(reply) => self.fill_request = Some(reply),
+                RttCommand::CycleCounter(reset, reply) => {
+                    let _ = reply.send(cycle_counter(core, reset));
+                }
+                RttCommand::ReadMemory(regions, reply) => {
+                    let _ = reply.send(read_memory_regions(core, &regions));
+                }
+                RttCommand::ReadRegister(address, bits, reply) => {
+                    let _ = reply.send(read_sized(core, address, bits));
+                }
+                RttCommand::WriteMemory(address, bits, value, halt, reply) => {
+                    let _ = reply.send(poke_on_core(core, address, bits, value, halt));
+                }
+                RttCommand::EnterBootloader(write, reply) => {
+                    let _ = reply.send(enter_bootloader_on_core(core, &write));
+                }
+            }
+        }
+    }
+
+    /// Read every watch, merging nearby ones into a single read, and emit
+    /// `watch-update` for values that changed. Failed reads are retried next poll.
+    fn poll_watches(&mut self, core: &mut probe_rs::Core<'_>, app: &AppHandle) {
+        if self.watches.is_empty() || self.last_watch_poll.elapsed() < WATCH_INTERVAL {
+            return;
+        }
+        self.last_watch_poll = std::time::Instant::now();
+
+        let mut i = 0;
+        while i < self.watches.len() {
+            let start = self.watches[i].address;
+            let mut end = self.watches[i].end();
+            let mut j = i + 1;
+            while j < self.watches.len() && self.watches[j].address <= end + WATCH_MERGE_GAP {
+                end = end.max(self.watches[j].end());
+                j += 1;
+            }
+            let mut buf = vec![0u8; (end - start) as usize];
+            if core.read(start, &mut buf).is_ok() {
+                for w in &mut self.watches[i..j] {
+                    let offset = (w.address - start) as usize;
+                    let bytes = &buf[offset..offset + w.kind.size()];
+                    if w.last.as_deref() != Some(bytes) {
+                        w.last = Some(bytes.to_vec());
+                        let _ = app.emit(
+                            "watch-update",
+                            WatchUpdate {
+                                symbol: w.symbol.clone(),
+                                value: w.kind.decode(bytes),
+                            },
+                        );
+                    }
+                }
+            }
+            i = j;
+        }
+    }
+
+    fn reset_parsers(&mut self) {
+        self.parsers.iter_mut().flatten().for_each(RttParser::reset);
+    }
+
+    fn expire_ping(&mut self) {
+        if self
+            .ping
+            .as_ref()
+            .is_some_and(|p| p.sent.elapsed() > PING_TIMEOUT)
+        {
+            if let Some(ping) = self.ping.take() {
+                let _ = ping.reply.send(Err(AppError::NotConnected(format!(
+                    "No echo within {} ms — target unreachable or firmware doesn't echo",
+                    PING_TIMEOUT.as_millis()
+                ))));
+            }
+        }
+    }
+}
+
+/// Look for the ping token in freshly read bytes.
+fn check_ping_echo(pending: &mut Option<PendingPing>, data: &[u8]) {
+    let Some(ping) = pending.as_mut() else {
+        return;
+    };
+    ping.seen.extend_from_slice(data);
+    if ping.seen.windows(ping.token.len()).any(|w| w == ping.token) {
+        let ms = ping.sent.elapsed().as_secs_f64() * 1000.0;
+        if let Some(ping) = pending.take() {
+            let _ = ping.reply.send(Ok(ms));
+        }
+        return;
+    }
+    let keep = ping.token.len().saturating_sub(1);
+    let drop = ping.seen.len().saturating_sub(keep);
+    ping.seen.drain(..drop);
+}
+
+/// Write a unique token followed by as many backspaces, so a shell that
+/// echoes input is left with an empty command line.
+fn send_ping_token(
+    rtt: &mut probe_rs::rtt::Rtt,
+    core: &mut probe_rs::Core<'_>,
+) -> Result<Vec<u8>, AppError> {
+    let down = rtt
+        .down_channels()
+        .first_mut()
+        .ok_or_else(|| AppError::InvalidInput("Target has no RTT down channel".to_string()))?;
+    static NEXT_TOKEN: AtomicU64 = AtomicU64::new(0);
+    let token = format!(
+        "#{:04X}#",
+        NEXT_TOKEN.fetch_add(1, Ordering::Relaxed) & 0xFFFF
+    )
+    .into_bytes();
+    let mut bytes = token.clone();
+    bytes.extend(std::iter::repeat_n(0x08, token.len()));
+    let written = down
+        .write(core, &bytes)
+        .map_err(|e| AppError::Other(format!("Down channel write failed: {e}")))?;
+    if written < bytes.len() {
+        return Err(AppError::Other(
+            "Down channel full — firmware isn't reading input".to_string(),
+        ));
+    }
+    Ok(token)
+}
+
+fn rtt_read_loop(
+    session: &mut probe_rs::Session,
+    cores: &mut [RttCore],
+    state: &mut SessionState,
+    stop_flag: &Arc<AtomicBool>,
+    profile: &Profile,
+    sink: LogSink,
+) -> ReadResult {
+    let mut buf = [0u8; 4096];
+    let mut consecutive_errors = 0u32;
+    let mut any_read_ok = false;
+    let mut last_stats = std::time::Instant::now();
+    let mut backoff = IdleBackoff::default();
+    // All core access goes through the GDB target so a GDB client can share
+    // the session; it is only ever driven from this thread, between reads
+    let mut gdb_target = GdbTarget {
+        session,
+        core: cores[0].index,
+    };
+    let mut gdb: Option<GdbStub<'_>> = None;
+
+    // One parser per up channel — each has its own line buffer and terminal state
+    state.parsers.resize_with(cores.len(), Vec::new);
+    for (rc, parsers) in cores.iter_mut().zip(state.parsers.iter_mut()) {
+        while parsers.len() < rc.rtt.up_channels().len() {
+            parsers.push(RttParser::new(parsers.len(), state.options.clone()));
+        }
+        // A reattach can add or drop the second core, which changes the labels
+        for (ch, parser) in rc.rtt.up_channels().iter().zip(parsers.iter_mut()) {
+            parser.core_name = rc.name.clone();
+            parser.session_start = state.started;
+            parser.channel_name = ch.name().map(str::to_string);
+            parser.trigger = state.trigger.as_ref().map(|t| t.pattern.clone());
+            // Data already in the buffer may start mid-line; the first read tells
+            parser.awaiting_first_read = true;
+        }
+    }
+
+    if !state.backlog_done {
+        state.backlog_done = true;
+        if let Some(symbol) = profile.backlog_symbol.as_deref() {
+            match (
+                profile.elf_path.as_deref(),
+                gdb_target.session.core(cores[0].index),
+            ) {
+                (Some(elf_path), Ok(mut core)) => {
+                    emit_backlog(sink, &mut core, state, elf_path, symbol)
+                }
+                (None, _) => sink.status("warn", "No backlog: the profile has no ELF."),
+                (_, Err(e)) => sink.status("warn", &format!("No backlog: {e}")),
+            }
+        }
+    }
+
+    loop {
+        if stop_flag.load(Ordering::Relaxed) {
+            for parser in state.parsers.iter_mut().flatten() {
+                let _ = parser.flush(&mut state.stats, sink);
+            }
+            sink.status("info", "Disconnected by user.");
+            return ReadResult::Stopped;
+        }
+        if let Some(secs) = state.idle_expired() {
+            for parser in state.parsers.iter_mut().flatten() {
+                let _ = parser.flush(&mut state.stats, sink);
+            }
+            emit_idle_disconnect(sink, secs);
+            stop_flag.store(true, Ordering::Relaxed);
+            return ReadResult::Stopped;
+        }
+
+        // Only the GUI edits the profile mid-session
+        if let Some(app_state) = sink.app().map(|app| app.state::<AppState>()) {
+            if app_state.reattach.swap(false, Ordering::Relaxed) {
+                state.reset_parsers();
+                return ReadResult::Reattach;
+            }
+            if app_state.hot_reload.swap(false, Ordering::Relaxed) {
+                if let Some(profile) = app_state.active_profile.lock().unwrap().as_ref() {
+                    state.options = ParseOptions::from_profile(profile);
+                    state.idle_timeout = profile.idle_timeout_s.map(std::time::Duration::from_secs);
+                    state.read_strategy = ReadStrategy::from_profile(profile);
+                }
+                for parser in state.parsers.iter_mut().flatten() {
+                    parser.options = state.options.clone();
+                }
+                sink.status("info", "Settings applied.");
+            }
+        }
+
+        let mut got_data = false;
+        let mut read_error = None;
+        let mut trigger_fired = false;
+
+        poll_gdb(&mut gdb, &mut gdb_target, state.gdb_listener.as_ref(), sink);
+
+        // Commands (ping, watches) go to the profile's core
+        match gdb_target.session.core(cores[0].index) {
+            Ok(mut core) => {
+                state.handle_commands(&mut cores[0].rtt, &mut core);
+                state.expire_ping();
+                if let Some(app) = sink.app() {
+                    state.poll_watches(&mut core, app);
+                }
+            }
+            Err(e) => read_error = Some(error_chain(&e)),
+        }
+
+        // Cores are borrowed from the session one at a time, so both nRF5340
+        // cores can be read in one pass
+        for (i, (rc, parsers)) in cores.iter_mut().zip(state.parsers.iter_mut()).enumerate() {
+            if read_error.is_some() {
+                break;
+            }
+            let mut core = match gdb_target.session.core(rc.index) {
+                Ok(c) => c,
+                Err(e) => {
+                    read_error = Some(error_chain(&e));
+                    break;
+                }
+            };
+            let stats = &mut state.stats;
+            let channels = rc.rtt.up_channels();
+            let count = channels.len().min(parsers.len());
+            let (reads, limit) = state.read_strategy.budget(buf.len());
+            for k in 0..count {
+                let idx = state.read_strategy.channel_at(k, count, state.read_pass);
+                let (ch, parser) = (&mut channels[idx], &mut parsers[idx]);
+                for _ in 0..reads {
+                    let result = ch.read(&mut core, &mut buf[..limit]);
+                    if result.is_ok() && !any_read_ok {
+                        any_read_ok = true;
+                        if let Some(khz) = state.tuned_speed_khz.take() {
+                            sink.status(
+                                "info",
+                                &format!("RTT reads are stable at {khz} kHz. Set it as the profile's probe speed to skip tuning next time."),
+                            );
+                            if let Some(app) = sink.app() {
+                                let _ = app.emit("rtt-speed-tuned", khz);
+                            }
+                        }
+                    }
+                    // A short read emptied the buffer; only a full one can have more behind it
+                    let full = matches!(result, Ok(n) if n == limit);
+                    match result {
+                        Ok(count) if count > 0 => {
+                            got_data = true;
+                            state.last_data = std::time::Instant::now();
+                            consecutive_errors = 0;
+                            stats.bytes += count as u64;
+                            PERF.bytes_read.fetch_add(count as u64, Ordering::Relaxed);
+                            if let Some(bench) = state.benchmark.as_mut() {
+                                bench.record(count, ch.buffer_size());
+                            } else {
+                                if parser.process_bytes(&buf, count, stats, sink).is_err() {
+                                    return ReadResult::AppClosed;
+                                }
+                                if i == 0 {
+                                    check_ping_echo(&mut state.ping, &buf[..count]);
+                                }
+                            }
+                        }
+                        // Quiet channel: the held error entry has all its lines
+                        Ok(_) => {
+                            parser.quiet_first_read();
+                            if parser.flush_pending(stats, sink).is_err() {
+                                return ReadResult::AppClosed;
+                            }
+                        }
+                        Err(e) => {
+                            read_error = Some(error_chain(&e));
+                            break;
+                        }
+                    }
+                    // The matching line is already out; later reads still drain
+                    // the buffer since memory stays readable while halted
+                    if let (Some(entry), Some(trigger), Some(app)) =
+                        (parser.fired.take(), state.trigger.as_ref(), sink.app())
+                    {
+                        fire_trigger(trigger, &entry, &mut core, app);
+                        trigger_fired = true;
+                    }
+                    if !full {
+                        break;
+                    }
+                }
+                if read_error.is_some() {
+                    break;
+                }
+            }
+        }
+        state.read_pass = state.read_pass.wrapping_add(1);
+
+        if any_read_ok {
+            state.boot_loop.settle(sink);
+        }
+
+        // One-shot: disarm once it has fired
+        if trigger_fired {
+            state.set_trigger(None);
+        }
+
+        if state.benchmark.as_ref().is_some_and(Benchmark::is_done) {
+            if let Some(bench) = state.benchmark.take() {
+                bench.finish();
+            }
+            // Lines cut off when measuring started would splice onto new ones
+            state.reset_parsers();
+            sink.status("info", "Benchmark finished, logging resumed.");
+        }
+
+        if let Some(chain) = read_error {
+            consecutive_errors += 1;
+            if consecutive_errors >= sink.error_threshold() {
+                sink.status(
+                    "warn",
+                    &format!("Lost connection: {}. Reconnecting...", chain[0]),
+                );
+                sink.last_error("Lost connection", chain);
+                state.reset_parsers();
+                return if any_read_ok {
+                    ReadResult::Disconnected
+                } else {
+                    ReadResult::Unstable
+                };
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+
+        let stats_due = last_stats.elapsed() >= std::time::Duration::from_secs(1);
+        if stats_due || state.fill_request.is_some() {
+            let mut fill = Vec::new();
+            let mut fill_error = None;
+            for rc in cores.iter() {
+                match gdb_target
+                    .session
+                    .core(rc.index)
+                    .and_then(|mut core| read_channel_fill(&rc.rtt, &mut core, rc.name.as_deref()))
+                {
+                    Ok(f) => fill.extend(f),
+                    Err(e) => fill_error = Some(e.to_string()),
+                }
+            }
+            if let Some(reply) = state.fill_request.take() {
+                let _ = reply.send(match &fill_error {
+                    Some(e) => Err(AppError::Other(format!("Failed to read channel fill: {e}"))),
+                    None => Ok(fill.clone()),
+                });
+            }
+            state.stats.channel_fill = fill;
+        }
+
+        if stats_due {
+            let corrupt = take_corruption_percent(&mut state.parsers);
+            if let (Some(percent), Some(limit)) = (corrupt, state.options.corruption_warn_percent) {
+                if percent > limit as f64
+                    && state
+                        .last_corruption_warn
+                        .is_none_or(|t| t.elapsed() >= CORRUPTION_WARN_INTERVAL)
+                {
+                    state.last_corruption_warn = Some(std::time::Instant::now());
+                    sink.status(
+                        "warn",
+                        &format!(
+                            "{percent:.0}% of RTT text in the last second was garbled. \
+                             The SWD clock may be too fast for the wiring, or Vtref marginal: \
+                             lower the probe speed, shorten the cable, or check ground and Vtref."
+                        ),
+                    );
+                }
+            }
+            state.stats.tick(last_stats.elapsed(), sink.emit_batching());
+            if let Some(app) = sink.app() {
+                let _ = app.emit("rtt-stats", &state.stats);
+                with_sqlite_log(app, SqliteLog::flush_if_due);
+            }
+            last_stats = std::time::Instant::now();
+        }
+
+        backoff.pace(sink.read_mode(), got_data);
+    }
+}
+
+// ── Log trigger ──
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum TriggerAction {
+    Halt,
+}
+
+/// Acts on the first line whose raw text matches `pattern`, then disarms.
+struct LogTrigger {
+    pattern: Regex,
+    action: TriggerAction,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TriggerFired {
+    pattern: String,
+    action: TriggerAction,
+    /// Id of the matching log entry
+    id: u64,
+    message: String,
+    core_name: Option<String>,
+    /// Where the core stopped, if the halt succeeded
+    pc: Option<u64>,
+}
+
+fn fire_trigger(
+    trigger: &LogTrigger,
+    entry: &LogEntry,
+    core: &mut probe_rs::Core<'_>,
+    app: &AppHandle,
+) {
+    let pc = match trigger.action {
+        TriggerAction::Halt => match core.halt(std::time::Duration::from_millis(100)) {
+            Ok(info) => {
+                emit_rtt_status(
+                    app,
+                    "warn",
+                    &format!("Trigger matched, core halted at PC {:#010X}.", info.pc),
+                );
+                Some(info.pc)
+            }
+            Err(e) => {
+                emit_rtt_status(
+                    app,
+                    "error",
+                    &format!("Trigger matched but halt failed: {e}"),
+                );
+                None
+            }
+        },
+    };
+    let _ = app.emit(
+        "trigger-fired",
+        TriggerFired {
+            pattern: trigger.pattern.as_str().to_string(),
+            action: trigger.action,
+            id: entry.id,
+            message: entry.raw.clone(),
+            core_name: entry.core_name.clone(),
+            pc,
+        },
+    );
+}
+
+/// Halt the core the first time a log line matches `pattern` (a regex on the
+/// raw line). `action` is "halt". Fires once; set it again to re-arm.
+#[tauri::command]
+async fn set_log_trigger(app: AppHandle, pattern: String, action: String) -> Result<(), AppError> {
+    let action = match action.as_str() {
+        "halt" => TriggerAction::Halt,
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Unknown trigger action: {action}"
+            )))
+        }
+    };
+    let pattern = Regex::new(&pattern)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid trigger pattern: {e}")))?;
+    send_rtt_command(
+        &app,
+        RttCommand::SetTrigger(Some(LogTrigger { pattern, action })),
+    )
+}
+
+#[tauri::command]
+async fn clear_log_trigger(app: AppHandle) -> Result<(), AppError> {
+    send_rtt_command(&app, RttCommand::SetTrigger(None))
+}
+
+// ── Core snapshot ──
+
+const DEFAULT_SNAPSHOT_STACK_BYTES: u32 = 256;
+const MAX_SNAPSHOT_STACK_BYTES: u32 = 16 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+struct SnapshotRegister {
+    name: String,
+    value: u64,
+}
+
+/// State of a halted core, read between RTT polls.
+#[derive(Debug, Clone, Serialize)]
+struct CoreSnapshot {
+    /// Every non-FPU register, in the core's own order
+    registers: Vec<SnapshotRegister>,
+    pc: u64,
+    sp: u64,
+    lr: u64,
+    /// "function+0xNN" from the profile's ELF, if it has one
+    pc_symbol: Option<String>,
+    lr_symbol: Option<String>,
+    /// Memory from SP upwards, as space-separated hex
+    stack_hex: String,
+}
+
+/// Runs on the RTT thread. Symbols are filled in afterwards by `core_snapshot`.
+fn read_core_snapshot(
+    core: &mut probe_rs::Core<'_>,
+    stack_bytes: u32,
+) -> Result<CoreSnapshot, AppError> {
+    let halted = core
+        .core_halted()
+        .map_err(|e| AppError::Other(format!("Failed to read core status: {e}")))?;
+    if !halted {
+        return Err(AppError::InvalidInput(
+            "Core is running; halt it first (e.g. with a log trigger)".to_string(),
+        ));
+    }
+    let read = |core: &mut probe_rs::Core<'_>, reg: &probe_rs::CoreRegister| {
+        core.read_core_reg::<u64>(reg)
+            .map_err(|e| AppError::Other(format!("Failed to read {}: {e}", reg.name())))
+    };
+
+    let mut registers = Vec::new();
+    for reg in core.registers().core_registers() {
+        registers.push(SnapshotRegister {
+            name: reg.name().to_string(),
+            value: read(core, reg)?,
+        });
+    }
+    let pc = read(core, core.program_counter())?;
+    let sp = read(core, core.stack_pointer())?;
+    let lr = read(core, core.return_address())?;
+
+    // A short read near the end of RAM is still useful, so halve until it fits
+    let mut stack = vec![0u8; stack_bytes as usize];
+    while !stack.is_empty() && core.read(sp, &mut stack).is_err() {
+        stack.truncate(stack.len() / 2);
+    }
+
+    Ok(CoreSnapshot {
+        registers,
+        pc,
+        sp,
+        lr,
+        pc_symbol: None,
+        lr_symbol: None,
+        stack_hex: hex_bytes(&stack),
+    })
+}
+
+/// Registers and the top of the stack of the halted core, with PC and LR
+/// symbolised from the profile's ELF. Also emitted into the log as a
+/// "snapshot" entry. `stack_bytes` defaults to 256.
+#[tauri::command]
+async fn core_snapshot(app: AppHandle, stack_bytes: Option<u32>) -> Result<CoreSnapshot, AppError> {
+    let stack_bytes = stack_bytes.unwrap_or(DEFAULT_SNAPSHOT_STACK_BYTES);
+    if stack_bytes > MAX_SNAPSHOT_STACK_BYTES {
+        return Err(AppError::InvalidInput(format!(
+            "Stack size must be at most {MAX_SNAPSHOT_STACK_BYTES} bytes"
+        )));
+    }
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    send_rtt_command(&app, RttCommand::Snapshot(stack_bytes, tx))?;
+    let mut snapshot = await_rtt_reply(rx).await?;
+
+    let elf_path = app
+        .state::<AppState>()
+        .active_profile
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|p| p.elf_path.clone());
+    if let Some(path) = elf_path {
+        let addrs = [snapshot.pc, snapshot.lr];
+        let names = tokio::task::spawn_blocking(move || elf_functions_at(&path, &addrs)).await;
+        // A missing or stale ELF just leaves the addresses bare
+        if let Ok(Ok(mut names)) = names {
+            snapshot.lr_symbol = names.pop().flatten();
+            snapshot.pc_symbol = names.pop().flatten();
+        }
+    }
+
+    let mut entry = snapshot_entry(&snapshot);
+    emit_log(&app, &mut entry)
+        .map_err(|e| AppError::Other(format!("Failed to emit snapshot: {e}")))?;
+    Ok(snapshot)
+}
+
+/// One collapsible entry: a PC/LR/SP summary line, then registers and stack.
+fn snapshot_entry(snapshot: &CoreSnapshot) -> LogEntry {
+    let with_symbol = |addr: u64, symbol: &Option<String>| match symbol {
+        Some(s) => format!("{addr:#010X} ({s})"),
+        None => format!("{addr:#010X}"),
+    };
+    let mut lines = vec![format!(
+        "Core snapshot: PC {} LR {} SP {:#010X}",
+        with_symbol(snapshot.pc, &snapshot.pc_symbol),
+        with_symbol(snapshot.lr, &snapshot.lr_symbol),
+        snapshot.sp
+    )];
+    for regs in snapshot.registers.chunks(4) {
+        lines.push(
+            regs.iter()
+                .map(|r| format!("{:>4} {:#010X}", r.name, r.value))
+                .collect::<Vec<_>>()
+                .join("  "),
+        );
+    }
+    // 16 bytes per row, addressed from SP
+    let stack: Vec<&str> = snapshot
+        .stack_hex
+        .split(' ')
+        .filter(|b| !b.is_empty())
+        .collect();
+    for (i, row) in stack.chunks(16).enumerate() {
+        lines.push(format!(
+            "{:#010X}: {}",
+            snapshot.sp + i as u64 * 16,
+            row.join(" ")
+        ));
+    }
+
+    let text = lines.join("\n");
+    let now = host_now_ms();
+    LogEntry {
+        level: "snapshot".to_string(),
+        message: text.clone(),
+        raw: text,
+        host_timestamp: Some(now),
+        display_time: Some(now * 1000),
+        continuation_lines: (lines.len() - 1) as u32,
+        ..Default::default()
+    }
+}
+
+// ── Fault backtrace ──
+
+/// Interrupt Control and State; VECTACTIVE is the exception being handled
+const ICSR: u64 = 0xE000_ED04;
+const ICSR_VECTACTIVE: u32 = 0x1FF;
+/// LR values from 0xFF000000 up are EXC_RETURN codes, not addresses
+const EXC_RETURN_PREFIX: u32 = 0xFF00_0000;
+/// EXC_RETURN bit set when the interrupted code was on the process stack
+const EXC_RETURN_PSP: u32 = 1 << 2;
+/// EXC_RETURN bit clear when the frame includes the FPU registers
+const EXC_RETURN_BASIC_FRAME: u32 = 1 << 4;
+/// Stacked xPSR bit set when the core padded SP to 8 bytes on entry
+const XPSR_STACK_ALIGN: u32 = 1 << 9;
+const MAX_UNWIND_FRAMES: usize = 32;
+
+/// r0-r15, `None` where the unwind lost track of a value
+type UnwindRegs = [Option<u32>; 16];
+
+#[derive(Debug, Clone, Serialize)]
+struct BacktraceFrame {
+    pc: u64,
+    sp: u64,
+    /// "function+0xNN" from the profile's ELF
+    function: Option<String>,
+    /// The exception interrupted this frame, so `pc` is the instruction that
+    /// faulted rather than a return address
+    interrupted: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FaultBacktrace {
+    /// The exception being handled, e.g. "HardFault"
+    exception: String,
+    /// Innermost first, starting in the handler
+    frames: Vec<BacktraceFrame>,
+    /// Why the unwind ended before an outermost frame, if it did
+    stopped: Option<String>,
+}
+
+/// `.debug_frame` of the profile's ELF: the CFI rules for finding a caller's
+/// registers from any PC.
+struct DebugFrameTable {
+    data: Vec<u8>,
+}
+
+impl DebugFrameTable {
+    fn load(elf_path: &str) -> Result<Self, AppError> {
+        let data = std::fs::read(elf_path)
+            .map_err(|e| AppError::Io(format!("Failed to read ELF file: {e}")))?;
+        let elf = goblin::elf::Elf::parse(&data)
+            .map_err(|e| AppError::Elf(format!("Failed to parse ELF: {e}")))?;
+        let section = elf
+            .section_headers
+            .iter()
+            .find(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(".debug_frame"))
+            .and_then(|sh| data.get(sh.file_range()?))
+            .ok_or_else(|| {
+                AppError::Elf("The ELF has no .debug_frame section; build it with -g".to_string())
+            })?;
+        Ok(Self {
+            data: section.to_vec(),
+        })
+    }
+
+    /// The caller's registers, with its PC in r15. `exact` looks up `regs[15]`
+    /// itself instead of the call just before it, for a frame that was
+    /// interrupted rather than one that made a call. Errors say why the
+    /// unwind can't go on.
+    fn caller(
+        &self,
+        core: &mut probe_rs::Core<'_>,
+        regs: &UnwindRegs,
+        exact: bool,
+    ) -> Result<UnwindRegs, String> {
+        use gimli::UnwindSection;
+
+        let pc = regs[15].ok_or("PC unknown")?;
+        let mut frame = gimli::DebugFrame::new(&self.data, gimli::LittleEndian);
+        frame.set_address_size(4);
+        let mut ctx = gimli::UnwindContext::new();
+        let lookup = if exact { pc } else { pc.saturating_sub(1) };
+        let row = frame
+            .unwind_info_for_address(
+                &gimli::BaseAddresses::default(),
+                &mut ctx,
+                lookup as u64,
+                gimli::DebugFrame::cie_from_offset,
+            )
+            .map_err(|e| match e {
+                gimli::Error::NoUnwindInfoForAddress => format!("no unwind info for {pc:#010X}"),
+                e => format!("bad unwind info for {pc:#010X}: {e}"),
+            })?;
+        let cfa = match row.cfa() {
+            gimli::CfaRule::RegisterAndOffset { register, offset } => regs
+                .get(register.0 as usize)
+                .copied()
+                .flatten()
+                .map(|base| (base as i64 + offset) as u32)
+                .ok_or_else(|| format!("CFA register unknown at {pc:#010X}"))?,
+            gimli::CfaRule::Expression(_) => {
+                return Err(format!("CFA expressions not supported ({pc:#010X})"))
+            }
+        };
+
+        let mut caller = [None; 16];
+        for (i, value) in caller.iter_mut().enumerate() {
+            *value =
+                match row.register(gimli::Register(i as u16)) {
+                    // Registers without a rule: the AAPCS caller-saved ones are
+                    // lost, the rest weren't touched
+                    gimli::RegisterRule::Undefined => match i {
+                        0..=3 | 12 => None,
+                        _ => regs[i],
+                    },
+                    gimli::RegisterRule::SameValue => regs[i],
+                    gimli::RegisterRule::Offset(offset) => {
+                        let addr = (cfa as i64 + offset) as u32;
+                        Some(core.read_word_32(addr as u64).map_err(|e| {
+                            format!("failed to read the stack at {addr:#010X}: {e}")
+                        })?)
+                    }
+                    gimli::RegisterRule::ValOffset(offset) => Some((cfa as i64 + offset) as u32),
+                    gimli::RegisterRule::Register(r) => regs.get(r.0 as usize).copied().flatten(),
+                    _ => None,
+                };
+        }
+        caller[13] = Some(cfa);
+        // The ARM return address column is LR
+        caller[15] = caller[14];
+        Ok(caller)
+    }
+}
+
+/// Walk the stack of the halted core from the exception handler out, through
+/// the hardware-stacked frame into the code that faulted and its callers.
+/// Runs on the RTT thread; functions are filled in by `unwind_fault`.
+fn unwind_fault_on_core(
+    core: &mut probe_rs::Core<'_>,
+    table: &DebugFrameTable,
+) -> Result<FaultBacktrace, AppError> {
+    let halted = core
+        .core_halted()
+        .map_err(|e| AppError::Other(format!("Failed to read core status: {e}")))?;
+    if !halted {
+        return Err(AppError::InvalidInput(
+            "Core is running; halt it in the fault handler first".to_string(),
+        ));
+    }
+    let icsr = core
+        .read_word_32(ICSR)
+        .map_err(|e| AppError::Other(format!("Failed to read ICSR: {e}")))?;
+    let exception = (icsr & ICSR_VECTACTIVE) as u16;
+    if exception == 0 {
+        return Err(AppError::InvalidInput(
+            "The core is not handling an exception, so there is no fault to unwind".to_string(),
+        ));
+    }
+
+    let mut regs: UnwindRegs = [None; 16];
+    for (i, value) in regs.iter_mut().enumerate() {
+        *value = Some(
+            core.read_core_reg::<u32>(probe_rs::RegisterId(i as u16))
+                .map_err(|e| AppError::Other(format!("Failed to read R{i}: {e}")))?,
+        );
+    }
+    let psp = match core.registers().psp() {
+        Some(reg) => Some(
+            core.read_core_reg::<u32>(reg)
+                .map_err(|e| AppError::Other(format!("Failed to read PSP: {e}")))?,
+        ),
+        None => None,
+    };
+
+    let mut frames = Vec::new();
+    let mut stopped = None;
+    // The halted PC is where the handler is, not a return address
+    let mut exact = true;
+    let mut interrupted = false;
+    while let (Some(pc), Some(sp)) = (regs[15], regs[13]) {
+        if frames.len() == MAX_UNWIND_FRAMES {
+            stopped = Some(format!("stopped after {MAX_UNWIND_FRAMES} frames"));
+            break;
+        }
+        frames.push(BacktraceFrame {
+            pc: pc as u64,
+            sp: sp as u64,
+            function: None,
+            interrupted,
+        });
+        let mut caller = match table.caller(core, &regs, exact) {
+            Ok(caller) => caller,
+            Err(reason) => {
+                stopped = Some(reason);
+                break;
+            }
+        };
+        let Some(ret) = caller[15] else {
+            stopped = Some("return address unknown".to_string());
+            break;
+        };
+
+        if ret & EXC_RETURN_PREFIX == EXC_RETURN_PREFIX {
+            // Returning from an exception: the caller's registers are in the
+            // frame the core pushed on entry
+            let frame_sp = if ret & EXC_RETURN_PSP != 0 {
+                psp
+            } else {
+                caller[13]
+            };
+            let Some(frame_sp) = frame_sp else {
+                stopped = Some("exception stack pointer unknown".to_string());
+                break;
+            };
+            let mut stacked = [0u32; 8];
+            if let Err(e) = core.read_32(frame_sp as u64, &mut stacked) {
+                stopped = Some(format!(
+                    "failed to read the exception frame at {frame_sp:#010X}: {e}"
+                ));
+                break;
+            }
+            let [r0, r1, r2, r3, r12, lr, pc, xpsr] = stacked;
+            let mut size = if ret & EXC_RETURN_BASIC_FRAME != 0 {
+                0x20
+            } else {
+                0x68
+            };
+            if xpsr & XPSR_STACK_ALIGN != 0 {
+                size += 4;
+            }
+            caller[..4].copy_from_slice(&[Some(r0), Some(r1), Some(r2), Some(r3)]);
+            caller[12] = Some(r12);
+            caller[13] = Some(frame_sp + size);
+            caller[14] = Some(lr);
+            caller[15] = Some(pc & !1);
+            regs = caller;
+            exact = true;
+            interrupted = true;
+            continue;
+        }
+
+        let ret = ret & !1;
+        let caller_sp = caller[13].unwrap_or(0);
+        if ret == 0 {
+            break;
+        }
+        if caller_sp < sp || (caller_sp == sp && ret == pc) {
+            stopped = Some(format!("stack does not unwind past {pc:#010X}"));
+            break;
+        }
+        caller[15] = Some(ret);
+        regs = caller;
+        exact = false;
+        interrupted = false;
+    }
+
+    Ok(FaultBacktrace {
+        exception: exception_name(exception),
+        frames,
+        stopped,
+    })
+}
+
+/// Symbolised backtrace of the fault the halted core is handling, unwound with
+/// the DWARF CFI in the profile's ELF. Halt the core in the handler first
+/// (e.g. with a breakpoint on HardFault_Handler). Also emitted into the log
+/// as a "snapshot" entry.
+#[tauri::command]
+async fn unwind_fault(app: AppHandle) -> Result<FaultBacktrace, AppError> {
+    let elf_path = app
+        .state::<AppState>()
+        .active_profile
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|p| p.elf_path.clone())
+        .ok_or_else(|| {
+            AppError::InvalidInput("The profile has no ELF to unwind with".to_string())
+        })?;
+    let path = elf_path.clone();
+    let table = tokio::task::spawn_blocking(move || DebugFrameTable::load(&path))
+        .await
+        .map_err(|e| AppError::Other(format!("ELF read task failed: {e}")))??;
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    send_rtt_command(&app, RttCommand::UnwindFault(table, tx))?;
+    let mut backtrace = await_rtt_reply(rx).await?;
+
+    let pcs: Vec<u64> = backtrace.frames.iter().map(|f| f.pc).collect();
+    let names = tokio::task::spawn_blocking(move || elf_functions_at(&elf_path, &pcs)).await;
+    if let Ok(Ok(names)) = names {
+        for (frame, name) in backtrace.frames.iter_mut().zip(names) {
+            frame.function = name;
+        }
+    }
+
+    let mut entry = backtrace_entry(&backtrace);
+    emit_log(&app, &mut entry)
+        .map_err(|e| AppError::Other(format!("Failed to emit backtrace: {e}")))?;
+    Ok(backtrace)
+}
+
+/// One collapsible entry: a summary line, then a line per frame.
+fn backtrace_entry(backtrace: &FaultBacktrace) -> LogEntry {
+    let mut lines = vec![format!(
+        "{} backtrace: {} frames",
+        backtrace.exception,
+        backtrace.frames.len()
+    )];
+    for (i, frame) in backtrace.frames.iter().enumerate() {
+        if frame.interrupted {
+            lines.push(format!("    --- {} ---", backtrace.exception));
+        }
+        let mut line = format!("#{i:<2} {:#010X}", frame.pc);
+        if let Some(function) = &frame.function {
+            line.push_str(&format!(" in {function}"));
+        }
+        if frame.interrupted {
+            line.push_str("  <- faulted here");
+        }
+        lines.push(line);
+    }
+    if let Some(reason) = &backtrace.stopped {
+        lines.push(format!("(unwind stopped: {reason})"));
+    }
+
+    let text = lines.join("\n");
+    let now = host_now_ms();
+    LogEntry {
+        level: "snapshot".to_string(),
+        message: text.clone(),
+        raw: text,
+        host_timestamp: Some(now),
+        display_time: Some(now * 1000),
+        continuation_lines: (lines.len() - 1) as u32,
+        ..Default::default()
+    }
+}
+
+// ── DWT cycle counter ──
+
+/// Debug Exception and Monitor Control; TRCENA powers the DWT
+const DEMCR: u64 = 0xE000_EDFC;
+const DEMCR_TRCENA: u32 = 1 << 24;
+const DWT_CTRL: u64 = 0xE000_1000;
+const DWT_CTRL_CYCCNTENA: u32 = 1 << 0;
+/// Set on parts without a cycle counter (all ARMv6-M, some ARMv8-M baseline)
+const DWT_CTRL_NOCYCCNT: u32 = 1 << 25;
+const DWT_CYCCNT: u64 = 0xE000_1004;
+
+/// Runs on the RTT thread. Turns on the trace clock and the counter if the
+/// firmware hasn't, then reads DWT_CYCCNT (zeroing it first with `reset`).
+/// Counts wrap at 2^32 cycles — about 27 s at 160 MHz.
+fn cycle_counter(core: &mut probe_rs::Core<'_>, reset: bool) -> Result<u32, AppError> {
+    let err = |e: probe_rs::Error| AppError::Other(format!("DWT access failed: {e}"));
+    if core.architecture() != probe_rs::Architecture::Arm {
+        return Err(AppError::InvalidInput(
+            "The cycle counter is a Cortex-M feature; this core is not ARM".to_string(),
+        ));
+    }
+    let demcr = core.read_word_32(DEMCR).map_err(err)?;
+    if demcr & DEMCR_TRCENA == 0 {
+        core.write_word_32(DEMCR, demcr | DEMCR_TRCENA)
+            .map_err(err)?;
+    }
+    let ctrl = core.read_word_32(DWT_CTRL).map_err(err)?;
+    if ctrl & DWT_CTRL_NOCYCCNT != 0 {
+        return Err(AppError::InvalidInput(
+            "This core has no DWT cycle counter (e.g. Cortex-M0/M0+/M23)".to_string(),
+        ));
+    }
+    if reset {
+        core.write_word_32(DWT_CYCCNT, 0).map_err(err)?;
+    }
+    if ctrl & DWT_CTRL_CYCCNTENA == 0 {
+        core.write_word_32(DWT_CTRL, ctrl | DWT_CTRL_CYCCNTENA)
+            .map_err(err)?;
+    }
+    core.read_word_32(DWT_CYCCNT).map_err(err)
+}
+
+/// Current DWT_CYCCNT of the attached core, enabling the counter on first use.
+/// Two reads bracket a stretch of code when the core is halted at each end
+/// (log trigger, GDB); while running, the host round trip adds its own cycles.
+#[tauri::command]
+async fn read_cycle_counter(app: AppHandle) -> Result<u32, AppError> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    send_rtt_command(&app, RttCommand::CycleCounter(false, tx))?;
+    await_rtt_reply(rx).await
+}
+
+/// Zero DWT_CYCCNT (enabling it if needed). Returns the count read right after,
+/// which is small but not zero on a running core.
+#[tauri::command]
+async fn reset_cycle_counter(app: AppHandle) -> Result<u32, AppError> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    send_rtt_command(&app, RttCommand::CycleCounter(true, tx))?;
+    await_rtt_reply(rx).await
+}
+
+// ── Firmware verification ──
+
+/// Bytes compared at the start and at the end of each flash segment
+const VERIFY_SAMPLE_BYTES: u64 = 256;
+
+/// Bytes shown either side of a mismatch
+const VERIFY_SHOW_BYTES: usize = 16;
+
+/// Part of the ELF's flash image, to compare against target memory
+struct FlashSample {
+    address: u64,
+    expected: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FirmwareMismatch {
+    /// First differing byte
+    address: u64,
+    expected: String,
+    found: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FirmwareMatch {
+    matches: bool,
+    regions_checked: usize,
+    bytes_compared: usize,
+    /// GNU build ID from the ELF, if it has one. When it is stored in flash
+    /// it is one of the compared regions.
+    build_id: Option<String>,
+    mismatches: Vec<FirmwareMismatch>,
+}
+
+/// The first and last `VERIFY_SAMPLE_BYTES` of every loadable segment that
+/// lands in flash (by load address), plus the build ID note. Without a known
+/// chip every loadable segment counts.
+fn elf_flash_samples(
+    elf_path: &str,
+    chip: Option<&str>,
+) -> Result<(Vec<FlashSample>, Option<String>), AppError> {
+    use goblin::elf::program_header::PT_LOAD;
+    use goblin::elf::section_header::SHF_ALLOC;
+
+    let data = std::fs::read(elf_path)
+        .map_err(|e| AppError::Io(format!("Failed to read ELF file: {e}")))?;
+    let elf = goblin::elf::Elf::parse(&data)
+        .map_err(|e| AppError::Elf(format!("Failed to parse ELF: {e}")))?;
+    let flash: Vec<std::ops::Range<u64>> = chip
+        .and_then(|name| probe_rs::config::get_target_by_name(name).ok())
+        .map(|target| {
+            target
+                .memory_map
+                .iter()
+                .filter_map(|region| match region {
+                    probe_rs::config::MemoryRegion::Nvm(nvm) => Some(nvm.range.clone()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let in_flash = |addr: u64| flash.is_empty() || flash.iter().any(|r| r.contains(&addr));
+    // None for ranges outside the file, overflowing ones included
+    let file_bytes = |offset: u64, len: u64| {
+        let end = offset.checked_add(len)?;
+        data.get(offset as usize..end as usize).map(<[u8]>::to_vec)
+    };
+
+    let mut samples = Vec::new();
+    for ph in elf
+        .program_headers
+        .iter()
+        .filter(|ph| ph.p_type == PT_LOAD && ph.p_filesz > 0 && in_flash(ph.p_paddr))
+    {
+        let head = ph.p_filesz.min(VERIFY_SAMPLE_BYTES);
+        let tail_start = ph.p_filesz.saturating_sub(VERIFY_SAMPLE_BYTES).max(head);
+        for (start, len) in [(0, head), (tail_start, ph.p_filesz - tail_start)] {
+            let expected = ph
+                .p_offset
+                .checked_add(start)
+                .and_then(|offset| file_bytes(offset, len))
+                .filter(|b| !b.is_empty());
+            if let (Some(expected), Some(address)) = (expected, ph.p_paddr.checked_add(start)) {
+                samples.push(FlashSample { address, expected });
+            }
+        }
+    }
+
+    let mut build_id = None;
+    for sh in &elf.section_headers {
+        if elf.shdr_strtab.get_at(sh.sh_name) != Some(".note.gnu.build-id") {
+            continue;
+        }
+        // Note header: namesz, descsz, type, then "GNU\0" and the ID
+        if let Some(id) =
+            file_bytes(sh.sh_offset, sh.sh_size).and_then(|note| note.get(16..).map(<[u8]>::to_vec))
+        {
+            build_id = Some(id.iter().map(|b| format!("{b:02x}")).collect());
+        }
+        if sh.sh_flags & SHF_ALLOC as u64 != 0 && in_flash(sh.sh_addr) {
+            if let Some(expected) = file_bytes(sh.sh_offset, sh.sh_size) {
+                samples.push(FlashSample {
+                    address: sh.sh_addr,
+                    expected,
+                });
+            }
+        }
+    }
+    Ok((samples, build_id))
+}
+
+/// Read `len` bytes at each address from the attached core.
+fn read_memory_regions(
+    core: &mut probe_rs::Core<'_>,
+    regions: &[(u64, usize)],
+) -> Result<Vec<Vec<u8>>, AppError> {
+    regions
+        .iter()
+        .map(|&(address, len)| {
+            let mut buf = vec![0u8; len];
+            core.read(address, &mut buf).map_err(|e| {
+                AppError::Other(format!(
+                    "Failed to read {len} bytes at {address:#010X}: {e}"
+                ))
+            })?;
+            Ok(buf)
+        })
+        .collect()
+}
+
+/// Compare the ELF's flash contents with what the target holds, so that
+/// symbolized addresses aren't trusted against the wrong build. A mismatch is
+/// also reported in the log as an error.
+#[tauri::command]
+async fn verify_firmware_match(
+    app: AppHandle,
+    elf_path: String,
+) -> Result<FirmwareMatch, AppError> {
+    let chip = app
+        .state::<AppState>()
+        .active_profile
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|p| p.chip.clone());
+    let path = elf_path.clone();
+    let (samples, build_id) =
+        tokio::task::spawn_blocking(move || elf_flash_samples(&path, chip.as_deref()))
+            .await
+            .map_err(|e| AppError::Other(format!("ELF read task failed: {e}")))??;
+    if samples.is_empty() {
+        return Err(AppError::Elf(
+            "The ELF has nothing to compare: no loadable segment in flash".to_string(),
+        ));
+    }
+
+    let regions = samples
+        .iter()
+        .map(|s| (s.address, s.expected.len()))
+        .collect();
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    send_rtt_command(&app, RttCommand::ReadMemory(regions, tx))?;
+    let found = await_rtt_reply(rx).await?;
+
+    let mismatches: Vec<FirmwareMismatch> = samples
+        .iter()
+        .zip(&found)
+        .filter_map(|(sample, found)| {
+            let at = sample
+                .expected
+                .iter()
+                .zip(found)
+                .position(|(a, b)| a != b)?;
+            let end = (at + VERIFY_SHOW_BYTES).min(found.len());
+            Some(FirmwareMismatch {
+                address: sample.address + at as u64,
+                expected: hex_bytes(&sample.expected[at..end]),
+                found: hex_bytes(&found[at..end]),
+            })
+        })
+        .collect();
+    let result = FirmwareMatch {
+        matches: mismatches.is_empty(),
+        regions_checked: samples.len(),
+        bytes_compared: samples.iter().map(|s| s.expected.len()).sum(),
+        build_id,
+        mismatches,
+    };
+    match result.mismatches.first() {
+        Some(first) => emit_rtt_status(
+            &app,
+            "error",
+            &format!(
+                "The running firmware does not match {elf_path} (first difference at {:#010X}). Function names and file:line from this ELF will be wrong; reflash or load the matching ELF.",
+                first.address
+            ),
+        ),
+        None => emit_rtt_status(
+            &app,
+            "info",
+            &format!(
+                "Firmware matches {elf_path} ({} bytes in {} regions compared).",
+                result.bytes_compared, result.regions_checked
+            ),
+        ),
+    }
+    Ok(result)
+}
+
+// ── SVD registers ──
+
+/// Element tree of an XML document. Entity references in text are dropped;
+/// SVD names and numbers never contain them.
+#[derive(Debug, Default)]
+struct XmlNode {
+    name: String,
+    attrs: Vec<(String, String)>,
+    text: String,
+    children: Vec<XmlNode>,
+}
+
+impl XmlNode {
+    fn from_start(start: &quick_xml::events::BytesStart<'_>) -> Self {
+        Self {
+            name: String::from_utf8_lossy(start.local_name().into_inner()).into_owned(),
+            attrs: start
+                .attributes()
+                .flatten()
+                .filter_map(|a| {
+                    let key = String::from_utf8_lossy(a.key.local_name().into_inner()).into_owned();
+                    Some((key, a.unescape_value().ok()?.into_owned()))
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn child(&self, name: &str) -> Option<&XmlNode> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlNode> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    /// Trimmed text of the first child called `name`
+    fn text_of(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|c| c.text.trim())
+    }
+
+    fn number_of(&self, name: &str) -> Option<u64> {
+        self.text_of(name).and_then(svd_number)
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn parse_xml(text: &str) -> Result<XmlNode, String> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(text);
+    let mut stack = vec![XmlNode::default()];
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("XML error at byte {}: {e}", reader.error_position()))?;
+        match event {
+            Event::Start(start) => stack.push(XmlNode::from_start(&start)),
+            Event::Empty(start) => {
+                let node = XmlNode::from_start(&start);
+                stack.last_mut().unwrap().children.push(node);
+            }
+            Event::End(_) => {
+                let node = stack.pop().unwrap();
+                let parent = stack
+                    .last_mut()
+                    .ok_or_else(|| "XML error: unbalanced end tag".to_string())?;
+                parent.children.push(node);
+            }
+            Event::Text(text) => {
+                if let Ok(text) = text.decode() {
+                    stack.last_mut().unwrap().text.push_str(&text);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    match stack.pop() {
+        Some(root) if stack.is_empty() => Ok(root),
+        _ => Err("XML error: unexpected end of file".to_string()),
+    }
+}
+
+/// SVD scaled non-negative integer: decimal, 0x hex, or #/0b binary (with
+/// `x` as don't-care, taken as 0).
+fn svd_number(s: &str) -> Option<u64> {
+    let s = s.trim();
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else if let Some(bin) = s.strip_prefix('#').or_else(|| s.strip_prefix("0b")) {
+        u64::from_str_radix(&bin.replace(['x', 'X'], "0"), 2).ok()
+    } else {
+        s.parse().ok()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SvdField {
+    name: String,
+    bit_offset: u32,
+    bit_width: u32,
+    /// Enumerated values: raw value and its name
+    values: Vec<(u64, String)>,
+}
+
+#[derive(Debug, Clone)]
+struct SvdRegister {
+    /// From the peripheral's base address
+    offset: u64,
+    /// In bits
+    size: u32,
+    fields: Vec<SvdField>,
+}
+
+#[derive(Debug, Clone)]
+struct SvdPeripheral {
+    base: u64,
+    /// Uppercase names; registers in clusters are "CLUSTER.REGISTER"
+    registers: BTreeMap<String, SvdRegister>,
+}
+
+struct SvdDevice {
+    name: String,
+    /// Keyed by uppercase name
+    peripherals: BTreeMap<String, SvdPeripheral>,
+}
+
+/// Names and extra offsets for an element with `dim`: "CH[%s]" becomes
+/// "CH[0]", "CH[1]", ... spaced `dimIncrement` apart.
+fn svd_dim_names(node: &XmlNode, name: &str) -> Vec<(String, u64)> {
+    let Some(dim) = node.number_of("dim") else {
+        return vec![(name.to_string(), 0)];
+    };
+    let increment = node.number_of("dimIncrement").unwrap_or(0);
+    let indices: Vec<String> = match node.text_of("dimIndex") {
+        Some(list) => match list
+            .split_once('-')
+            .and_then(|(a, b)| Some((a.trim().parse::<u64>().ok()?, b.trim().parse::<u64>().ok()?)))
+        {
+            Some((from, to)) => (from..=to).map(|i| i.to_string()).collect(),
+            None => list.split(',').map(|s| s.trim().to_string()).collect(),
+        },
+        None => (0..dim).map(|i| i.to_string()).collect(),
+    };
+    indices
+        .iter()
+        .enumerate()
+        .map(|(i, index)| {
+            let name = name
+                .replace("[%s]", &format!("[{index}]"))
+                .replace("%s", index);
+            (name, i as u64 * increment)
+        })
+        .collect()
+}
+
+fn svd_field(node: &XmlNode) -> Option<SvdField> {
+    let name = node.text_of("name")?.to_string();
+    let (bit_offset, bit_width) = if let (Some(offset), Some(width)) =
+        (node.number_of("bitOffset"), node.number_of("bitWidth"))
+    {
+        (offset, width)
+    } else if let (Some(lsb), Some(msb)) = (node.number_of("lsb"), node.number_of("msb")) {
+        (lsb, msb.checked_sub(lsb)? + 1)
+    } else {
+        // "[msb:lsb]"
+        let range = node.text_of("bitRange")?;
+        let (msb, lsb) = range.trim_matches(['[', ']']).split_once(':')?;
+        let (msb, lsb) = (svd_number(msb)?, svd_number(lsb)?);
+        (lsb, msb.checked_sub(lsb)? + 1)
+    };
+    let values = node
+        .children("enumeratedValues")
+        .flat_map(|list| list.children("enumeratedValue"))
+        .filter_map(|v| Some((v.number_of("value")?, v.text_of("name")?.to_string())))
+        .collect();
+    Some(SvdField {
+        name,
+        bit_offset: bit_offset as u32,
+        bit_width: bit_width as u32,
+        values,
+    })
+}
+
+/// Registers under `parent`, clusters flattened into "CLUSTER.REGISTER".
+fn svd_registers(
+    parent: &XmlNode,
+    prefix: &str,
+    base_offset: u64,
+    default_size: u32,
+    out: &mut BTreeMap<String, SvdRegister>,
+) {
+    for node in &parent.children {
+        let Some(name) = node.text_of("name") else {
+            continue;
+        };
+        let offset = base_offset + node.number_of("addressOffset").unwrap_or(0);
+        let size = node.number_of("size").map_or(default_size, |s| s as u32);
+        match node.name.as_str() {
+            "register" => {
+                let fields: Vec<SvdField> = node
+                    .child("fields")
+                    .map(|f| f.children("field").filter_map(svd_field).collect())
+                    .unwrap_or_default();
+                for (name, extra) in svd_dim_names(node, name) {
+                    out.insert(
+                        format!("{prefix}{}", name.to_ascii_uppercase()),
+                        SvdRegister {
+                            offset: offset + extra,
+                            size,
+                            fields: fields.clone(),
+                        },
+                    );
+                }
+            }
+            "cluster" => {
+                for (name, extra) in svd_dim_names(node, name) {
+                    let prefix = format!("{prefix}{}.", name.to_ascii_uppercase());
+                    svd_registers(node, &prefix, offset + extra, size, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse_svd(text: &str) -> Result<SvdDevice, String> {
+    let root = parse_xml(text)?;
+    let device = root
+        .child("device")
+        .ok_or_else(|| "Not an SVD file: no <device> element".to_string())?;
+    let default_size = device.number_of("size").map_or(32, |s| s as u32);
+    let mut peripherals = BTreeMap::new();
+    let mut derived = Vec::new();
+    for node in device
+        .child("peripherals")
+        .into_iter()
+        .flat_map(|list| list.children("peripheral"))
+    {
+        let Some(name) = node.text_of("name").map(str::to_ascii_uppercase) else {
+            continue;
+        };
+        let size = node.number_of("size").map_or(default_size, |s| s as u32);
+        let mut registers = BTreeMap::new();
+        if let Some(list) = node.child("registers") {
+            svd_registers(list, "", 0, size, &mut registers);
+        }
+        if let Some(from) = node.attr("derivedFrom") {
+            derived.push((name.clone(), from.to_ascii_uppercase()));
+        }
+        peripherals.insert(
+            name,
+            SvdPeripheral {
+                base: node.number_of("baseAddress").unwrap_or(0),
+                registers,
+            },
+        );
+    }
+    // A derived peripheral has the registers of its source at its own address
+    for (name, from) in derived {
+        let Some(inherited) = peripherals.get(&from).map(|p| p.registers.clone()) else {
+            continue;
+        };
+        if let Some(peripheral) = peripherals.get_mut(&name) {
+            for (reg, def) in inherited {
+                peripheral.registers.entry(reg).or_insert(def);
+            }
+        }
+    }
+    Ok(SvdDevice {
+        name: device.text_of("name").unwrap_or("device").to_string(),
+        peripherals,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SvdSummary {
+    device: String,
+    peripherals: usize,
+    registers: usize,
+}
+
+/// Parse a CMSIS-SVD file for `read_register`, replacing one loaded before.
+#[tauri::command]
+async fn load_svd(app: AppHandle, path: String) -> Result<SvdSummary, AppError> {
+    let device = tokio::task::spawn_blocking(move || {
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| AppError::Io(format!("Failed to read SVD file: {e}")))?;
+        parse_svd(&text).map_err(AppError::InvalidInput)
+    })
+    .await
+    .map_err(|e| AppError::Other(format!("SVD parse task failed: {e}")))??;
+    let summary = SvdSummary {
+        device: device.name.clone(),
+        peripherals: device.peripherals.len(),
+        registers: device.peripherals.values().map(|p| p.registers.len()).sum(),
+    };
+    *app.state::<AppState>().svd.lock().unwrap() = Some(Arc::new(device));
+    Ok(summary)
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FieldValue {
+    name: String,
+    bit_offset: u32,
+    bit_width: u32,
+    value: u64,
+    /// Enumerated value name from the SVD, if the value has one
+    meaning: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RegisterValue {
+    peripheral: String,
+    register: String,
+    address: u64,
+    value: u64,
+    fields: Vec<FieldValue>,
+}
+
+/// Read a register at its SVD size. Peripherals can care about access width,
+/// so this never reads more or less than the register.
+fn read_sized(core: &mut probe_rs::Core<'_>, address: u64, bits: u32) -> Result<u64, AppError> {
+    let err = |e: probe_rs::Error| AppError::Other(format!("Failed to read {address:#010X}: {e}"));
+    match bits {
+        8 => core.read_word_8(address).map(u64::from).map_err(err),
+        16 => core.read_word_16(address).map(u64::from).map_err(err),
+        32 => core.read_word_32(address).map(u64::from).map_err(err),
+        64 => core.read_word_64(address).map_err(err),
+        _ => Err(AppError::InvalidInput(format!(
+            "Can't read a {bits}-bit register"
+        ))),
+    }
+}
+
+/// Read a peripheral register from the attached core and split it into the
+/// SVD's bitfields. Names are case-insensitive; registers inside clusters are
+/// "CLUSTER.REGISTER". The decoded value is also logged.
+#[tauri::command]
+async fn read_register(
+    app: AppHandle,
+    peripheral: String,
+    register: String,
+) -> Result<RegisterValue, AppError> {
+    let svd = app
+        .state::<AppState>()
+        .svd
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| AppError::InvalidInput("No SVD file loaded".to_string()))?;
+    let periph = svd
+        .peripherals
+        .get(&peripheral.to_ascii_uppercase())
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!("No peripheral '{peripheral}' in {}", svd.name))
+        })?;
+    let reg = periph
+        .registers
+        .get(&register.to_ascii_uppercase())
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!("{peripheral} has no register '{register}'"))
+        })?;
+    let address = periph.base + reg.offset;
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    send_rtt_command(&app, RttCommand::ReadRegister(address, reg.size, tx))?;
+    let value = await_rtt_reply(rx).await?;
+
+    let fields: Vec<FieldValue> = reg
+        .fields
+        .iter()
+        .map(|f| {
+            let mask = 1u64.checked_shl(f.bit_width).map_or(u64::MAX, |m| m - 1);
+            let v = value.checked_shr(f.bit_offset).unwrap_or(0) & mask;
+            FieldValue {
+                name: f.name.clone(),
+                bit_offset: f.bit_offset,
+                bit_width: f.bit_width,
+                value: v,
+                meaning: f
+                    .values
+                    .iter()
+                    .find(|(raw, _)| *raw == v)
+                    .map(|(_, name)| name.clone()),
+            }
+        })
+        .collect();
+    let result = RegisterValue {
+        peripheral: peripheral.to_ascii_uppercase(),
+        register: register.to_ascii_uppercase(),
+        address,
+        value,
+        fields,
+    };
+
+    let decoded: Vec<String> = result
+        .fields
+        .iter()
+        .map(|f| match &f.meaning {
+            Some(meaning) => format!("{}={meaning}", f.name),
+            None => format!("{}={:#x}", f.name, f.value),
+        })
+        .collect();
+    let digits = (reg.size as usize).div_ceil(4);
+    emit_rtt_status(
+        &app,
+        "info",
+        &format!(
+            "{}.{} @ {address:#010X} = {value:#0width$X}{}{}",
+            result.peripheral,
+            result.register,
+            if decoded.is_empty() { "" } else { " · " },
+            decoded.join(" "),
+            width = digits + 2,
+        ),
+    );
+    Ok(result)
+}
+
+// ── GDB server ──
+
+/// Upper bound on bytes taken from the GDB client per read-loop pass
+const GDB_BYTES_PER_PASS: usize = 4096;
+
+/// Cortex-M as GDB sees it: r0-r15 and xPSR
+enum CortexM {}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct CortexMRegs {
+    /// r0-r12, sp, lr, pc, xpsr
+    regs: [u32; 17],
+}
+
+const CORTEX_M_TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+<architecture>arm</architecture>
+<feature name="org.gnu.gdb.arm.m-profile">
+<reg name="r0" bitsize="32"/><reg name="r1" bitsize="32"/><reg name="r2" bitsize="32"/>
+<reg name="r3" bitsize="32"/><reg name="r4" bitsize="32"/><reg name="r5" bitsize="32"/>
+<reg name="r6" bitsize="32"/><reg name="r7" bitsize="32"/><reg name="r8" bitsize="32"/>
+<reg name="r9" bitsize="32"/><reg name="r10" bitsize="32"/><reg name="r11" bitsize="32"/>
+<reg name="r12" bitsize="32"/>
+<reg name="sp" bitsize="32" type="data_ptr"/>
+<reg name="lr" bitsize="32"/>
+<reg name="pc" bitsize="32" type="code_ptr"/>
+<reg name="xpsr" bitsize="32"/>
+</feature>
+</target>"#;
+
+impl gdbstub::arch::Registers for CortexMRegs {
+    type ProgramCounter = u32;
+
+    fn pc(&self) -> u32 {
+        self.regs[15]
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        for b in self.regs.iter().flat_map(|r| r.to_le_bytes()) {
+            write_byte(Some(b));
+        }
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != self.regs.len() * 4 {
+            return Err(());
+        }
+        for (reg, chunk) in self.regs.iter_mut().zip(bytes.chunks_exact(4)) {
+            *reg = u32::from_le_bytes(chunk.try_into().map_err(|_| ())?);
+        }
+        Ok(())
+    }
+}
+
+impl gdbstub::arch::Arch for CortexM {
+    type Usize = u32;
+    type Registers = CortexMRegs;
+    type BreakpointKind = usize;
+    type RegId = ();
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(CORTEX_M_TARGET_XML)
+    }
+}
+
+/// The session as a GDB target. Operations go to the profile's core.
+struct GdbTarget<'s> {
+    session: &'s mut probe_rs::Session,
+    core: usize,
+}
+
+type GdbStub<'s> =
+    gdbstub::stub::state_machine::GdbStubStateMachine<'static, GdbTarget<'s>, std::net::TcpStream>;
+
+use gdbstub::target::TargetError;
+
+type GdbResult<'s, T> = gdbstub::target::TargetResult<T, GdbTarget<'s>>;
+
+impl GdbTarget<'_> {
+    fn core(&mut self) -> Result<probe_rs::Core<'_>, probe_rs::Error> {
+        self.session.core(self.core)
+    }
+}
+
+impl<'s> gdbstub::target::Target for GdbTarget<'s> {
+    type Arch = CortexM;
+    type Error = probe_rs::Error;
+
+    fn base_ops(&mut self) -> gdbstub::target::ext::base::BaseOps<'_, CortexM, probe_rs::Error> {
+        gdbstub::target::ext::base::BaseOps::SingleThread(self)
+    }
+
+    fn support_breakpoints(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::breakpoints::BreakpointsOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl<'s> gdbstub::target::ext::base::singlethread::SingleThreadBase for GdbTarget<'s> {
+    fn read_registers(&mut self, regs: &mut CortexMRegs) -> GdbResult<'s, ()> {
+        let mut core = self.core().map_err(TargetError::Fatal)?;
+        for (i, reg) in regs.regs.iter_mut().enumerate() {
+            *reg = core
+                .read_core_reg(probe_rs::RegisterId(i as u16))
+                .map_err(TargetError::Fatal)?;
+        }
+        Ok(())
+    }
+
+    fn write_registers(&mut self, regs: &CortexMRegs) -> GdbResult<'s, ()> {
+        let mut core = self.core().map_err(TargetError::Fatal)?;
+        for (i, reg) in regs.regs.iter().enumerate() {
+            core.write_core_reg(probe_rs::RegisterId(i as u16), *reg)
+                .map_err(TargetError::Fatal)?;
+        }
+        Ok(())
+    }
+
+    fn read_addrs(&mut self, start_addr: u32, data: &mut [u8]) -> GdbResult<'s, usize> {
+        let mut core = self.core().map_err(TargetError::Fatal)?;
+        // Unmapped memory is for GDB to report, not a reason to drop the link
+        core.read(start_addr as u64, data)
+            .map_err(|_| TargetError::NonFatal)?;
+        Ok(data.len())
+    }
+
+    fn write_addrs(&mut self, start_addr: u32, data: &[u8]) -> GdbResult<'s, ()> {
+        let mut core = self.core().map_err(TargetError::Fatal)?;
+        core.write(start_addr as u64, data)
+            .map_err(|_| TargetError::NonFatal)
+    }
+
+    fn support_resume(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::base::singlethread::SingleThreadResumeOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl gdbstub::target::ext::base::singlethread::SingleThreadResume for GdbTarget<'_> {
+    fn resume(&mut self, _signal: Option<gdbstub::common::Signal>) -> Result<(), probe_rs::Error> {
+        self.core()?.run()
+    }
+
+    fn support_single_step(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::base::singlethread::SingleThreadSingleStepOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl gdbstub::target::ext::base::singlethread::SingleThreadSingleStep for GdbTarget<'_> {
+    /// Steps synchronously; the next poll sees the halt and reports it
+    fn step(&mut self, _signal: Option<gdbstub::common::Signal>) -> Result<(), probe_rs::Error> {
+        self.core()?.step().map(|_| ())
+    }
+}
+
+impl gdbstub::target::ext::breakpoints::Breakpoints for GdbTarget<'_> {
+    fn support_sw_breakpoint(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::breakpoints::SwBreakpointOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_hw_breakpoint(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::breakpoints::HwBreakpointOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+// Code usually runs from flash, so software breakpoints become hardware ones too
+impl<'s> gdbstub::target::ext::breakpoints::SwBreakpoint for GdbTarget<'s> {
+    fn add_sw_breakpoint(&mut self, addr: u32, kind: usize) -> GdbResult<'s, bool> {
+        gdbstub::target::ext::breakpoints::HwBreakpoint::add_hw_breakpoint(self, addr, kind)
+    }
+
+    fn remove_sw_breakpoint(&mut self, addr: u32, kind: usize) -> GdbResult<'s, bool> {
+        gdbstub::target::ext::breakpoints::HwBreakpoint::remove_hw_breakpoint(self, addr, kind)
+    }
+}
+
+impl<'s> gdbstub::target::ext::breakpoints::HwBreakpoint for GdbTarget<'s> {
+    /// Ok(false) when the comparators are all in use
+    fn add_hw_breakpoint(&mut self, addr: u32, _kind: usize) -> GdbResult<'s, bool> {
+        Ok(self
+            .core()
+            .map_err(TargetError::Fatal)?
+            .set_hw_breakpoint(addr as u64)
+            .is_ok())
+    }
+
+    fn remove_hw_breakpoint(&mut self, addr: u32, _kind: usize) -> GdbResult<'s, bool> {
+        Ok(self
+            .core()
+            .map_err(TargetError::Fatal)?
+            .clear_hw_breakpoint(addr as u64)
+            .is_ok())
+    }
+}
+
+/// Listener for `start_gdb_server`. Only Cortex-M cores are supported.
+fn bind_gdb_listener(
+    core: &mut probe_rs::Core<'_>,
+    port: u16,
+) -> Result<std::net::TcpListener, AppError> {
+    if !core.core_type().is_cortex_m() {
+        return Err(AppError::InvalidInput(format!(
+            "GDB server supports Cortex-M cores only, this one is {:?}",
+            core.core_type()
+        )));
+    }
+    let listener = std::net::TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| AppError::Io(format!("Failed to listen on port {port}: {e}")))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| AppError::Io(format!("Failed to listen on port {port}: {e}")))?;
+    Ok(listener)
+}
+
+/// Accept a client or move its session along. Called once per read-loop
+/// pass, so GDB and RTT never use the core at the same time.
+fn poll_gdb<'s>(
+    stub: &mut Option<GdbStub<'s>>,
+    target: &mut GdbTarget<'s>,
+    listener: Option<&std::net::TcpListener>,
+    sink: LogSink,
+) {
+    let Some(listener) = listener else {
+        *stub = None;
+        return;
+    };
+    if stub.is_none() {
+        let Ok((stream, addr)) = listener.accept() else {
+            return;
+        };
+        // GDB expects a stopped target when it attaches
+        let halted = target
+            .core()
+            .and_then(|mut core| core.halt(std::time::Duration::from_millis(100)));
+        if let Err(e) = halted {
+            sink.status("warn", &format!("GDB client refused: {e}"));
+            return;
+        }
+        match gdbstub::stub::GdbStub::new(stream).run_state_machine(target) {
+            Ok(machine) => {
+                sink.status("info", &format!("GDB connected from {addr}."));
+                *stub = Some(machine);
+            }
+            Err(e) => sink.status("warn", &format!("GDB session failed: {e}")),
+        }
+        return;
+    }
+
+    for _ in 0..GDB_BYTES_PER_PASS {
+        let Some(machine) = stub.take() else {
+            return;
+        };
+        match step_gdb(machine, target) {
+            Ok((next, progressed)) => {
+                *stub = next;
+                if stub.is_none() {
+                    sink.status("info", "GDB disconnected.");
+                }
+                if !progressed {
+                    return;
+                }
+            }
+            Err(e) => {
+                sink.status("warn", &format!("GDB session ended: {e}"));
+                return;
+            }
+        }
+    }
+}
+
+/// One state machine transition. The flag is false when there was nothing to do.
+fn step_gdb<'s>(
+    machine: GdbStub<'s>,
+    target: &mut GdbTarget<'s>,
+) -> Result<(Option<GdbStub<'s>>, bool), String> {
+    use gdbstub::conn::ConnectionExt;
+    use gdbstub::stub::state_machine::GdbStubStateMachine;
+    use gdbstub::stub::SingleThreadStopReason;
+
+    let next_byte = |conn: &mut std::net::TcpStream| -> Result<Option<u8>, String> {
+        match conn.peek().map_err(|e| e.to_string())? {
+            Some(_) => conn.read().map(Some).map_err(|e| e.to_string()),
+            None => Ok(None),
+        }
+    };
+
+    Ok(match machine {
+        GdbStubStateMachine::Idle(mut state) => match next_byte(state.borrow_conn())? {
+            Some(b) => (
+                Some(state.incoming_data(target, b).map_err(|e| e.to_string())?),
+                true,
+            ),
+            None => (Some(state.into()), false),
+        },
+        GdbStubStateMachine::Running(mut state) => {
+            if let Some(b) = next_byte(state.borrow_conn())? {
+                return Ok((
+                    Some(state.incoming_data(target, b).map_err(|e| e.to_string())?),
+                    true,
+                ));
+            }
+            let status = target
+                .core()
+                .and_then(|mut core| core.status())
+                .map_err(|e| e.to_string())?;
+            let reason = match status {
+                probe_rs::CoreStatus::Halted(probe_rs::HaltReason::Breakpoint(_)) => {
+                    SingleThreadStopReason::HwBreak(())
+                }
+                probe_rs::CoreStatus::Halted(probe_rs::HaltReason::Step) => {
+                    SingleThreadStopReason::DoneStep
+                }
+                // Includes a halt from the log trigger
+                probe_rs::CoreStatus::Halted(_) => {
+                    SingleThreadStopReason::Signal(gdbstub::common::Signal::SIGINT)
+                }
+                _ => return Ok((Some(state.into()), false)),
+            };
+            (
+                Some(
+                    state
+                        .report_stop(target, reason)
+                        .map_err(|e| e.to_string())?,
+                ),
+                true,
+            )
+        }
+        GdbStubStateMachine::CtrlCInterrupt(state) => {
+            target
+                .core()
+                .and_then(|mut core| core.halt(std::time::Duration::from_millis(100)))
+                .map_err(|e| e.to_string())?;
+            let reason = SingleThreadStopReason::Signal(gdbstub::common::Signal::SIGINT);
+            (
+                Some(
+                    state
+                        .interrupt_handled(target, Some(reason))
+                        .map_err(|e| e.to_string())?,
+                ),
+                true,
+            )
+        }
+        GdbStubStateMachine::Disconnected(_) => (None, true),
+    })
+}
+
+/// Serve GDB on `127.0.0.1:port` from the running RTT session. Logs keep
+/// flowing while GDB halts, steps and sets breakpoints; both share the
+/// session from the RTT thread. Returns the address to `target remote`.
+#[tauri::command]
+async fn start_gdb_server(app: AppHandle, port: u16) -> Result<String, AppError> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    send_rtt_command(&app, RttCommand::StartGdb(port, tx))?;
+    await_rtt_reply(rx).await
+}
+
+#[tauri::command]
+async fn stop_gdb_server(app: AppHandle) -> Result<(), AppError> {
+    send_rtt_command(&app, RttCommand::StopGdb)
+}
+
+// ── Throughput benchmark ──
+
+const MAX_BENCHMARK_SECS: u64 = 60;
+
+/// Peak throughput is the best of these windows
+const BENCHMARK_WINDOW: std::time::Duration = std::time::Duration::from_millis(100);
+
+#[derive(Debug, Clone, Serialize)]
+struct BenchmarkReport {
+    duration_ms: u64,
+    bytes: u64,
+    average_bytes_per_sec: f64,
+    peak_bytes_per_sec: f64,
+    reads: u64,
+    /// Reads that found an up buffer completely full. The target was writing
+    /// faster than we drain, so it dropped or blocked (depending on channel mode).
+    full_reads: u64,
+}
+
+struct Benchmark {
+    started: std::time::Instant,
+    duration: std::time::Duration,
+    bytes: u64,
+    reads: u64,
+    full_reads: u64,
+    window_start: std::time::Instant,
+    window_bytes: u64,
+    peak_bytes_per_sec: f64,
+    reply: tokio::sync::oneshot::Sender<Result<BenchmarkReport, AppError>>,
+}
+
+impl Benchmark {
+    fn new(
+        duration: std::time::Duration,
+        reply: tokio::sync::oneshot::Sender<Result<BenchmarkReport, AppError>>,
+    ) -> Self {
+        let now = std::time::Instant::now();
+        Self {
+            started: now,
+            duration,
+            bytes: 0,
+            reads: 0,
+            full_reads: 0,
+            window_start: now,
+            window_bytes: 0,
+            peak_bytes_per_sec: 0.0,
+            reply,
+        }
+    }
+
+    fn record(&mut self, count: usize, buffer_size: usize) {
+        self.bytes += count as u64;
+        self.reads += 1;
+        // A ring buffer holds at most size - 1 bytes
+        if count + 1 >= buffer_size {
+            self.full_reads += 1;
+        }
+        self.window_bytes += count as u64;
+        let window = self.window_start.elapsed();
+        if window >= BENCHMARK_WINDOW {
+            let rate = self.window_bytes as f64 / window.as_secs_f64();
+            self.peak_bytes_per_sec = self.peak_bytes_per_sec.max(rate);
+            self.window_start = std::time::Instant::now();
+            self.window_bytes = 0;
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.started.elapsed() >= self.duration
+    }
+
+    fn finish(self) {
+        let elapsed = self.started.elapsed();
+        let average = self.bytes as f64 / elapsed.as_secs_f64();
+        let _ = self.reply.send(Ok(BenchmarkReport {
+            duration_ms: elapsed.as_millis() as u64,
+            bytes: self.bytes,
+            average_bytes_per_sec: average,
+            // Runs shorter than one window never close one
+            peak_bytes_per_sec: self.peak_bytes_per_sec.max(average),
+            reads: self.reads,
+            full_reads: self.full_reads,
+        }));
+    }
+}
+
+/// Measure sustained RTT throughput at the current probe speed and buffer
+/// sizes. For `duration_s` seconds reads are counted but not parsed or shown.
+#[tauri::command]
+async fn benchmark_rtt(app: AppHandle, duration_s: u64) -> Result<BenchmarkReport, AppError> {
+    if !(1..=MAX_BENCHMARK_SECS).contains(&duration_s) {
+        return Err(AppError::InvalidInput(format!(
+            "Benchmark duration must be 1 to {MAX_BENCHMARK_SECS} seconds"
+        )));
+    }
+    let duration = std::time::Duration::from_secs(duration_s);
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    send_rtt_command(&app, RttCommand::Benchmark(duration, tx))?;
+    // Longer than `await_rtt_reply` allows; the reply comes when the run ends
+    match tokio::time::timeout(duration + std::time::Duration::from_secs(3), rx).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(_)) => Err(AppError::NotConnected(
+            "RTT session ended during the benchmark".to_string(),
+        )),
+        Err(_) => Err(AppError::NotConnected(
+            "Target not attached (reconnecting?)".to_string(),
+        )),
+    }
+}
+
+// ── Up channel fill ──
+
+/// How much of an up buffer the target has written that we haven't read yet.
+/// Consistently near `size` means the host isn't keeping up.
+#[derive(Debug, Clone, Serialize)]
+struct ChannelFill {
+    /// Core or control block label when the session reads more than one block
+    core: Option<String>,
+    channel: usize,
+    name: Option<String>,
+    used: u64,
+    size: u64,
+}
+
+/// Read each up channel's write/read offsets straight from the control block.
+/// probe-rs keeps its own copies private, and only reads them when draining.
+fn read_channel_fill(
+    rtt: &probe_rs::rtt::Rtt,
+    core: &mut probe_rs::Core<'_>,
+    core_name: Option<&str>,
+) -> Result<Vec<ChannelFill>, probe_rs::Error> {
+    // Header is the 16-byte ID plus up/down counts; each channel is six words:
+    // name, buffer, size, write offset, read offset, flags
+    let word = if core.is_64_bit() { 8 } else { 4 };
+    let header = 16 + 2 * word;
+    let mut fill = Vec::new();
+    for ch in &rtt.up_channels {
+        let desc = rtt.ptr() + header + ch.number() as u64 * 6 * word;
+        let (write, read) = if word == 8 {
+            let mut offsets = [0u64; 2];
+            core.read_64(desc + 3 * word, &mut offsets)?;
+            (offsets[0], offsets[1])
+        } else {
+            let mut offsets = [0u32; 2];
+            core.read_32(desc + 3 * word, &mut offsets)?;
+            (u64::from(offsets[0]), u64::from(offsets[1]))
+        };
+        let size = ch.buffer_size() as u64;
+        fill.push(ChannelFill {
+            core: core_name.map(str::to_string),
+            channel: ch.number(),
+            name: ch.name().map(str::to_string),
+            // Offsets wrap at the buffer size; a corrupt one shouldn't panic
+            used: (write + size).wrapping_sub(read) % size.max(1),
+            size,
+        });
+    }
+    Ok(fill)
+}
+
+/// Current fill of every up channel on every attached core.
+#[tauri::command]
+async fn channel_fill(app: AppHandle) -> Result<Vec<ChannelFill>, AppError> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    send_rtt_command(&app, RttCommand::ChannelFill(tx))?;
+    await_rtt_reply(rx).await
+}
+
+/// Restart the attached target into its bootloader's DFU mode, for the
+/// families in `BOOTLOADER_ENTRIES` (nRF51/nRF52 with the nRF5 SDK or Adafruit
+/// bootloader, SAMD21/SAMD51/SAME5x with the UF2 bootloader).
+#[tauri::command]
+async fn enter_bootloader(app: AppHandle, chip: String) -> Result<String, AppError> {
+    let (write, bootloader) = bootloader_write(&chip)?;
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    send_rtt_command(&app, RttCommand::EnterBootloader(write, tx))?;
+    await_rtt_reply(rx).await?;
+    let msg = format!("Restarted {chip} into the {bootloader}.");
+    emit_rtt_status(&app, "info", &msg);
+    Ok(msg)
+}
+
+// ── RAM log backlog ──
+
+/// Largest backlog buffer read on connect
+const MAX_BACKLOG_BYTES: u64 = 64 * 1024;
+
+/// Lines the firmware logged before we attached, from the char array named
+/// by `Profile.backlog_symbol`. A wrapped ring must keep a NUL at its write
+/// position (most do, by terminating each message): the text after that NUL
+/// is the oldest. Without a NUL the buffer is read front to back.
+fn read_backlog(
+    core: &mut probe_rs::Core<'_>,
+    elf_path: &str,
+    symbol: &str,
+) -> Result<Vec<u8>, AppError> {
+    let (address, size) = resolve_elf_symbol(elf_path, symbol)?;
+    if size == 0 || size > MAX_BACKLOG_BYTES {
+        return Err(AppError::InvalidInput(format!(
+            "'{symbol}' is {size} bytes; a backlog buffer must be 1 to {MAX_BACKLOG_BYTES}"
+        )));
+    }
+    let mut ring = vec![0u8; size as usize];
+    core.read(address, &mut ring)
+        .map_err(|e| AppError::Other(format!("Failed to read '{symbol}': {e}")))?;
+    Ok(unwrap_backlog(&ring))
+}
+
+/// Oldest-first text from a ring with a NUL at the write position.
+fn unwrap_backlog(ring: &[u8]) -> Vec<u8> {
+    let Some(write_pos) = ring.iter().position(|&b| b == 0) else {
+        return ring.to_vec();
+    };
+    // Older text follows the write position, minus the line it cut into.
+    // A ring that never wrapped has only NULs there, so no newline.
+    let after = &ring[write_pos + 1..];
+    let older = match after.iter().position(|&b| b == b'\n') {
+        Some(nl) => &after[nl + 1..],
+        None => &[],
+    };
+    let mut text: Vec<u8> = older.iter().chain(&ring[..write_pos]).copied().collect();
+    text.retain(|&b| b != 0);
+    if text.last().is_some_and(|&b| b != b'\n') {
+        text.push(b'\n');
+    }
+    text
+}
+
+/// Feed the backlog through a throwaway parser so its entries come out
+/// marked, before any live line.
+fn emit_backlog(
+    sink: LogSink,
+    core: &mut probe_rs::Core<'_>,
+    state: &mut SessionState,
+    elf_path: &str,
+    symbol: &str,
+) {
+    let text = match read_backlog(core, elf_path, symbol) {
+        Ok(text) => text,
+        Err(e) => {
+            sink.status("warn", &format!("No backlog: {e}"));
+            return;
+        }
+    };
+    let mut parser = RttParser::new(0, state.options.clone());
+    parser.backlog = true;
+    parser.session_start = state.started;
+    let lines_before = state.stats.lines;
+    let _ = parser.process_bytes(&text, text.len(), &mut state.stats, sink);
+    let _ = parser.flush(&mut state.stats, sink);
+    sink.status(
+        "info",
+        &format!(
+            "Replayed {} backlog line(s) from '{symbol}'.",
+            state.stats.lines - lines_before
+        ),
+    );
+}
+
+// ── Live variable watches ──
+
+/// How often watched variables are read
+const WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+/// Watches at most this many bytes apart are fetched in one read
+const WATCH_MERGE_GAP: u64 = 32;
+
+/// How to decode a watched variable (little-endian, as on Cortex-M).
+#[derive(Debug, Clone, Copy)]
+enum WatchKind {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+impl WatchKind {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "u8" => Self::U8,
+            "i8" => Self::I8,
+            "u16" => Self::U16,
+            "i16" => Self::I16,
+            "u32" => Self::U32,
+            "i32" => Self::I32,
+            "u64" => Self::U64,
+            "i64" => Self::I64,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::U8 | Self::I8 => 1,
+            Self::U16 | Self::I16 => 2,
+            Self::U32 | Self::I32 | Self::F32 => 4,
+            Self::U64 | Self::I64 | Self::F64 => 8,
+        }
+    }
+
+    /// `bytes` is exactly `size()` long.
+    fn decode(self, bytes: &[u8]) -> serde_json::Value {
+        let mut b = [0u8; 8];
+        b[..bytes.len()].copy_from_slice(bytes);
+        let raw = u64::from_le_bytes(b);
+        match self {
+            Self::U8 | Self::U16 | Self::U32 | Self::U64 => raw.into(),
+            Self::I8 => (raw as u8 as i8).into(),
+            Self::I16 => (raw as u16 as i16).into(),
+            Self::I32 => (raw as u32 as i32).into(),
+            Self::I64 => (raw as i64).into(),
+            Self::F32 => f64::from(f32::from_bits(raw as u32)).into(),
+            Self::F64 => f64::from_bits(raw).into(),
+        }
+    }
+}
+
+struct Watch {
+    symbol: String,
+    address: u64,
+    kind: WatchKind,
+    /// Bytes from the previous poll, to emit only on change
+    last: Option<Vec<u8>>,
+}
+
+impl Watch {
+    fn end(&self) -> u64 {
+        self.address + self.kind.size() as u64
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct WatchUpdate {
+    symbol: String,
+    value: serde_json::Value,
+}
+
+/// Poll a global from the active profile's ELF and emit `watch-update` on every
+/// change. `kind` is "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" | "f32" | "f64".
+/// Returns the resolved address.
+#[tauri::command]
+async fn add_watch(app: AppHandle, symbol: String, kind: String) -> Result<u64, AppError> {
+    let kind = WatchKind::parse(&kind)
+        .ok_or_else(|| AppError::InvalidInput(format!("Unknown watch type: {kind}")))?;
+    let elf_path = app
+        .state::<AppState>()
+        .active_profile
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|p| p.elf_path.clone())
+        .ok_or_else(|| {
+            AppError::InvalidInput(
+                "Watching needs an ELF file in the connected profile".to_string(),
+            )
+        })?;
+    let name = symbol.clone();
+    let (address, size) =
+        tauri::async_runtime::spawn_blocking(move || resolve_elf_symbol(&elf_path, &name))
+            .await
+            .map_err(|e| AppError::Other(format!("Symbol lookup task failed: {e}")))??;
+    if size != 0 && size < kind.size() as u64 {
+        return Err(AppError::InvalidInput(format!(
+            "'{symbol}' is {size} byte(s), too small for {} bytes",
+            kind.size()
+        )));
+    }
+    send_rtt_command(
+        &app,
+        RttCommand::AddWatch(Watch {
+            symbol,
+            address,
+            kind,
+            last: None,
+        }),
+    )?;
+    Ok(address)
+}
+
+#[tauri::command]
+async fn remove_watch(app: AppHandle, symbol: String) -> Result<(), AppError> {
+    send_rtt_command(&app, RttCommand::RemoveWatch(symbol))
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PokeResult {
+    address: u64,
+    size: u32,
+    old: u64,
+    new: u64,
+}
+
+/// Runs on the RTT thread. Reads the old value and writes the new one with
+/// a single access of the given width. With `halt`, a running core is halted
+/// for the two accesses and resumed; one that was already halted stays so.
+fn poke_on_core(
+    core: &mut probe_rs::Core<'_>,
+    address: u64,
+    bits: u32,
+    value: u64,
+    halt: bool,
+) -> Result<u64, AppError> {
+    let err = |e: probe_rs::Error| AppError::Other(format!("Failed to write {address:#010X}: {e}"));
+    let halted_here = halt && !core.core_halted().map_err(err)?;
+    if halted_here {
+        core.halt(std::time::Duration::from_millis(100))
+            .map_err(err)?;
+    }
+    let result = read_sized(core, address, bits).and_then(|old| {
+        match bits {
+            8 => core.write_word_8(address, value as u8),
+            16 => core.write_word_16(address, value as u16),
+            32 => core.write_word_32(address, value as u32),
+            _ => core.write_word_64(address, value),
+        }
+        .map_err(err)?;
+        Ok(old)
+    });
+    // Resume even when the write failed; a poke shouldn't leave the target stopped
+    if halted_here {
+        core.run().map_err(err)?;
+    }
+    result
+}
+
+/// Write a value into target memory for a live experiment, e.g. setting a
+/// `debug_mode` flag without reflashing. `target` is a 0x-prefixed address or
+/// a global from the connected profile's ELF. `size` is in bytes (1, 2, 4 or
+/// 8) and defaults to the symbol's size. The old and new value are logged.
+#[tauri::command]
+async fn poke_memory(
+    app: AppHandle,
+    target: String,
+    value: u64,
+    size: Option<u32>,
+    halt: Option<bool>,
+) -> Result<PokeResult, AppError> {
+    let target = target.trim().to_string();
+    let (address, symbol_size) = if target.starts_with("0x") || target.starts_with("0X") {
+        let address = parse_hex_u64(&target)
+            .ok_or_else(|| AppError::InvalidInput(format!("Invalid address '{target}'")))?;
+        (address, None)
+    } else {
+        let elf_path = app
+            .state::<AppState>()
+            .active_profile
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|p| p.elf_path.clone())
+            .ok_or_else(|| {
+                AppError::InvalidInput(
+                    "Writing a variable by name needs an ELF file in the connected profile"
+                        .to_string(),
+                )
+            })?;
+        let name = target.clone();
+        let (address, size) =
+            tauri::async_runtime::spawn_blocking(move || resolve_elf_symbol(&elf_path, &name))
+                .await
+                .map_err(|e| AppError::Other(format!("Symbol lookup task failed: {e}")))??;
+        (address, Some(size))
+    };
+    let size = match (size, symbol_size) {
+        (Some(size), _) => size,
+        (None, Some(size @ (1 | 2 | 4 | 8))) => size as u32,
+        (None, Some(size)) => {
+            return Err(AppError::InvalidInput(format!(
+                "'{target}' is {size} byte(s); give a size of 1, 2, 4 or 8"
+            )))
+        }
+        (None, None) => 4,
+    };
+    if !matches!(size, 1 | 2 | 4 | 8) {
+        return Err(AppError::InvalidInput(format!(
+            "Can't write {size} bytes at once; use 1, 2, 4 or 8"
+        )));
+    }
+    if address % size as u64 != 0 {
+        return Err(AppError::InvalidInput(format!(
+            "{address:#010X} isn't aligned for a {size}-byte write"
+        )));
+    }
+    if symbol_size.is_some_and(|s| s != 0 && (size as u64) > s) {
+        return Err(AppError::InvalidInput(format!(
+            "'{target}' is smaller than {size} bytes; the write would spill into the next variable"
+        )));
+    }
+    let bits = size * 8;
+    if bits < 64 && value >> bits != 0 {
+        return Err(AppError::InvalidInput(format!(
+            "{value:#x} doesn't fit in {size} byte(s)"
+        )));
+    }
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    send_rtt_command(
+        &app,
+        RttCommand::WriteMemory(address, bits, value, halt.unwrap_or(false), tx),
+    )?;
+    let old = await_rtt_reply(rx).await?;
+
+    let width = size as usize * 2 + 2;
+    let name = if symbol_size.is_some() {
+        format!("{target} @ {address:#010X}")
+    } else {
+        format!("{address:#010X}")
+    };
+    emit_rtt_status(
+        &app,
+        "info",
+        &format!("Wrote {name}: {old:#0width$X} → {value:#0width$X}"),
+    );
+    Ok(PokeResult {
+        address,
+        size,
+        old,
+        new: value,
+    })
+}
+
+// ── Main command ──
+
+/// RTT scans slower than this over a RAM range get a hint to pin the address
+const SLOW_RTT_SCAN_MS: u64 = 200;
+
+/// Where one connect spent its time, in milliseconds. probe-rs picks the wire
+/// protocol inside `attach`, so that part counts toward `target_attach_ms`.
+#[derive(Debug, Clone, Serialize)]
+struct ConnectTiming {
+    probe_open_ms: u64,
+    /// Probe speed, target lookup and connect sequence, before attaching
+    protocol_select_ms: u64,
+    target_attach_ms: u64,
+    /// Finding the control block, on every attached core
+    rtt_scan_ms: u64,
+    total_ms: u64,
+    /// The profile gave an exact control block address; otherwise RAM was scanned
+    exact_address: bool,
+}
+
+fn millis_between(from: std::time::Instant, to: std::time::Instant) -> u64 {
+    to.duration_since(from).as_millis() as u64
+}
+
+#[tauri::command]
+async fn start_rtt(
+    app: AppHandle,
+    profile: Profile,
+    probe_index: Option<usize>,
+    log_to_sqlite: Option<String>,
+) -> Result<String, AppError> {
+    let state = app.state::<AppState>();
+    state.stop_flag.store(false, Ordering::Relaxed);
+    let stop_flag = state.stop_flag.clone();
+    let chip = profile.chip.clone();
+    let core_idx = profile.core.unwrap_or(0);
+    let probe_idx = probe_index.unwrap_or(0);
+    let probe_info = match &profile.probe_serial {
+        Some(serial) => find_probe_by_serial(serial)
+            .ok_or_else(|| AppError::NoProbe(format!("No probe with serial '{serial}'")))?,
+        None => select_probe(probe_idx)?,
+    };
+    // Once per session; reconnects reuse the registry entry
+    if let Some(path) = &profile.target_yaml {
+        register_target_yaml(path, &profile.chip)?;
+    }
+
+    let sqlite_log = log_to_sqlite.as_deref().map(SqliteLog::open).transpose()?;
+
+    let probe_name = match &profile.probe_serial {
+        Some(serial) => serial.clone(),
+        None => probe_idx.to_string(),
+    };
+    let mut msg = format!("RTT connecting ({chip}, core {core_idx}, probe {probe_name})...");
+    if let Some(path) = &log_to_sqlite {
+        msg.push_str(&format!(" Recording to {path}."));
+    }
+    *state.sqlite_log.lock().unwrap() = sqlite_log;
+
+    state.status_routing.lock().unwrap().quiet_connect = profile.quiet_connect;
+    *state.active_profile.lock().unwrap() = Some(profile.clone());
+    update_elf_watcher(&app, Some(&profile));
+    state.hot_reload.store(false, Ordering::Relaxed);
+    state.reattach.store(false, Ordering::Relaxed);
+    state.rtt_running.store(true, Ordering::Relaxed);
+    PERF.reset();
+
+    let (commands_tx, commands_rx) = std::sync::mpsc::channel();
+    *state.rtt_commands.lock().unwrap() = Some(commands_tx);
+
+    std::thread::spawn(move || {
+        if let Err(e) = run_rtt_session(
+            LogSink::App(&app),
+            profile,
+            probe_info,
+            &stop_flag,
+            commands_rx,
+        ) {
+            emit_rtt_error(&app, &e);
+        }
+        let state = app.state::<AppState>();
+        state.rtt_running.store(false, Ordering::Relaxed);
+        *state.rtt_commands.lock().unwrap() = None;
+        // Dropping commits what's left
+        state.sqlite_log.lock().unwrap().take();
+        update_elf_watcher(&app, None);
+        state.control_blocks.lock().unwrap().clear();
+        state.session_probe.lock().unwrap().take();
+        let _ = app.emit("rtt-disconnected", ());
+    });
+
+    Ok(msg)
+}
+
+/// Connect, read and reconnect until stopped, the sink goes away or a fatal
+/// error. Runs on its own thread: `start_rtt`'s for the GUI, the main one headless.
+fn run_rtt_session(
+    sink: LogSink,
+    mut profile: Profile,
+    mut probe_info: probe_rs::probe::DebugProbeInfo,
+    stop_flag: &Arc<AtomicBool>,
+    commands: std::sync::mpsc::Receiver<RttCommand>,
+) -> Result<(), AppError> {
+    let mut session_state = SessionState::new(commands);
+    // Set by auto-tune; overrides the profile's speed for this session
+    let mut tuned_speed: Option<u32> = None;
+
+    loop {
+        if stop_flag.load(Ordering::Relaxed) {
+            break;
+        }
+        let connect_start = std::time::Instant::now();
+        // A boot loop would repeat these every cycle; errors still show
+        let looping = session_state.boot_loop.is_looping();
+        let progress = move |level: &str, msg: &str| {
+            if !looping {
+                sink.connect_status(level, msg);
+            }
+        };
+
+        let chip = profile.chip.clone();
+        let core_idx = profile.core.unwrap_or(0);
+        // Profiles saved before address validation existed can still hold junk
+        let scan_region = parse_scan_region(&profile.rtt_address)?;
+        session_state.options = ParseOptions::from_profile(&profile);
+        session_state.idle_timeout = profile.idle_timeout_s.map(std::time::Duration::from_secs);
+        session_state.read_strategy = ReadStrategy::from_profile(&profile);
+        // A target that stays away runs the timer out between reconnects too
+        if let Some(secs) = session_state.idle_expired() {
+            emit_idle_disconnect(sink, secs);
+            break;
+        }
+        validate_both_cores(&profile)?;
+
+        let mut probe = match probe_info.open() {
+            Ok(p) => p,
+            Err(e) => {
+                sink.last_error("Probe open failed", error_chain(&e));
+                match probe_open_error(&e) {
+                    Some(err) => sink.status("error", &format!("{err} ({e}). Retrying in 3s...")),
+                    None => progress(
+                        "warn",
+                        &format!("Probe open failed: {e}. Retrying in 3s..."),
+                    ),
+                }
+                if let Some(info) =
+                    reconnect_wait(sink, std::time::Duration::from_secs(3), &profile)
+                {
+                    probe_info = info;
+                }
+                continue;
+            }
+        };
+        let probe_opened = std::time::Instant::now();
+        if let Some(khz) = tuned_speed.or(profile.speed_khz) {
+            if let Err(e) = probe.set_speed(khz) {
+                sink.status(
+                    "warn",
+                    &format!("Probe rejected {khz} kHz ({e}), using its default."),
+                );
+            }
+        }
+        let speed_khz = probe.speed_khz();
+        if let Some(app) = sink.app() {
+            *app.state::<AppState>().session_probe.lock().unwrap() =
+                Some((probe_key(&probe_info), session_probe_caps(&mut probe)));
+        }
+
+        let mut target = match probe_rs::config::get_target_by_name(&chip) {
+            Ok(t) => t,
+            Err(e) => {
+                return Err(AppError::UnknownChip(format!("Unknown chip '{chip}': {e}")));
+            }
+        };
+
+        if let Some(ap) = profile.access_port {
+            apply_access_port(&mut target, core_idx, ap)?;
+            progress("info", &format!("Using access port {ap}."));
+        }
+
+        if let Some(name) = &profile.connect_sequence {
+            ConnectSequence::parse(name).and_then(|seq| seq.play(&mut probe, &target))?;
+            progress("info", &format!("Sent connect sequence '{name}'."));
+        }
+
+        let attach_start = std::time::Instant::now();
+        let mut session = match probe.attach(target, probe_rs::Permissions::default()) {
+            Ok(s) => s,
+            Err(probe_rs::Error::Arm(probe_rs::architecture::arm::ArmError::ApDoesNotExist(
+                addr,
+            ))) => {
+                return Err(AppError::InvalidInput(format!(
+                    "Access port {addr:?} does not exist on this target. Check the profile's access port."
+                )));
+            }
+            Err(e) => {
+                sink.last_error("Attach failed", error_chain(&e));
+                if let Some(err) = protection_error(&e) {
+                    return Err(err);
+                }
+                // Some probes open fine and only report the conflict once we talk to them
+                let in_use = match &e {
+                    probe_rs::Error::Probe(pe) => probe_open_error(pe),
+                    _ => None,
+                };
+                match in_use {
+                    Some(err) => sink.status("error", &format!("{err} ({e}). Retrying in 3s...")),
+                    None => progress("warn", &format!("Attach failed: {e}. Retrying in 3s...")),
+                }
+                if let Some(info) =
+                    reconnect_wait(sink, std::time::Duration::from_secs(3), &profile)
+                {
+                    probe_info = info;
+                }
+                continue;
+            }
+        };
+
+        let attached = std::time::Instant::now();
+        let grace = std::time::Duration::from_millis(
+            profile
+                .rtt_init_grace_ms
+                .unwrap_or(DEFAULT_RTT_INIT_GRACE_MS),
+        );
+        let rtt = match attach_rtt(
+            &mut session,
+            core_idx,
+            &scan_region,
+            profile.rtt_id.as_deref(),
+            grace,
+            sink,
+            looping,
+        ) {
+            Ok(r) => r,
+            Err(e) if e.is_fatal() => {
+                return Err(e);
+            }
+            Err(e) => {
+                progress("warn", &format!("{e}. Retrying in 3s..."));
+                std::thread::sleep(std::time::Duration::from_secs(3));
+                continue;
+            }
+        };
+        let mut cores = vec![RttCore {
+            index: core_idx,
+            name: None,
+            rtt,
+        }];
+
+        // probe-rs releases the net core's FORCEOFF while attaching, so it is
+        // running and debuggable here. Its RAM is separate; scan all of it.
+        if profile.both_cores {
+            progress("info", "Attaching to the network core...");
+            match attach_rtt(
+                &mut session,
+                NRF5340_NET_CORE,
+                &probe_rs::rtt::ScanRegion::Ram,
+                None,
+                grace,
+                sink,
+                looping,
+            ) {
+                Ok(rtt) => {
+                    cores[0].name = Some("app".to_string());
+                    cores.push(RttCore {
+                        index: NRF5340_NET_CORE,
+                        name: Some("net".to_string()),
+                        rtt,
+                    });
+                }
+                Err(e) => sink.status(
+                    "warn",
+                    &format!("Network core: {e}. Showing the app core only."),
+                ),
+            }
+        }
+        if !profile.extra_rtt_blocks.is_empty() {
+            cores[0].name = Some("main".to_string());
+            cores.extend(attach_extra_rtt_blocks(
+                &mut session,
+                core_idx,
+                &profile,
+                sink,
+            ));
+        }
+        if let Some(app) = sink.app() {
+            *app.state::<AppState>().control_blocks.lock().unwrap() =
+                cores.iter().map(ControlBlockInfo::of).collect();
+        }
+        let scanned = std::time::Instant::now();
+        report_connect_timing(
+            sink,
+            looping,
+            ConnectTiming {
+                probe_open_ms: millis_between(connect_start, probe_opened),
+                protocol_select_ms: millis_between(probe_opened, attach_start),
+                target_attach_ms: millis_between(attach_start, attached),
+                rtt_scan_ms: millis_between(attached, scanned),
+                total_ms: millis_between(connect_start, scanned),
+                exact_address: matches!(scan_region, probe_rs::rtt::ScanRegion::Exact(_)),
+            },
+        );
+
+        match session.core(core_idx) {
+            Ok(mut core) => {
+                if let Some(reason) = read_reset_reason(&mut core, &chip, core_idx) {
+                    progress("info", &format!("Reset reason: {reason}"));
+                }
+                if profile.show_device_id {
+                    emit_device_id(sink, &mut core, &chip, core_idx);
+                }
+            }
+            Err(e) => {
+                progress(
+                    "warn",
+                    &format!("Core access failed: {e}. Retrying in 3s..."),
+                );
+                std::thread::sleep(std::time::Duration::from_secs(3));
+                continue;
+            }
+        }
+
+        match rtt_read_loop(
+            &mut session,
+            &mut cores,
+            &mut session_state,
+            stop_flag,
+            &profile,
+            sink,
+        ) {
+            ReadResult::Stopped | ReadResult::AppClosed => break,
+            // The GUI can switch auto-tune on and off without a reattach
+            ReadResult::Unstable
+                if sink.app().map_or(profile.auto_tune_speed, |app| {
+                    app.state::<AppState>()
+                        .active_profile
+                        .lock()
+                        .unwrap()
+                        .as_ref()
+                        .is_some_and(|p| p.auto_tune_speed)
+                }) =>
+            {
+                PERF.reconnects.fetch_add(1, Ordering::Relaxed);
+                let half = speed_khz / 2;
+                if half < AUTO_TUNE_MIN_SPEED_KHZ {
+                    sink.status(
+                        "warn",
+                        &format!("RTT reads still fail at {speed_khz} kHz, the lowest auto-tune tries. Check the wiring, ground and Vtref. Retrying in 2s..."),
+                    );
+                    std::thread::sleep(std::time::Duration::from_secs(2));
+                } else {
+                    sink.status(
+                        "warn",
+                        &format!("RTT reads failed right after attach at {speed_khz} kHz. Retrying at {half} kHz..."),
+                    );
+                    tuned_speed = Some(half);
+                    session_state.tuned_speed_khz = Some(half);
+                }
+            }
+            ReadResult::Disconnected | ReadResult::Unstable => {
+                PERF.reconnects.fetch_add(1, Ordering::Relaxed);
+                session_state.boot_loop.record(sink);
+                if !session_state.boot_loop.is_looping() {
+                    sink.connect_status("warn", "Disconnected. Reconnecting in 2s...");
+                }
+                if let Some(info) =
+                    reconnect_wait(sink, std::time::Duration::from_secs(2), &profile)
+                {
+                    probe_info = info;
+                }
+            }
+            ReadResult::Reattach => {
+                if let Some(p) = sink.app().and_then(|app| {
+                    app.state::<AppState>()
+                        .active_profile
+                        .lock()
+                        .unwrap()
+                        .clone()
+                }) {
+                    // A new pinned speed replaces whatever auto-tune settled on
+                    if p.speed_khz != profile.speed_khz {
+                        tuned_speed = None;
+                    }
+                    if p.probe_serial != profile.probe_serial {
+                        match p.probe_serial.clone().map(|serial| {
+                            enumerate_on_runtime(move || find_probe_by_serial(&serial))
+                        }) {
+                            Some(Some(info)) => probe_info = info,
+                            Some(None) => sink.status(
+                                "warn",
+                                "The profile's new probe serial isn't connected; keeping the current probe.",
+                            ),
+                            None => {}
+                        }
+                    }
+                    profile = p;
+                }
+                sink.connect_status("info", "Re-attaching with updated settings...");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Emit `connect-timing` and a one-line summary, and keep it for `last_connect_timing`.
+/// `quiet` leaves out the line while the target boot-loops.
+fn report_connect_timing(sink: LogSink, quiet: bool, timing: ConnectTiming) {
+    let mut msg = format!(
+        "Connected in {} ms: probe open {} ms, setup {} ms, attach {} ms, RTT scan {} ms.",
+        timing.total_ms,
+        timing.probe_open_ms,
+        timing.protocol_select_ms,
+        timing.target_attach_ms,
+        timing.rtt_scan_ms
+    );
+    if !timing.exact_address && timing.rtt_scan_ms >= SLOW_RTT_SCAN_MS {
+        msg.push_str(" Most of the scan goes away with the control block address in the profile (load the ELF).");
+    }
+    if !quiet {
+        sink.connect_status("info", &msg);
+    }
+    if let Some(app) = sink.app() {
+        let _ = app.emit("connect-timing", &timing);
+        *app.state::<AppState>().connect_timing.lock().unwrap() = Some(timing);
+    }
+}
+
+/// Step durations of the latest connect, `None` before the first one.
+#[tauri::command]
+async fn last_connect_timing(app: AppHandle) -> Result<Option<ConnectTiming>, AppError> {
+    Ok(app
+        .state::<AppState>()
+        .connect_timing
+        .lock()
+        .unwrap()
+        .clone())
+}
+
+/// `err`'s message, then that of each `source()` beneath it. Our status lines
+/// show the outermost layer only, which is rarely the one that explains it.
+fn error_chain(err: &dyn std::error::Error) -> Vec<String> {
+    let mut chain = vec![err.to_string()];
+    let mut source = err.source();
+    while let Some(e) = source {
+        let msg = e.to_string();
+        // Some layers print their source as their whole message
+        if chain.last() != Some(&msg) {
+            chain.push(msg);
+        }
+        source = e.source();
+    }
+    chain
+}
+
+/// Keep a connection failure for `last_error_detail`: the step, then its chain.
+fn set_last_error(app: &AppHandle, step: &str, chain: Vec<String>) {
+    let mut detail = vec![step.to_string()];
+    detail.extend(chain);
+    *app.state::<AppState>().last_error.lock().unwrap() = detail;
+}
+
+/// The latest connection failure, outermost first: e.g. "Attach failed",
+/// then the probe-rs error, then the USB error under it. Empty before any.
+#[tauri::command]
+async fn last_error_detail(app: AppHandle) -> Result<Vec<String>, AppError> {
+    Ok(app.state::<AppState>().last_error.lock().unwrap().clone())
+}
+
+/// Control blocks the running session reads, with their labels. Empty when
+/// not connected.
+#[tauri::command]
+async fn list_control_blocks(app: AppHandle) -> Result<Vec<ControlBlockInfo>, AppError> {
+    Ok(app
+        .state::<AppState>()
+        .control_blocks
+        .lock()
+        .unwrap()
+        .clone())
+}
+
+#[tauri::command]
+async fn perf_counters() -> Result<PerfCounters, AppError> {
+    Ok(PERF.snapshot())
+}
+
+#[tauri::command]
+async fn reset_perf() -> Result<(), AppError> {
+    PERF.reset();
+    Ok(())
+}
+
+fn send_rtt_command(app: &AppHandle, cmd: RttCommand) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let commands = state.rtt_commands.lock().unwrap();
+    commands
+        .as_ref()
+        .ok_or_else(|| AppError::NotConnected("No RTT session running".to_string()))?
+        .send(cmd)
+        .map_err(|_| AppError::NotConnected("RTT session ended".to_string()))
+}
+
+/// Wait for the RTT thread's reply. It only picks commands up while attached,
+/// so during a reconnect this times out.
+async fn await_rtt_reply<T>(
+    rx: tokio::sync::oneshot::Receiver<Result<T, AppError>>,
+) -> Result<T, AppError> {
+    match tokio::time::timeout(std::time::Duration::from_secs(3), rx).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(_)) => Err(AppError::NotConnected("RTT session ended".to_string())),
+        Err(_) => Err(AppError::NotConnected(
+            "Target not attached (reconnecting?)".to_string(),
+        )),
+    }
+}
+
+/// Round trip through the down and up channels in milliseconds. Needs
+/// firmware that echoes input, like most RTT shells.
+#[tauri::command]
+async fn rtt_ping(app: AppHandle) -> Result<f64, AppError> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    send_rtt_command(&app, RttCommand::Ping(tx))?;
+    await_rtt_reply(rx).await
+}
+
+/// Like `select_probe`, call it on the async runtime.
+fn find_probe_by_serial(serial: &str) -> Option<probe_rs::probe::DebugProbeInfo> {
+    get_lister()
+        .list_all()
+        .into_iter()
+        .find(|p| p.serial_number.as_deref() == Some(serial))
+}
+
+/// Run a probe enumeration on the async runtime from a plain thread (the RTT
+/// thread, or main when headless) and wait for it.
+fn enumerate_on_runtime<T: Send + 'static>(list: impl FnOnce() -> T + Send + 'static) -> T {
+    let (tx, rx) = std::sync::mpsc::channel();
+    tauri::async_runtime::spawn(async move {
+        let _ = tx.send(list());
+    });
+    rx.recv().expect("probe enumeration panicked")
+}
+
+/// How often `reconnect_wait` lists probes while watching for a serial
+const PROBE_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Back off before the next connect attempt. With `Profile.watch_probe_serial`,
+/// the wait ends as soon as the profile's probe is listed again after being
+/// absent, returning its new listing (a swapped board enumerates afresh).
+fn reconnect_wait(
+    sink: LogSink,
+    delay: std::time::Duration,
+    profile: &Profile,
+) -> Option<probe_rs::probe::DebugProbeInfo> {
+    let serial = match &profile.probe_serial {
+        Some(serial) if profile.watch_probe_serial => serial,
+        _ => {
+            std::thread::sleep(delay);
+            return None;
+        }
+    };
+    let deadline = std::time::Instant::now() + delay;
+    let mut was_absent = false;
+    loop {
+        let listed = serial.clone();
+        match enumerate_on_runtime(move || find_probe_by_serial(&listed)) {
+            Some(info) if was_absent => {
+                sink.connect_status("info", &format!("Probe {serial} is back. Reconnecting..."));
+                return Some(info);
+            }
+            Some(_) => {}
+            None => was_absent = true,
+        }
+        let left = deadline.saturating_duration_since(std::time::Instant::now());
+        if left.is_zero() {
+            return None;
+        }
+        std::thread::sleep(PROBE_WATCH_INTERVAL.min(left));
+    }
+}
+
+/// Enumerate on the Tauri async thread — safe for macOS HID.
+fn select_probe(probe_idx: usize) -> Result<probe_rs::probe::DebugProbeInfo, AppError> {
+    let probes = get_lister().list_all();
+    if probes.is_empty() {
+        return Err(AppError::NoProbe("No debug probes found".to_string()));
+    }
+    if probe_idx >= probes.len() {
+        return Err(AppError::InvalidInput(format!(
+            "Probe index {probe_idx} out of range (found {})",
+            probes.len()
+        )));
+    }
+    Ok(probes[probe_idx].clone())
+}
+
+/// Settings that only take effect on a fresh attach. Everything else is
+/// applied to the live parsers without dropping the connection.
+fn needs_reattach(old: &Profile, new: &Profile) -> bool {
+    old.chip != new.chip
+        || old.rtt_address != new.rtt_address
+        || old.core != new.core
+        || old.access_port != new.access_port
+        || old.connect_sequence != new.connect_sequence
+        || old.speed_khz != new.speed_khz
+        || old.probe_serial != new.probe_serial
+        || old.rtt_id != new.rtt_id
+        || old.target_yaml != new.target_yaml
+        || old.both_cores != new.both_cores
+        || old.extra_rtt_blocks != new.extra_rtt_blocks
+}
+
+/// Apply changed profile settings to the running session, re-attaching only
+/// when a connection-level setting changed. The probe is not re-enumerated.
+#[tauri::command]
+async fn restart_rtt(app: AppHandle, profile: Profile) -> Result<String, AppError> {
+    let state = app.state::<AppState>();
+    if !state.rtt_running.load(Ordering::Relaxed) || state.stop_flag.load(Ordering::Relaxed) {
+        return Err(AppError::NotConnected("No RTT session running".to_string()));
+    }
+
+    update_elf_watcher(&app, Some(&profile));
+    if let Some(path) = &profile.target_yaml {
+        register_target_yaml(path, &profile.chip)?;
+    }
+    let mut active = state.active_profile.lock().unwrap();
+    let reattach = active
+        .as_ref()
+        .is_none_or(|old| needs_reattach(old, &profile));
+    state.status_routing.lock().unwrap().quiet_connect = profile.quiet_connect;
+    *active = Some(profile);
+    drop(active);
+
+    if reattach {
+        state.reattach.store(true, Ordering::Relaxed);
+        Ok("Re-attaching with new settings".to_string())
+    } else {
+        state.hot_reload.store(true, Ordering::Relaxed);
+        Ok("Settings applied without reconnect".to_string())
+    }
+}
+
+// ── Locked target recovery ──
+
+/// Erase a read-back protected chip so it can be debugged again. With erase-all
+/// permitted, probe-rs runs the vendor unlock while attaching (nRF: CTRL-AP
+/// ERASEALL on every locked core). Wipes flash, UICR and RAM.
+#[tauri::command]
+async fn recover_target(
+    app: AppHandle,
+    chip: String,
+    probe_index: Option<usize>,
+) -> Result<String, AppError> {
+    let state = app.state::<AppState>();
+    if state.rtt_running.load(Ordering::Relaxed) {
+        return Err(AppError::InvalidInput(
+            "Stop the RTT session before recovering the target".to_string(),
+        ));
+    }
+    let probe_info = select_probe(probe_index.unwrap_or(0))?;
+    let target = probe_rs::config::get_target_by_name(&chip)
+        .map_err(|e| AppError::UnknownChip(format!("Unknown chip '{chip}': {e}")))?;
+
+    emit_rtt_status(
+        &app,
+        "warn",
+        &format!("Recovering {chip}: erasing the whole chip..."),
+    );
+    tauri::async_runtime::spawn_blocking(move || {
+        let probe = probe_info.open().map_err(|e| {
+            probe_open_error(&e)
+                .unwrap_or_else(|| AppError::Other(format!("Probe open failed: {e}")))
+        })?;
+        let permissions = probe_rs::Permissions::new().allow_erase_all();
+        probe
+            .attach(target, permissions)
+            .map(drop)
+            .map_err(|e| AppError::Other(format!("Recovery failed: {e}")))
+    })
+    .await
+    .map_err(|e| AppError::Other(format!("Recovery task failed: {e}")))??;
+
+    let msg = format!("{chip} recovered. The chip is blank — flash firmware before connecting.");
+    emit_rtt_status(&app, "info", &msg);
+    Ok(msg)
+}
+
+/// Jittered mock timestamps wrap back to zero after this long, like a
+/// free-running target tick counter
+const MOCK_WRAP_MS: u64 = 60_000;
+
+/// Target time of the mock's `idx`th line: 250 ms apart, or with `jitter`,
+/// sometimes repeating the previous stamp, sometimes stepping back behind
+/// earlier lines (as clocks on different cores/channels disagree), and wrapping.
+fn mock_timestamp_ms(idx: u64, jitter: bool) -> u64 {
+    let ms = idx * 250;
+    if !jitter {
+        return ms;
+    }
+    let ms = match idx % 11 {
+        5 => ms.saturating_sub(250),
+        // Back 250..1000 ms, past one to four earlier lines
+        8 => ms.saturating_sub((idx * 37 % 4 + 1) * 250),
+        _ => ms,
+    };
+    ms % MOCK_WRAP_MS
+}
+
+/// Start the simulated log stream. `jitter` makes its timestamps adversarial
+/// (duplicates, out of order, wraparound) for testing time sorting.
+#[tauri::command]
+async fn start_mock(app: AppHandle, jitter: Option<bool>) -> Result<String, AppError> {
+    let jitter = jitter.unwrap_or(false);
+    let state = app.state::<AppState>();
+    state.stop_flag.store(false, Ordering::Relaxed);
+
+    let stop_flag = state.stop_flag.clone();
+    let app_clone = app.clone();
+
+    let messages: Vec<(&'static str, &'static str, &'static str)> = vec![
+        ("ble_mesh", "inf", "Mesh network initialized, node count: 5"),
+        ("cellular", "inf", "Modem powered on"),
+        ("gps", "inf", "Cold start, searching for satellites..."),
+        ("main", "inf", "System boot, fw v2.4.1"),
+        ("uwb", "dbg", "TWR ranging started with anchor 1"),
+        ("battery", "inf", "Voltage: 3.8V (72%)"),
+        ("plas", "inf", "PLAS engine started"),
+        ("ble_mesh", "wrn", "Peer timeout: 0x1A3F"),
+        ("cellular", "err", "Network registration failed"),
+        ("gps", "inf", "Fix acquired: 8 satellites"),
+        ("main", "dbg", "Heap: 42KB used / 128KB total"),
+        ("uwb", "inf", "Distance to anchor 1: 4.2m"),
+        ("ble_mesh", "inf", "Peer connected: 0xAB12"),
+        ("cellular", "inf", "Signal: RSRP=-87 dBm"),
+        ("plas", "wrn", "Worker approaching restricted area"),
+        ("battery", "dbg", "Current draw: 34mA"),
+    ];
+
+    tokio::spawn(async move {
+        let mut idx = 0u64;
+        loop {
+            if stop_flag.load(Ordering::Relaxed) {
+                break;
+            }
+            let (tag, level, msg) = messages[(idx as usize) % messages.len()];
+            let stamp = mock_timestamp_ms(idx, jitter);
+            let secs = stamp / 1000;
+            let ms = stamp % 1000;
+            let raw = format!(
+                "[00:{:02}:{:02}.{:03},000] <{level}> {tag}: {msg}",
+                secs / 60,
+                secs % 60,
+                ms
+            );
+            let mut entry = parse_line(&raw, false);
+            if emit_log(&app_clone, &mut entry).is_err() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(150 + (idx % 7) * 50)).await;
+            idx += 1;
+        }
+        let _ = app_clone.emit("rtt-stopped", ());
+    });
+
+    Ok(if jitter {
+        "Mock started with jittered timestamps".to_string()
+    } else {
+        "Mock started".to_string()
+    })
+}
+
+/// Files above this size report `elf-progress` while parsing
+const ELF_PROGRESS_THRESHOLD: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+struct ElfProgress {
+    elf_path: String,
+    stage: &'static str,
+    percent: u8,
+}
+
+/// Cancellation and progress hooks for one ELF parse. The default (no app
+/// handle) never cancels and stays silent — used by the ELF watcher.
+#[derive(Default)]
+struct ElfParseCtl {
+    app: Option<AppHandle>,
+    generation: u64,
+}
+
+impl ElfParseCtl {
+    fn check(&self) -> Result<(), AppError> {
+        match &self.app {
+            Some(app)
+                if app
+                    .state::<AppState>()
+                    .elf_parse_gen
+                    .load(Ordering::Relaxed)
+                    != self.generation =>
+            {
+                Err(AppError::Cancelled("ELF parse cancelled".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn progress(&self, elf_path: &str, stage: &'static str, percent: u8) {
+        if let Some(app) = &self.app {
+            let _ = app.emit(
+                "elf-progress",
+                &ElfProgress {
+                    elf_path: elf_path.to_string(),
+                    stage,
+                    percent,
+                },
+            );
+        }
+    }
+}
+
+// ── Session replay ──
+
+/// Microseconds since boot from a Zephyr-style "HH:MM:SS.mmm,uuu" timestamp.
+fn device_timestamp_us(ts: &str) -> Option<u64> {
+    let (hms, frac) = ts.split_once('.')?;
+    let mut parts = hms.split(':').map(|p| p.parse::<u64>().ok());
+    let (h, m, sec) = (parts.next()??, parts.next()??, parts.next()??);
+    let (ms, us) = match frac.split_once(',') {
+        Some((ms, us)) => (ms.parse::<u64>().ok()?, us.parse::<u64>().ok()?),
+        None => (frac.parse::<u64>().ok()?, 0),
+    };
+    Some(((h * 60 + m) * 60 + sec) * 1_000_000 + ms * 1000 + us)
+}
+
+/// Re-emit a saved JSON session on `rtt-log` as if it were live. Gaps come from
+/// the device timestamps (entries without one follow immediately), divided by `speed`.
+#[tauri::command]
+async fn replay_session(app: AppHandle, path: String, speed: f64) -> Result<String, AppError> {
+    if !(speed.is_finite() && speed > 0.0) {
+        return Err(AppError::InvalidInput(format!(
+            "Replay speed must be positive, got {speed}"
+        )));
+    }
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| AppError::Io(format!("Failed to read session: {e}")))?;
+    let entries: Vec<LogEntry> = serde_json::from_str(&text)
+        .map_err(|e| AppError::InvalidInput(format!("Replay needs a JSON log export: {e}")))?;
+
+    let state = app.state::<AppState>();
+    state.stop_flag.store(false, Ordering::Relaxed);
+    let stop_flag = state.stop_flag.clone();
+    let count = entries.len();
+
+    tokio::spawn(async move {
+        let mut prev_us: Option<u64> = None;
+        for mut entry in entries {
+            if stop_flag.load(Ordering::Relaxed) {
+                break;
+            }
+            let ts_us = entry
+                .device_timestamp
+                .as_deref()
+                .and_then(device_timestamp_us);
+            if let (Some(prev), Some(cur)) = (prev_us, ts_us) {
+                // A reboot mid-session makes time go backwards — just carry on
+                if cur > prev {
+                    let gap = (cur - prev) as f64 / speed;
+                    tokio::time::sleep(std::time::Duration::from_micros(gap as u64)).await;
+                }
+            }
+            if ts_us.is_some() {
+                prev_us = ts_us;
+            }
+            // emit_log gives fresh ids, so replayed lines never collide with what's on screen
+            if emit_log(&app, &mut entry).is_err() {
+                break;
+            }
+        }
+        let _ = app.emit("rtt-stopped", ());
+    });
+
+    Ok(format!("Replaying {count} entries at {speed}x"))
+}
+
+// ── Session diff ──
+
+/// Past this many inserted + removed lines the diff gives up; the trace grows
+/// with the square of it.
+const MAX_DIFF_EDITS: usize = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DiffOp {
+    Equal,
+    Delete,
+    Insert,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum HunkKind {
+    Equal,
+    Removed,
+    Added,
+    /// Lines removed from `a` and replaced by different lines in `b`
+    Changed,
+}
+
+/// `a_start..a_start + a_len` in the first session lines up with
+/// `b_start..b_start + b_len` in the second (indices into the inputs, not ids).
+#[derive(Debug, Clone, Serialize)]
+struct DiffHunk {
+    kind: HunkKind,
+    a_start: usize,
+    a_len: usize,
+    b_start: usize,
+    b_len: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+struct SessionDiff {
+    hunks: Vec<DiffHunk>,
+    /// Lines only in `b`
+    added: usize,
+    /// Lines only in `a`
+    removed: usize,
+}
+
+/// Shortest edit script from `a` to `b`, one op per element (Myers, O(ND)).
+/// Only the live diagonals of each round are kept, so memory is O(D²).
+fn myers_diff<T: PartialEq>(a: &[T], b: &[T]) -> Result<Vec<DiffOp>, AppError> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let offset = n + m + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'rounds: for d in 0..=(n + m) {
+        if d as usize > MAX_DIFF_EDITS {
+            return Err(AppError::InvalidInput(format!(
+                "Sessions differ in more than {MAX_DIFF_EDITS} lines — too different to diff"
+            )));
+        }
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                break 'rounds;
+            }
+        }
+    }
+
+    // Walk the rounds backwards from the end point
+    let mut ops = Vec::with_capacity((n + m) as usize);
+    let (mut x, mut y) = (n, m);
+    for (d, prev) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        if d == 0 {
+            ops.extend(std::iter::repeat_n(DiffOp::Equal, x as usize));
+            break;
+        }
+        let at = |k: isize| prev[(k + d) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            ops.push(DiffOp::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        ops.push(if x == prev_x {
+            DiffOp::Insert
+        } else {
+            DiffOp::Delete
+        });
+        (x, y) = (prev_x, prev_y);
+    }
+    ops.reverse();
+    Ok(ops)
+}
+
+/// Group an edit script into equal / removed / added / changed runs.
+fn diff_hunks(ops: &[DiffOp]) -> SessionDiff {
+    let mut diff = SessionDiff::default();
+    let (mut a, mut b) = (0, 0);
+    let mut i = 0;
+    while i < ops.len() {
+        let (a_start, b_start) = (a, b);
+        if ops[i] == DiffOp::Equal {
+            while i < ops.len() && ops[i] == DiffOp::Equal {
+                a += 1;
+                b += 1;
+                i += 1;
+            }
+        } else {
+            while i < ops.len() && ops[i] != DiffOp::Equal {
+                match ops[i] {
+                    DiffOp::Delete => a += 1,
+                    _ => b += 1,
+                }
+                i += 1;
+            }
+        }
+        let (a_len, b_len) = (a - a_start, b - b_start);
+        let kind = match (ops[i - 1], a_len, b_len) {
+            (DiffOp::Equal, _, _) => HunkKind::Equal,
+            (_, _, 0) => HunkKind::Removed,
+            (_, 0, _) => HunkKind::Added,
+            _ => HunkKind::Changed,
+        };
+        if kind != HunkKind::Equal {
+            diff.removed += a_len;
+            diff.added += b_len;
+        }
+        diff.hunks.push(DiffHunk {
+            kind,
+            a_start,
+            a_len,
+            b_start,
+            b_len,
+        });
+    }
+    diff
+}
+
+/// Line up two captures by tag + message, ignoring ids and timestamps, and
+/// return the runs that differ as ranges into `a` and `b`.
+#[tauri::command]
+async fn diff_sessions(a: Vec<LogEntry>, b: Vec<LogEntry>) -> Result<SessionDiff, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let key = |e: &LogEntry| (e.tag.clone(), e.message.clone());
+        let ka: Vec<_> = a.iter().map(key).collect();
+        let kb: Vec<_> = b.iter().map(key).collect();
+        // Shared head and tail are common between runs and cheap to skip
+        let head = ka.iter().zip(&kb).take_while(|(x, y)| x == y).count();
+        let tail = ka[head..]
+            .iter()
+            .rev()
+            .zip(kb[head..].iter().rev())
+            .take_while(|(x, y)| x == y)
+            .count();
+        let mut ops = vec![DiffOp::Equal; head];
+        ops.extend(myers_diff(
+            &ka[head..ka.len() - tail],
+            &kb[head..kb.len() - tail],
+        )?);
+        ops.extend(std::iter::repeat_n(DiffOp::Equal, tail));
+        Ok(diff_hunks(&ops))
+    })
+    .await
+    .map_err(|e| AppError::Other(format!("Diff task failed: {e}")))?
+}
+
+// ── Log volume histogram ──
+
+/// More buckets than this can't be drawn usefully; ask for wider ones
+const MAX_HISTOGRAM_BUCKETS: u64 = 20_000;
+
+#[derive(Debug, Clone, Serialize)]
+struct HistogramBucket {
+    /// Host time (ms since the Unix epoch) the bucket starts at
+    start: u64,
+    count: u32,
+    by_level: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+struct LogHistogram {
+    /// Every bucket from the first entry's to the last's, empty ones included
+    buckets: Vec<HistogramBucket>,
+    /// Entries without a host timestamp, left out
+    excluded: usize,
+}
+
+/// Count entries per `bucket_ms` of host time, and per level within each
+/// bucket, for a timeline of a long capture. Buckets are aligned to
+/// multiples of `bucket_ms`.
+#[tauri::command]
+async fn log_histogram(entries: Vec<LogEntry>, bucket_ms: u64) -> Result<LogHistogram, AppError> {
+    if bucket_ms == 0 {
+        return Err(AppError::InvalidInput(
+            "Histogram buckets must be at least 1 ms".to_string(),
+        ));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let stamped = entries.iter().filter_map(|e| e.host_timestamp);
+        let (Some(first), Some(last)) = (stamped.clone().min(), stamped.max()) else {
+            return Ok(LogHistogram {
+                buckets: Vec::new(),
+                excluded: entries.len(),
+            });
+        };
+        let first = first - first % bucket_ms;
+        let len = (last - first) / bucket_ms + 1;
+        if len > MAX_HISTOGRAM_BUCKETS {
+            return Err(AppError::InvalidInput(format!(
+                "{len} buckets of {bucket_ms} ms is too many; use wider buckets"
+            )));
+        }
+        let mut histogram = LogHistogram {
+            buckets: (0..len)
+                .map(|i| HistogramBucket {
+                    start: first + i * bucket_ms,
+                    count: 0,
+                    by_level: BTreeMap::new(),
+                })
+                .collect(),
+            excluded: 0,
+        };
+        for entry in &entries {
+            let Some(ts) = entry.host_timestamp else {
+                histogram.excluded += 1;
+                continue;
+            };
+            let bucket = &mut histogram.buckets[((ts - first) / bucket_ms) as usize];
+            bucket.count += 1;
+            *bucket.by_level.entry(entry.level.clone()).or_default() += 1;
+        }
+        Ok(histogram)
+    })
+    .await
+    .map_err(|e| AppError::Other(format!("Histogram task failed: {e}")))?
+}
+
+// ── Latency between events ──
+
+/// Named group that, in both patterns, ties an end to the start with the
+/// same value ("req (?P<key>\d+) sent" → "req (?P<key>\d+) done")
+const LATENCY_KEY_GROUP: &str = "key";
+
+#[derive(Debug, Clone, Serialize)]
+struct LatencyPair {
+    /// Value of the `key` group, when the patterns have one
+    key: Option<String>,
+    start_id: u64,
+    /// None: no end followed this start
+    end_id: Option<u64>,
+    latency_us: Option<u64>,
+    /// "device" when both lines carry device timestamps that go forwards,
+    /// "host" otherwise
+    clock: Option<&'static str>,
+}
+
+/// Microseconds from `start` to `end`: device uptime when both lines have it
+/// and it didn't go backwards (a reset in between), host time otherwise.
+fn latency_between(start: &LogEntry, end: &LogEntry) -> Option<(u64, &'static str)> {
+    let device = |e: &LogEntry| e.device_timestamp.as_deref().and_then(device_timestamp_us);
+    if let (Some(a), Some(b)) = (device(start), device(end)) {
+        if b >= a {
+            return Some((b - a, "device"));
+        }
+    }
+    let (a, b) = (start.host_timestamp?, end.host_timestamp?);
+    Some((b.checked_sub(a)? * 1000, "host"))
+}
+
+/// The pairing behind `measure_latency`. Each key (or, unkeyed, the one
+/// `None` key) has its own queue of open starts, so an end finds its start
+/// without a scan.
+fn pair_latencies(entries: &[LogEntry], from: &Regex, to: &Regex, keyed: bool) -> Vec<LatencyPair> {
+    let key_of = |re: &Regex, text: &str| -> Option<Option<String>> {
+        let caps = re.captures(text)?;
+        Some(if keyed {
+            caps.name(LATENCY_KEY_GROUP).map(|m| m.as_str().to_string())
+        } else {
+            None
+        })
+    };
+    // Index into `pairs` and `entries` of each start still waiting, oldest first
+    let mut open: HashMap<Option<String>, std::collections::VecDeque<(usize, usize)>> =
+        HashMap::new();
+    let mut pairs: Vec<LatencyPair> = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        if let Some(key) = key_of(to, &entry.raw) {
+            if let Some(waiting) = open.get_mut(&key) {
+                let (p, start) = waiting.pop_front().expect("empty queues are removed");
+                if waiting.is_empty() {
+                    open.remove(&key);
+                }
+                let pair = &mut pairs[p];
+                pair.end_id = Some(entry.id);
+                if let Some((us, clock)) = latency_between(&entries[start], entry) {
+                    pair.latency_us = Some(us);
+                    pair.clock = Some(clock);
+                }
+            }
+        }
+        if let Some(key) = key_of(from, &entry.raw) {
+            open.entry(key.clone())
+                .or_default()
+                .push_back((pairs.len(), i));
+            pairs.push(LatencyPair {
+                key,
+                start_id: entry.id,
+                end_id: None,
+                latency_us: None,
+                clock: None,
+            });
+        }
+    }
+    pairs
+}
+
+/// Time from each line matching `from_pattern` to the next matching
+/// `to_pattern`, matched against the raw line. Several starts before an end
+/// pair first-in first-out, so overlapping operations each get their own
+/// time; with a `(?P<key>...)` group in both patterns, an end only closes a
+/// start with the same key. A line matching both closes a pair and then opens
+/// the next, so one pattern twice measures the period between occurrences.
+/// Starts never closed are listed without an end; ends without a start are
+/// ignored.
+#[tauri::command]
+async fn measure_latency(
+    entries: Vec<LogEntry>,
+    from_pattern: String,
+    to_pattern: String,
+) -> Result<Vec<LatencyPair>, AppError> {
+    let from = Regex::new(&from_pattern)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid start pattern: {e}")))?;
+    let to = Regex::new(&to_pattern)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid end pattern: {e}")))?;
+    let has_key = |re: &Regex| re.capture_names().any(|n| n == Some(LATENCY_KEY_GROUP));
+    let keyed = match (has_key(&from), has_key(&to)) {
+        (true, true) => true,
+        (false, false) => false,
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Use a (?P<{LATENCY_KEY_GROUP}>...) group in both patterns or neither"
+            )))
+        }
+    };
+
+    tauri::async_runtime::spawn_blocking(move || Ok(pair_latencies(&entries, &from, &to, keyed)))
+        .await
+        .map_err(|e| AppError::Other(format!("Latency task failed: {e}")))?
+}
+
+// ── SWO / ITM source ──
+
+/// Splits an ITM byte stream into stimulus-port payloads and the DWT packets
+/// `HwTrace` understands. Packets may span reads, so state carries over
+/// between `feed` calls.
+#[derive(Default)]
+struct ItmDecoder {
+    state: ItmState,
+    /// Payload so far of the hardware packet being read
+    hw_payload: Vec<u8>,
+}
+
+#[derive(Default)]
+enum ItmState {
+    #[default]
+    Header,
+    /// Source packet payload: (port or discriminator, is_hardware, bytes still expected)
+    Payload(u8, bool, usize),
+    /// Protocol packet continuation bytes (timestamps, extension)
+    Continuation,
+}
+
+/// What a `feed` call decoded.
+enum ItmPacket {
+    /// Software stimulus data, runs on one port merged
+    Stimulus(u8, Vec<u8>),
+    /// DWT exception trace: exception number and what happened to it
+    Exception(u16, ExceptionAction),
+    /// DWT PC sample; None when the core was asleep
+    PcSample(Option<u32>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ExceptionAction {
+    Entered,
+    Exited,
+    Returned,
+}
+
+impl ItmDecoder {
+    fn feed(&mut self, data: &[u8]) -> Vec<ItmPacket> {
+        let mut out: Vec<ItmPacket> = Vec::new();
+        for &b in data {
+            match self.state {
+                ItmState::Header => match b {
+                    // Synchronization: a run of zeros terminated by 0x80
+                    0x00 | 0x80 => {}
+                    _ if b & 0x03 != 0 => {
+                        let size = match b & 0x03 {
+                            1 => 1,
+                            2 => 2,
+                            _ => 4,
+                        };
+                        self.state = ItmState::Payload(b >> 3, b & 0x04 != 0, size);
+                    }
+                    _ if b & 0x80 != 0 => self.state = ItmState::Continuation,
+                    // Overflow and single-byte timestamps carry no payload
+                    _ => {}
+                },
+                ItmState::Payload(port, hardware, remaining) => {
+                    if !hardware {
+                        match out.last_mut() {
+                            Some(ItmPacket::Stimulus(p, bytes)) if *p == port => bytes.push(b),
+                            _ => out.push(ItmPacket::Stimulus(port, vec![b])),
+                        }
+                    } else {
+                        self.hw_payload.push(b);
+                    }
+                    self.state = if remaining > 1 {
+                        ItmState::Payload(port, hardware, remaining - 1)
+                    } else {
+                        if hardware {
+                            out.extend(hw_packet(port, &self.hw_payload));
+                            self.hw_payload.clear();
+                        }
+                        ItmState::Header
+                    };
+                }
+                ItmState::Continuation => {
+                    if b & 0x80 == 0 {
+                        self.state = ItmState::Header;
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Decode a hardware source packet by its discriminator. Event counter and
+/// data trace packets are skipped.
+fn hw_packet(discriminator: u8, payload: &[u8]) -> Option<ItmPacket> {
+    match (discriminator, payload) {
+        (1, &[lo, hi]) => {
+            let action = match (hi >> 4) & 0x03 {
+                1 => ExceptionAction::Entered,
+                2 => ExceptionAction::Exited,
+                3 => ExceptionAction::Returned,
+                _ => return None,
+            };
+            Some(ItmPacket::Exception(
+                u16::from(lo) | u16::from(hi & 0x01) << 8,
+                action,
+            ))
+        }
+        (2, &[_]) => Some(ItmPacket::PcSample(None)),
+        (2, &[a, b, c, d]) => Some(ItmPacket::PcSample(Some(u32::from_le_bytes([a, b, c, d])))),
+        _ => None,
+    }
+}
+
+/// Exception trace and PC sampling, on top of the stimulus ports.
+const DWT_CTRL_EXCTRCENA: u32 = 1 << 16;
+const DWT_CTRL_PCSAMPLENA: u32 = 1 << 12;
+/// Post-counter tap at CYCCNT bit 10 instead of bit 6
+const DWT_CTRL_CYCTAP: u32 = 1 << 9;
+const DWT_CTRL_POSTPRESET_SHIFT: u32 = 1;
+/// POSTPRESET and POSTINIT: 16 taps between PC samples, so every 16384 cycles
+const DWT_PC_SAMPLE_POSTCNT: u32 = 15;
+/// How often `HwTrace` logs its counts
+const HW_TRACE_SUMMARY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+/// PC sample addresses listed per summary
+const HW_TRACE_TOP_PCS: usize = 5;
+
+/// "HardFault", "SysTick", "IRQ 12" for an exception number.
+fn exception_name(number: u16) -> String {
+    match number {
+        1 => "Reset".to_string(),
+        2 => "NMI".to_string(),
+        3 => "HardFault".to_string(),
+        4 => "MemManage".to_string(),
+        5 => "BusFault".to_string(),
+        6 => "UsageFault".to_string(),
+        7 => "SecureFault".to_string(),
+        11 => "SVCall".to_string(),
+        12 => "DebugMonitor".to_string(),
+        14 => "PendSV".to_string(),
+        15 => "SysTick".to_string(),
+        16.. => format!("IRQ {}", number - 16),
+        _ => format!("Exception {number}"),
+    }
+}
+
+/// Turns DWT packets into log entries. A busy system takes thousands of
+/// interrupts a second, so only faults and the first entry into each
+/// exception are logged as they happen; the rest is counted and logged once
+/// per `HW_TRACE_SUMMARY_INTERVAL`, together with the hottest PC samples.
+struct HwTrace {
+    /// Exceptions entered at least once this session
+    seen: HashSet<u16>,
+    entered: BTreeMap<u16, u32>,
+    pcs: HashMap<u32, u32>,
+    asleep: u32,
+    last_summary: std::time::Instant,
+}
+
+impl HwTrace {
     fn new() -> Self {
         Self {
-            line_buf: String::new(),
-            current_terminal: 0,
+            seen: HashSet::new(),
+            entered: BTreeMap::new(),
+            pcs: HashMap::new(),
+            asleep: 0,
+            last_summary: std::time::Instant::now(),
+        }
+    }
+
+    fn entry(level: &str, message: String) -> LogEntry {
+        LogEntry {
+            level: level.to_string(),
+            tag: Some("trace".to_string()),
+            tag_color: Some(tag_color_index("trace")),
+            raw: message.clone(),
+            message,
+            ..Default::default()
+        }
+    }
+
+    /// An entry to log right away, if this one is a fault or a first.
+    fn exception(&mut self, number: u16, action: ExceptionAction) -> Option<LogEntry> {
+        if action != ExceptionAction::Entered {
+            return None;
+        }
+        *self.entered.entry(number).or_default() += 1;
+        let first = self.seen.insert(number);
+        let name = exception_name(number);
+        match number {
+            2..=7 => Some(Self::entry("error", format!("Exception: {name} entered"))),
+            _ if first => Some(Self::entry(
+                "info",
+                format!("Exception: {name} entered (first this session)"),
+            )),
+            _ => None,
+        }
+    }
+
+    fn pc_sample(&mut self, pc: Option<u32>) {
+        match pc {
+            Some(pc) => *self.pcs.entry(pc).or_default() += 1,
+            None => self.asleep += 1,
+        }
+    }
+
+    /// The counts since the last summary, once the interval has passed.
+    fn summary(&mut self) -> Option<LogEntry> {
+        if self.last_summary.elapsed() < HW_TRACE_SUMMARY_INTERVAL {
+            return None;
+        }
+        self.last_summary = std::time::Instant::now();
+        let mut parts = Vec::new();
+        if !self.entered.is_empty() {
+            let counts: Vec<String> = self
+                .entered
+                .iter()
+                .map(|(&n, count)| format!("{} ×{count}", exception_name(n)))
+                .collect();
+            parts.push(format!("Exceptions: {}", counts.join(", ")));
+        }
+        let samples = self.pcs.values().sum::<u32>() + self.asleep;
+        if let Some(samples) = std::num::NonZeroU32::new(samples) {
+            let mut pcs: Vec<(u32, u32)> = self.pcs.drain().collect();
+            pcs.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            let mut top: Vec<String> = pcs
+                .iter()
+                .take(HW_TRACE_TOP_PCS)
+                .map(|(pc, n)| format!("0x{pc:08X} {}%", n * 100 / samples))
+                .collect();
+            if self.asleep > 0 {
+                top.push(format!("asleep {}%", self.asleep * 100 / samples));
+            }
+            parts.push(format!("PC samples ({samples}): {}", top.join(", ")));
+        }
+        self.entered.clear();
+        self.asleep = 0;
+        (!parts.is_empty()).then(|| Self::entry("debug", parts.join(" · ")))
+    }
+}
+
+/// Turn on the DWT packets `HwTrace` decodes. ITM already forwards DWT
+/// packets once `setup_tracing` has run.
+fn enable_hw_trace(
+    core: &mut probe_rs::Core<'_>,
+    exceptions: bool,
+    pc_sampling: bool,
+) -> Result<(), AppError> {
+    let err = |e: probe_rs::Error| AppError::Other(format!("DWT setup failed: {e}"));
+    let mut ctrl = core.read_word_32(DWT_CTRL).map_err(err)?;
+    if exceptions {
+        ctrl |= DWT_CTRL_EXCTRCENA;
+    }
+    if pc_sampling {
+        if ctrl & DWT_CTRL_NOCYCCNT != 0 {
+            return Err(AppError::InvalidInput(
+                "This core has no cycle counter, which PC sampling needs".to_string(),
+            ));
+        }
+        // POSTINIT (bits 8:5) reloads from POSTPRESET; set both so the first
+        // sample doesn't wait for a stale count
+        ctrl &= !(0xF << DWT_CTRL_POSTPRESET_SHIFT | 0xF << 5);
+        ctrl |= DWT_PC_SAMPLE_POSTCNT << DWT_CTRL_POSTPRESET_SHIFT
+            | DWT_PC_SAMPLE_POSTCNT << 5
+            | DWT_CTRL_CYCTAP
+            | DWT_CTRL_CYCCNTENA
+            | DWT_CTRL_PCSAMPLENA;
+    }
+    core.write_word_32(DWT_CTRL, ctrl).map_err(err)
+}
+
+#[tauri::command]
+async fn start_swo(
+    app: AppHandle,
+    chip: String,
+    core_freq: u32,
+    baud: u32,
+    probe_index: Option<usize>,
+    exception_trace: Option<bool>,
+    pc_sampling: Option<bool>,
+) -> Result<String, AppError> {
+    if core_freq == 0 || baud == 0 {
+        return Err(AppError::InvalidInput(
+            "Core frequency and SWO baud rate must be non-zero".to_string(),
+        ));
+    }
+    let state = app.state::<AppState>();
+    state.stop_flag.store(false, Ordering::Relaxed);
+    let stop_flag = state.stop_flag.clone();
+    let probe_idx = probe_index.unwrap_or(0);
+    let probe_info = select_probe(probe_idx)?;
+
+    PERF.reset();
+
+    let msg = format!("SWO connecting ({chip}, {core_freq} Hz, {baud} baud, probe {probe_idx})...");
+    let hw_trace = (
+        exception_trace.unwrap_or(false),
+        pc_sampling.unwrap_or(false),
+    );
+
+    std::thread::spawn(move || {
+        if let Err(e) = run_swo(
+            &app,
+            &probe_info,
+            &chip,
+            core_freq,
+            baud,
+            hw_trace,
+            &stop_flag,
+        ) {
+            emit_rtt_error(&app, &e);
+        }
+        let _ = app.emit("rtt-disconnected", ());
+    });
+
+    Ok(msg)
+}
+
+/// Attach, route ITM out through SWO and feed stimulus data through the RTT
+/// parsers — one per port, with the port number as the channel. `hw_trace`
+/// turns on (exception trace, PC sampling), logged through `HwTrace`.
+fn run_swo(
+    app: &AppHandle,
+    probe_info: &probe_rs::probe::DebugProbeInfo,
+    chip: &str,
+    core_freq: u32,
+    baud: u32,
+    hw_trace: (bool, bool),
+    stop_flag: &Arc<AtomicBool>,
+) -> Result<(), AppError> {
+    use probe_rs::architecture::arm::{component::TraceSink, SwoConfig};
+
+    let probe = probe_info.open().map_err(|e| {
+        probe_open_error(&e).unwrap_or_else(|| AppError::Other(format!("Probe open failed: {e}")))
+    })?;
+    let target = probe_rs::config::get_target_by_name(chip)
+        .map_err(|e| AppError::UnknownChip(format!("Unknown chip '{chip}': {e}")))?;
+    let mut session = probe
+        .attach(target, probe_rs::Permissions::default())
+        .map_err(|e| {
+            protection_error(&e).unwrap_or_else(|| AppError::Other(format!("Attach failed: {e}")))
+        })?;
+
+    let config = SwoConfig::new(core_freq).set_baud(baud);
+    session
+        .setup_tracing(0, TraceSink::Swo(config))
+        .map_err(|e| AppError::Other(format!("SWO setup failed: {e}")))?;
+    let (exceptions, pc_sampling) = hw_trace;
+    if exceptions || pc_sampling {
+        let mut core = session
+            .core(0)
+            .map_err(|e| AppError::Other(format!("Cannot access core 0: {e}")))?;
+        enable_hw_trace(&mut core, exceptions, pc_sampling)?;
+    }
+
+    emit_rtt_status(app, "info", "SWO capture started.");
+    let _ = app.emit("rtt-connected", ());
+
+    let mut decoder = ItmDecoder::default();
+    let mut parsers: HashMap<u8, RttParser> = HashMap::new();
+    let mut trace = HwTrace::new();
+    // Only for its clock: trace entries are stamped like the lines around them
+    let mut trace_clock = RttParser::new(0, ParseOptions::default());
+    let mut stats = RttStats::default();
+    let mut consecutive_errors = 0u32;
+    let mut last_stats = std::time::Instant::now();
+
+    loop {
+        if stop_flag.load(Ordering::Relaxed) {
+            for parser in parsers.values_mut() {
+                let _ = parser.flush(&mut stats, LogSink::App(app));
+            }
+            emit_rtt_status(app, "info", "Disconnected by user.");
+            return Ok(());
+        }
+
+        let mode = *app.state::<AppState>().read_mode.lock().unwrap();
+        let read = match mode {
+            ReadMode::Poll => session.read_trace_data(),
+            ReadMode::Block => session
+                .get_arm_interface()
+                .and_then(|interface| interface.read_swo_timeout(BLOCK_TIMEOUT)),
+        };
+        match read {
+            Ok(data) if !data.is_empty() => {
+                consecutive_errors = 0;
+                stats.bytes += data.len() as u64;
+                PERF.bytes_read
+                    .fetch_add(data.len() as u64, Ordering::Relaxed);
+                for packet in decoder.feed(&data) {
+                    let sent = match packet {
+                        ItmPacket::Stimulus(port, bytes) => parsers
+                            .entry(port)
+                            .or_insert_with(|| {
+                                RttParser::new(port as usize, ParseOptions::default())
+                            })
+                            .process_bytes(&bytes, bytes.len(), &mut stats, LogSink::App(app)),
+                        ItmPacket::Exception(number, action) => {
+                            match trace.exception(number, action) {
+                                Some(entry) => {
+                                    trace_clock.emit_stamped(entry, &mut stats, LogSink::App(app))
+                                }
+                                None => Ok(()),
+                            }
+                        }
+                        ItmPacket::PcSample(pc) => {
+                            trace.pc_sample(pc);
+                            Ok(())
+                        }
+                    };
+                    if sent.is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            Ok(_) if mode == ReadMode::Poll => std::thread::sleep(POLL_INTERVAL),
+            Ok(_) => {}
+            Err(e) => {
+                consecutive_errors += 1;
+                let threshold = app
+                    .state::<AppState>()
+                    .error_threshold
+                    .load(Ordering::Relaxed);
+                if consecutive_errors >= threshold {
+                    return Err(AppError::Other(format!("SWO read failed: {e}")));
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+        }
+
+        if let Some(entry) = trace.summary() {
+            if trace_clock
+                .emit_stamped(entry, &mut stats, LogSink::App(app))
+                .is_err()
+            {
+                return Ok(());
+            }
+        }
+
+        if last_stats.elapsed() >= std::time::Duration::from_secs(1) {
+            stats.tick(
+                last_stats.elapsed(),
+                *app.state::<AppState>().emit_batching.lock().unwrap(),
+            );
+            let _ = app.emit("rtt-stats", &stats);
+            last_stats = std::time::Instant::now();
+        }
+    }
+}
+
+#[tauri::command]
+async fn extract_rtt_address_from_elf(
+    app: AppHandle,
+    elf_path: String,
+) -> Result<ElfInfo, AppError> {
+    // Picking another file supersedes whatever parse is still running
+    let generation = app
+        .state::<AppState>()
+        .elf_parse_gen
+        .fetch_add(1, Ordering::Relaxed)
+        + 1;
+    let ctl = ElfParseCtl {
+        app: Some(app.clone()),
+        generation,
+    };
+    let path = elf_path.clone();
+    let info = tauri::async_runtime::spawn_blocking(move || parse_elf_info(&path, &ctl))
+        .await
+        .map_err(|e| AppError::Other(format!("ELF parse task failed: {e}")))??;
+    // The ELF is loaded either way; only the recent list is out of date
+    if let Err(e) = remember_elf(&elf_path) {
+        emit_rtt_status(&app, "warn", e.message());
+    }
+    Ok(info)
+}
+
+#[tauri::command]
+async fn cancel_elf_parse(app: AppHandle) -> Result<(), AppError> {
+    app.state::<AppState>()
+        .elf_parse_gen
+        .fetch_add(1, Ordering::Relaxed);
+    Ok(())
+}
+
+fn parse_elf_info(elf_path: &str, ctl: &ElfParseCtl) -> Result<ElfInfo, AppError> {
+    let data = std::fs::read(elf_path)
+        .map_err(|e| AppError::Io(format!("Failed to read ELF file: {e}")))?;
+    ctl.check()?;
+    let report = data.len() > ELF_PROGRESS_THRESHOLD;
+    if report {
+        ctl.progress(elf_path, "parse", 0);
+    }
+
+    let elf = goblin::elf::Elf::parse(&data)
+        .map_err(|e| AppError::Elf(format!("Failed to parse ELF: {e}")))?;
+    ctl.check()?;
+
+    // Find _SEGGER_RTT addresses; images linked together can have one each
+    let mut rtt_addresses: Vec<u64> = Vec::new();
+    let mut symbols: Vec<(String, u64)> = Vec::new();
+    let total = elf.syms.len().max(1);
+
+    for (i, sym) in elf.syms.iter().enumerate() {
+        if i % 4096 == 0 && i > 0 {
+            ctl.check()?;
+            if report {
+                ctl.progress(elf_path, "symbols", (i * 100 / total) as u8);
+            }
+        }
+        if let Some(name) = elf.strtab.get_at(sym.st_name) {
+            if name == "_SEGGER_RTT" && !rtt_addresses.contains(&sym.st_value) {
+                rtt_addresses.push(sym.st_value);
+            }
+            symbols.push((name.to_string(), sym.st_value));
+        }
+    }
+
+    let mut rtt_addresses = rtt_addresses
+        .into_iter()
+        .map(|addr| format!("0x{addr:08X}"));
+    let rtt_address = rtt_addresses
+        .next()
+        .ok_or_else(|| AppError::RttNotFound("_SEGGER_RTT symbol not found in ELF".to_string()))?;
+
+    // Detect chip from ELF metadata
+    let chip_hint = detect_chip(&elf, &symbols).map(|s| s.replace("-", "_"));
+
+    Ok(ElfInfo {
+        rtt_address,
+        extra_rtt_addresses: rtt_addresses.collect(),
+        chip_hint,
+        ram_region: None,
+    })
+}
+
+/// Address and size of a global variable from the ELF symbol table.
+fn resolve_elf_symbol(elf_path: &str, name: &str) -> Result<(u64, u64), AppError> {
+    let data = std::fs::read(elf_path)
+        .map_err(|e| AppError::Io(format!("Failed to read ELF file: {e}")))?;
+    let elf = goblin::elf::Elf::parse(&data)
+        .map_err(|e| AppError::Elf(format!("Failed to parse ELF: {e}")))?;
+    elf.syms
+        .iter()
+        .find(|sym| {
+            sym.st_type() == goblin::elf::sym::STT_OBJECT
+                && elf.strtab.get_at(sym.st_name) == Some(name)
+        })
+        .map(|sym| (sym.st_value, sym.st_size))
+        .ok_or_else(|| AppError::Elf(format!("No variable named '{name}' in the ELF")))
+}
+
+/// "function+0xNN" for each address, or None outside every function symbol.
+/// The Thumb bit is ignored on both sides.
+fn elf_functions_at(elf_path: &str, addrs: &[u64]) -> Result<Vec<Option<String>>, AppError> {
+    let data = std::fs::read(elf_path)
+        .map_err(|e| AppError::Io(format!("Failed to read ELF file: {e}")))?;
+    let elf = goblin::elf::Elf::parse(&data)
+        .map_err(|e| AppError::Elf(format!("Failed to parse ELF: {e}")))?;
+    Ok(addrs
+        .iter()
+        .map(|&addr| {
+            let addr = addr & !1;
+            elf.syms
+                .iter()
+                .filter(|sym| sym.st_type() == goblin::elf::sym::STT_FUNC && sym.st_size > 0)
+                .find(|sym| {
+                    let start = sym.st_value & !1;
+                    (start..start + sym.st_size).contains(&addr)
+                })
+                .and_then(|sym| {
+                    let name = elf.strtab.get_at(sym.st_name)?;
+                    Some(match addr - (sym.st_value & !1) {
+                        0 => name.to_string(),
+                        off => format!("{name}+{off:#x}"),
+                    })
+                })
+        })
+        .collect())
+}
+
+#[tauri::command]
+async fn extract_rtt_address_from_map(map_path: String) -> Result<ElfInfo, AppError> {
+    let text = std::fs::read_to_string(&map_path)
+        .map_err(|e| AppError::Io(format!("Failed to read map file: {e}")))?;
+
+    let rtt_addr = find_rtt_in_map(&text).ok_or_else(|| {
+        AppError::RttNotFound("_SEGGER_RTT symbol not found in map file".to_string())
+    })?;
+
+    // Pick the memory region that actually contains the control block
+    let ram_region = map_memory_regions(&text)
+        .into_iter()
+        .find(|(start, size)| (*start..start.saturating_add(*size)).contains(&rtt_addr))
+        .map(|(start, size)| RamRegion {
+            start: format!("0x{start:08X}"),
+            size: format!("0x{size:08X}"),
+        });
+
+    Ok(ElfInfo {
+        rtt_address: format!("0x{rtt_addr:08X}"),
+        extra_rtt_addresses: Vec::new(),
+        chip_hint: None,
+        ram_region,
+    })
+}
+
+fn parse_map_hex(s: &str) -> Option<u64> {
+    // IAR groups digits with apostrophes: 0x2000'0abc
+    u64::from_str_radix(&s.replace('\'', ""), 16).ok()
+}
+
+/// Locate `_SEGGER_RTT` in a linker map. Handles:
+///   GNU ld:  `                0x0000000020000abc                _SEGGER_RTT`
+///   armlink: `    _SEGGER_RTT     0x20000abc   Data   168  segger_rtt.o(.bss)`
+///   IAR:     `_SEGGER_RTT             0x2000'0abc   0xa8  Data  Gb  SEGGER_RTT.o [1]`
+fn find_rtt_in_map(text: &str) -> Option<u64> {
+    let gnu_re = Regex::new(r"(?m)^\s+0x([0-9a-fA-F]+)\s+_SEGGER_RTT\s*$").unwrap();
+    let armlink_iar_re = Regex::new(r"(?m)^\s*_SEGGER_RTT\s+0x([0-9a-fA-F']+)\s").unwrap();
+
+    gnu_re
+        .captures(text)
+        .or_else(|| armlink_iar_re.captures(text))
+        .and_then(|caps| parse_map_hex(&caps[1]))
+}
+
+/// Regions from a GNU ld "Memory Configuration" table:
+///   `RAM              0x0000000020000000 0x0000000000040000 xw`
+fn map_memory_regions(text: &str) -> Vec<(u64, u64)> {
+    let region_re =
+        Regex::new(r"(?m)^(\w+)\s+0x([0-9a-fA-F]+)\s+0x([0-9a-fA-F]+)(?:\s+\w+)?\s*$").unwrap();
+    region_re
+        .captures_iter(text)
+        .filter_map(|caps| Some((parse_map_hex(&caps[2])?, parse_map_hex(&caps[3])?)))
+        .filter(|(_, size)| *size > 0)
+        .collect()
+}
+
+fn detect_chip(elf: &goblin::elf::Elf, symbols: &[(String, u64)]) -> Option<String> {
+    // Must be ARM
+    if elf.header.e_machine != goblin::elf::header::EM_ARM {
+        return None;
+    }
+
+    // Check entry point and symbol addresses to identify chip family
+    let entry = elf.header.e_entry;
+
+    // Look for known Zephyr/Nordic config symbols
+    let has_symbol = |prefix: &str| symbols.iter().any(|(name, _)| name.contains(prefix));
+
+    // nRF5340 detection
+    if has_symbol("NRF5340") || has_symbol("nrf5340") {
+        // Check if app or net core based on RAM address
+        let ram_base = symbols
+            .iter()
+            .find(|(name, _)| name == "_SEGGER_RTT")
+            .map(|(_, addr)| *addr)
+            .unwrap_or(0);
+
+        if ram_base >= 0x2100_0000 {
+            return Some("nRF5340_xxAA".to_string()); // net core RAM starts at 0x21000000
+        }
+        return Some("nRF5340_xxAA".to_string());
+    }
+
+    // nRF52840
+    if has_symbol("NRF52840") || has_symbol("nrf52840") {
+        return Some("nRF52840_xxAA".to_string());
+    }
+
+    // nRF52833
+    if has_symbol("NRF52833") || has_symbol("nrf52833") {
+        return Some("nRF52833_xxAA".to_string());
+    }
+
+    // nRF52832
+    if has_symbol("NRF52832") || has_symbol("nrf52832") {
+        return Some("nRF52832_xxAA".to_string());
+    }
+
+    // nRF9160
+    if has_symbol("NRF9160") || has_symbol("nrf9160") {
+        return Some("nRF9160_xxAA".to_string());
+    }
+
+    // nRF91x1 (nRF9151/9161)
+    if has_symbol("NRF9151")
+        || has_symbol("nrf9151")
+        || has_symbol("NRF91X1")
+        || has_symbol("nrf91x1")
+    {
+        return Some("nRF9151_xxAA".to_string());
+    }
+
+    if has_symbol("NRF9161") || has_symbol("nrf9161") {
+        return Some("nRF9161_xxAA".to_string());
+    }
+
+    // STM32 detection by RAM/flash ranges
+    if has_symbol("STM32") || has_symbol("stm32") {
+        return Some("STM32 (check exact variant)".to_string());
+    }
+
+    // ESP32 — won't be ARM, but just in case
+    if has_symbol("ESP32") || has_symbol("esp32") {
+        return Some("ESP32".to_string());
+    }
+
+    // Fallback: try to guess from memory map
+    // nRF52 family has RAM at 0x20000000, flash at 0x00000000
+    // nRF53 app core: RAM 0x20000000, flash 0x00000000
+    // nRF53 net core: RAM 0x21000000, flash 0x01000000
+    // nRF91: RAM 0x20000000, flash 0x00000000
+    if entry < 0x0010_0000 {
+        // Looks like nRF5x or nRF91 flash region
+        let rtt_addr = symbols
+            .iter()
+            .find(|(name, _)| name == "_SEGGER_RTT")
+            .map(|(_, addr)| *addr)
+            .unwrap_or(0);
+
+        if rtt_addr >= 0x2100_0000 {
+            return Some("nRF5340_xxAA (net core?)".to_string());
+        }
+        if rtt_addr >= 0x2000_0000 {
+            return Some("Nordic (nRF52/53/91 — check variant)".to_string());
+        }
+    }
+
+    None
+}
+
+/// Most suggestions `suggest_chips_for_elf` returns
+const MAX_CHIP_SUGGESTIONS: usize = 8;
+
+/// A probe-rs target the ELF could be built for, with the evidence.
+#[derive(Debug, Clone, Serialize)]
+struct ChipSuggestion {
+    /// probe-rs target name, attachable as is
+    name: String,
+    /// Core whose memory map holds the image (1 = net core on the nRF5340)
+    core: usize,
+    /// 0.0–1.0, for ranking against the other suggestions
+    confidence: f32,
+    /// Why, e.g. "RAM at 0x21000000 fits its network core"
+    reason: String,
+}
+
+/// What the ELF says about its target, gathered once for every candidate.
+struct ElfEvidence {
+    /// Symbol names split at `_`, `.` etc. and lower-cased: "nrf5340", "soc", …
+    tokens: HashSet<String>,
+    /// Loaded address ranges (run and load addresses)
+    segments: Vec<std::ops::Range<u64>>,
+    /// Core types that can run the code, from `e_machine` and `.ARM.attributes`
+    core_types: Option<Vec<probe_rs::config::CoreType>>,
+    /// CPU name from `.ARM.attributes`, e.g. "cortex-m33"
+    cpu_name: Option<String>,
+}
+
+impl ElfEvidence {
+    fn gather(data: &[u8], elf: &goblin::elf::Elf) -> Self {
+        use probe_rs::config::CoreType;
+
+        let tokens = elf
+            .syms
+            .iter()
+            .filter_map(|sym| elf.strtab.get_at(sym.st_name))
+            .flat_map(|name| name.split(|c: char| !c.is_ascii_alphanumeric()))
+            .filter(|t| t.len() >= 4)
+            .map(str::to_ascii_lowercase)
+            .collect();
+
+        let mut segments = Vec::new();
+        for ph in elf
+            .program_headers
+            .iter()
+            .filter(|ph| ph.p_type == goblin::elf::program_header::PT_LOAD && ph.p_memsz > 0)
+        {
+            // A header that runs past the address space is garbage; skip it
+            if let Some(end) = ph.p_vaddr.checked_add(ph.p_memsz) {
+                segments.push(ph.p_vaddr..end);
+            }
+            // .data is stored in flash and copied to RAM at boot
+            if ph.p_filesz > 0 && ph.p_paddr != ph.p_vaddr {
+                if let Some(end) = ph.p_paddr.checked_add(ph.p_filesz) {
+                    segments.push(ph.p_paddr..end);
+                }
+            }
+        }
+
+        let cpu_name = elf
+            .section_headers
+            .iter()
+            .find(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(".ARM.attributes"))
+            .and_then(|sh| {
+                let end = sh.sh_offset.checked_add(sh.sh_size)?;
+                data.get(sh.sh_offset as usize..end as usize)
+            })
+            .and_then(|attrs| {
+                let text = String::from_utf8_lossy(attrs).to_ascii_lowercase();
+                let start = text.find("cortex-m")?;
+                let name: String = text[start..]
+                    .chars()
+                    .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '.')
+                    .collect();
+                Some(name)
+            });
+
+        let core_types = match elf.header.e_machine {
+            goblin::elf::header::EM_ARM => Some(match cpu_name.as_deref() {
+                Some("cortex-m0" | "cortex-m0plus" | "cortex-m0.small-multiply" | "cortex-m1") => {
+                    vec![CoreType::Armv6m]
+                }
+                Some("cortex-m3") => vec![CoreType::Armv7m],
+                Some("cortex-m4" | "cortex-m7") => vec![CoreType::Armv7em],
+                // M23, M33, M35P, M55, M85
+                Some(cpu) if cpu.len() > "cortex-m3".len() => vec![CoreType::Armv8m],
+                // Unknown or missing: any Cortex-M; newer cores run older code
+                _ => vec![
+                    CoreType::Armv6m,
+                    CoreType::Armv7m,
+                    CoreType::Armv7em,
+                    CoreType::Armv8m,
+                ],
+            }),
+            goblin::elf::header::EM_RISCV => Some(vec![CoreType::Riscv]),
+            _ => None,
+        };
+
+        Self {
+            tokens,
+            segments,
+            core_types,
+            cpu_name,
+        }
+    }
+
+    /// Index and name of the first core whose memory map holds every segment.
+    fn fitting_core<'c>(&self, chip: &'c probe_rs::config::Chip) -> Option<(usize, &'c str)> {
+        chip.cores.iter().enumerate().find_map(|(i, core)| {
+            let type_ok = self
+                .core_types
+                .as_ref()
+                .is_none_or(|types| types.contains(&core.core_type));
+            let fits = self.segments.iter().all(|seg| {
+                chip.memory_map.iter().any(|region| {
+                    let range = region.address_range();
+                    region.cores().contains(&core.name)
+                        && range.start <= seg.start
+                        && seg.end <= range.end
+                })
+            });
+            (type_ok && fits).then_some((i, core.name.as_str()))
+        })
+    }
+}
+
+/// First run of letters and digits, lower-cased: "nRF52840_xxAA" → "nrf52840".
+fn chip_key(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Ranked probe-rs targets the ELF fits, with the evidence for each: chip
+/// names among the symbols, load addresses against each target's memory map,
+/// and the CPU from the ARM build attributes. Only targets whose memory map
+/// holds the whole image are suggested.
+fn suggest_chips(elf_path: &str) -> Result<Vec<ChipSuggestion>, AppError> {
+    let data = std::fs::read(elf_path)
+        .map_err(|e| AppError::Io(format!("Failed to read ELF file: {e}")))?;
+    let elf = goblin::elf::Elf::parse(&data)
+        .map_err(|e| AppError::Elf(format!("Failed to parse ELF: {e}")))?;
+    let evidence = ElfEvidence::gather(&data, &elf);
+
+    let ram_start = evidence
+        .segments
+        .iter()
+        .map(|s| s.start)
+        .filter(|&a| a >= 0x2000_0000)
+        .min();
+    // (named in the symbols, total memory, suggestion)
+    let mut candidates = Vec::new();
+    for family in probe_rs::config::families() {
+        let family_key = chip_key(&family.name);
+        let family_named = evidence.tokens.contains(&family_key);
+        for chip in &family.variants {
+            let Some((core, core_name)) = evidence.fitting_core(chip) else {
+                continue;
+            };
+            let key = chip_key(&chip.name);
+            let mut confidence = 0.0f32;
+            let mut reasons = Vec::new();
+            let chip_named = evidence.tokens.contains(&key);
+            if chip_named {
+                confidence += 0.5;
+                reasons.push(format!("symbols name the {key}"));
+            } else if family_named {
+                confidence += 0.25;
+                reasons.push(format!("symbols name the {} family", family.name));
+            }
+            if !evidence.segments.is_empty() {
+                confidence += 0.25;
+                reasons.push(match (ram_start, chip.cores.len() > 1) {
+                    (Some(ram), true) => format!("RAM at {ram:#010X} fits its {core_name} core"),
+                    _ => "the image fits its memory map".to_string(),
+                });
+            }
+            if let Some(cpu) = &evidence.cpu_name {
+                confidence += 0.15;
+                reasons.push(format!("built for {cpu}"));
+            }
+            let capacity: u64 = chip
+                .memory_map
+                .iter()
+                .map(|region| {
+                    let range = region.address_range();
+                    range.end - range.start
+                })
+                .sum();
+            candidates.push((
+                chip_named || family_named,
+                capacity,
+                ChipSuggestion {
+                    name: chip.name.clone(),
+                    core,
+                    confidence: confidence.min(1.0),
+                    reason: reasons.join("; "),
+                },
+            ));
+        }
+    }
+
+    // With no name in the symbols, the layout alone matches a whole swathe
+    // of parts; say so, and list the smallest ones the image fits first
+    if candidates.iter().any(|(named, ..)| *named) {
+        candidates.retain(|(named, ..)| *named);
+    } else {
+        for (.., s) in &mut candidates {
+            s.reason
+                .push_str("; only the memory layout matches, which many parts share");
+        }
+    }
+    candidates.sort_by(|(_, cap_a, a), (_, cap_b, b)| {
+        b.confidence.total_cmp(&a.confidence).then(cap_a.cmp(cap_b))
+    });
+    Ok(candidates
+        .into_iter()
+        .take(MAX_CHIP_SUGGESTIONS)
+        .map(|(.., s)| s)
+        .collect())
+}
+
+/// Ranked chip choices for an ELF whose target `detect_chip` can't pin down.
+#[tauri::command]
+async fn suggest_chips_for_elf(elf_path: String) -> Result<Vec<ChipSuggestion>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || suggest_chips(&elf_path))
+        .await
+        .map_err(|e| AppError::Other(format!("Chip suggestion task failed: {e}")))?
+}
+
+// ── Chip catalogue ──
+
+#[derive(Debug, Clone, Serialize)]
+struct ChipVariant {
+    name: String,
+    /// One per core, e.g. ["armv8m", "armv8m"] for the nRF5340
+    core_types: Vec<probe_rs::config::CoreType>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ChipFamily {
+    /// JEP106 manufacturer name, or "Other" when the target file has none
+    vendor: String,
+    family: String,
+    variants: Vec<ChipVariant>,
+}
+
+/// `list_chip_families` result. Built-in targets never change, but
+/// `register_target_yaml` adds families, so registering one clears this.
+static CHIP_FAMILIES: Mutex<Option<Arc<Vec<ChipFamily>>>> = Mutex::new(None);
+
+fn chip_families() -> Arc<Vec<ChipFamily>> {
+    CHIP_FAMILIES
+        .lock()
+        .unwrap()
+        .get_or_insert_with(|| {
+            let mut families: Vec<ChipFamily> = probe_rs::config::families()
+                .into_iter()
+                .map(|family| {
+                    let mut variants: Vec<ChipVariant> = family
+                        .variants
+                        .iter()
+                        .map(|chip| ChipVariant {
+                            name: chip.name.clone(),
+                            core_types: chip.cores.iter().map(|c| c.core_type).collect(),
+                        })
+                        .collect();
+                    variants.sort_by(|a, b| a.name.cmp(&b.name));
+                    ChipFamily {
+                        vendor: family
+                            .manufacturer
+                            .and_then(|m| m.get())
+                            .unwrap_or("Other")
+                            .to_string(),
+                        family: family.name,
+                        variants,
+                    }
+                })
+                .collect();
+            families.sort_by(|a, b| (&a.vendor, &a.family).cmp(&(&b.vendor, &b.family)));
+            Arc::new(families)
+        })
+        .clone()
+}
+
+/// Every known chip as vendor → family → variants, sorted, for a cascading picker.
+#[tauri::command]
+async fn list_chip_families() -> Result<Arc<Vec<ChipFamily>>, AppError> {
+    tauri::async_runtime::spawn_blocking(chip_families)
+        .await
+        .map_err(|e| AppError::Other(format!("Chip list task failed: {e}")))
+}
+
+// ── FIFO source ──
+
+/// Read a named pipe as a log source, parsed like RTT channel 0. Another
+/// tool writes into the FIFO; when it closes, the pipe is reopened for the
+/// next writer. Linux and macOS only.
+#[tauri::command]
+async fn start_fifo(app: AppHandle, path: String) -> Result<String, AppError> {
+    #[cfg(not(unix))]
+    {
+        let _ = (app, path);
+        Err(AppError::InvalidInput(
+            "Named pipes are only supported on Linux and macOS".to_string(),
+        ))
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        let meta = std::fs::metadata(&path)?;
+        if !meta.file_type().is_fifo() {
+            return Err(AppError::InvalidInput(format!(
+                "{path} is not a named pipe (create one with mkfifo)"
+            )));
+        }
+        // Surfaces permission errors here rather than in the thread
+        let file = open_fifo(&path)?;
+
+        let state = app.state::<AppState>();
+        state.stop_flag.store(false, Ordering::Relaxed);
+        let stop_flag = state.stop_flag.clone();
+        PERF.reset();
+
+        let msg = format!("Reading from {path}...");
+        std::thread::spawn(move || {
+            if let Err(e) = run_fifo(&app, &path, file, &stop_flag) {
+                emit_rtt_error(&app, &e);
+            }
+            let _ = app.emit("rtt-disconnected", ());
+        });
+        Ok(msg)
+    }
+}
+
+/// Non-blocking: a plain open waits for a writer, which would hang the
+/// command (and a stop request) until some tool opens the other end.
+#[cfg(unix)]
+fn open_fifo(path: &str) -> Result<std::fs::File, AppError> {
+    use std::os::unix::fs::OpenOptionsExt;
+    std::fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
+        .map_err(|e| AppError::Io(format!("Cannot open {path}: {e}")))
+}
+
+/// Block until `file` has data (or its writer leaves) or `timeout` passes.
+#[cfg(unix)]
+fn wait_readable(file: &std::fs::File, timeout: std::time::Duration) {
+    use std::os::unix::io::AsRawFd;
+
+    let mut fds = libc::pollfd {
+        fd: file.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    // Errors and EINTR just end the wait early; the next read reports real trouble
+    unsafe { libc::poll(&mut fds, 1, timeout.as_millis() as libc::c_int) };
+}
+
+#[cfg(unix)]
+fn run_fifo(
+    app: &AppHandle,
+    path: &str,
+    mut file: std::fs::File,
+    stop_flag: &Arc<AtomicBool>,
+) -> Result<(), AppError> {
+    use std::io::Read;
+
+    emit_rtt_status(app, "info", &format!("Waiting for a writer on {path}..."));
+    let _ = app.emit("rtt-connected", ());
+
+    let mut parser = RttParser::new(0, ParseOptions::default());
+    let mut stats = RttStats::default();
+    let mut buf = [0u8; 4096];
+    let mut writer = false;
+    let mut last_stats = std::time::Instant::now();
+
+    loop {
+        if stop_flag.load(Ordering::Relaxed) {
+            let _ = parser.flush(&mut stats, LogSink::App(app));
+            emit_rtt_status(app, "info", "Disconnected by user.");
+            return Ok(());
+        }
+
+        match file.read(&mut buf) {
+            // EOF: no writer has the pipe open, yet or any more
+            Ok(0) => {
+                if writer {
+                    writer = false;
+                    if parser.flush(&mut stats, LogSink::App(app)).is_err() {
+                        return Ok(());
+                    }
+                    emit_rtt_status(
+                        app,
+                        "info",
+                        &format!("Writer closed {path}, waiting for the next one..."),
+                    );
+                    file = open_fifo(path)?;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+            Ok(count) => {
+                if !writer {
+                    writer = true;
+                    emit_rtt_status(app, "info", &format!("Writer connected to {path}."));
+                }
+                stats.bytes += count as u64;
+                PERF.bytes_read.fetch_add(count as u64, Ordering::Relaxed);
+                if parser
+                    .process_bytes(&buf, count, &mut stats, LogSink::App(app))
+                    .is_err()
+                {
+                    return Ok(());
+                }
+            }
+            // Writer attached but idle
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                match *app.state::<AppState>().read_mode.lock().unwrap() {
+                    ReadMode::Poll => std::thread::sleep(POLL_INTERVAL),
+                    ReadMode::Block => wait_readable(&file, BLOCK_TIMEOUT),
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(AppError::Io(format!("Reading {path} failed: {e}"))),
+        }
+
+        if last_stats.elapsed() >= std::time::Duration::from_secs(1) {
+            stats.tick(
+                last_stats.elapsed(),
+                *app.state::<AppState>().emit_batching.lock().unwrap(),
+            );
+            let _ = app.emit("rtt-stats", &stats);
+            last_stats = std::time::Instant::now();
+        }
+    }
+}
+
+// ── ELF watcher ──
+
+#[derive(Debug, Clone, Serialize)]
+struct ElfUpdate {
+    elf_path: String,
+    #[serde(flatten)]
+    info: ElfInfo,
+}
+
+/// Start (or stop) watching the profile's ELF, replacing any previous watch.
+fn update_elf_watcher(app: &AppHandle, profile: Option<&Profile>) {
+    let state = app.state::<AppState>();
+    let mut slot = state.elf_watcher.lock().unwrap();
+    *slot = None;
+
+    let Some(elf_path) = profile
+        .filter(|p| p.watch_elf)
+        .and_then(|p| p.elf_path.as_deref())
+    else {
+        return;
+    };
+    match watch_elf(app, elf_path) {
+        Ok(watcher) => *slot = Some(watcher),
+        Err(e) => emit_rtt_status(app, "warn", e.message()),
+    }
+}
+
+fn watch_elf(app: &AppHandle, elf_path: &str) -> Result<notify::RecommendedWatcher, AppError> {
+    use notify::Watcher;
+
+    let path = std::path::PathBuf::from(elf_path);
+    // Linkers usually replace the file rather than rewrite it, which kills a
+    // watch on the file itself — watch the directory and filter by name instead.
+    let dir = path
+        .parent()
+        .filter(|d| !d.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."))
+        .to_path_buf();
+    let file_name = path.file_name().map(|n| n.to_os_string());
+
+    let (tx, rx) = std::sync::mpsc::channel::<()>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let Ok(event) = res else { return };
+        if !(event.kind.is_modify() || event.kind.is_create()) {
+            return;
+        }
+        if event
+            .paths
+            .iter()
+            .any(|p| p.file_name().map(|n| n.to_os_string()) == file_name)
+        {
+            let _ = tx.send(());
+        }
+    })
+    .map_err(|e| AppError::Io(format!("Failed to watch ELF: {e}")))?;
+    watcher
+        .watch(&dir, notify::RecursiveMode::NonRecursive)
+        .map_err(|e| AppError::Io(format!("Failed to watch {}: {e}", dir.display())))?;
+
+    // The sender lives in the watcher callback, so this thread ends once the watcher is dropped
+    let app = app.clone();
+    let elf_path = elf_path.to_string();
+    std::thread::spawn(move || {
+        while rx.recv().is_ok() {
+            // A link writes the file in several bursts; wait for it to settle
+            std::thread::sleep(std::time::Duration::from_millis(500));
+            while rx.try_recv().is_ok() {}
+            on_elf_changed(&app, &elf_path);
+        }
+    });
+
+    Ok(watcher)
+}
+
+fn on_elf_changed(app: &AppHandle, elf_path: &str) {
+    let info = match parse_elf_info(elf_path, &ElfParseCtl::default()) {
+        Ok(info) => info,
+        Err(e) => {
+            emit_rtt_status(
+                app,
+                "warn",
+                &format!("ELF changed but re-extract failed: {e}"),
+            );
+            return;
+        }
+    };
+
+    let state = app.state::<AppState>();
+    if state.rtt_running.load(Ordering::Relaxed) {
+        let old = state
+            .active_profile
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|p| p.rtt_address.as_deref())
+            .and_then(parse_hex_u64);
+        let new = parse_hex_u64(&info.rtt_address);
+        if old.is_some() && old != new {
+            emit_rtt_status(
+                app,
+                "warn",
+                &format!(
+                    "_SEGGER_RTT moved to {} after rebuild — update the profile and reconnect",
+                    info.rtt_address
+                ),
+            );
+        }
+    }
+
+    let _ = app.emit(
+        "elf-updated",
+        &ElfUpdate {
+            elf_path: elf_path.to_string(),
+            info,
+        },
+    );
+}
+
+// ── Remote viewing (WebSocket) ──
+
+/// Entries buffered per client before a slow one starts skipping
+const WS_CLIENT_BUFFER: usize = 4096;
+
+/// Fan-out to WebSocket clients, and the accept loop's stop signal
+struct WsServer {
+    tx: tokio::sync::broadcast::Sender<Arc<str>>,
+    shutdown: tokio::sync::oneshot::Sender<()>,
+}
+
+/// Stream every `rtt-log` entry as a JSON text message to any WebSocket
+/// client on `port`. A client that falls behind skips entries (and is told
+/// how many) rather than slowing capture down.
+///
+/// Listens on localhost unless `bind` names another interface. The stream is
+/// the whole device log, so anything reachable from other machines needs a
+/// `token`, which clients pass as `?token=` in the URL.
+#[tauri::command]
+async fn start_server(
+    app: AppHandle,
+    port: u16,
+    bind: Option<String>,
+    token: Option<String>,
+) -> Result<String, AppError> {
+    let addr: std::net::IpAddr = match bind.as_deref().map(str::trim) {
+        None | Some("") => std::net::Ipv4Addr::LOCALHOST.into(),
+        Some(ip) => ip
+            .parse()
+            .map_err(|_| AppError::InvalidInput(format!("Not an IP address: {ip}")))?,
+    };
+    let token: Option<Arc<str>> = token.filter(|t| !t.is_empty()).map(Into::into);
+    if !addr.is_loopback() && token.is_none() {
+        return Err(AppError::InvalidInput(format!(
+            "Listening on {addr} exposes the log to the network; set an access token"
+        )));
+    }
+
+    let state = app.state::<AppState>();
+    let mut server = state.ws_server.lock().unwrap();
+    if server.is_some() {
+        return Err(AppError::InvalidInput(
+            "WebSocket server already running".to_string(),
+        ));
+    }
+    // Bound synchronously so the check above and the store below share the lock
+    let listener = std::net::TcpListener::bind((addr, port))
+        .and_then(|l| l.set_nonblocking(true).map(|_| l))
+        .and_then(tokio::net::TcpListener::from_std)
+        .map_err(|e| AppError::Io(format!("Cannot listen on {addr} port {port}: {e}")))?;
+    let (tx, _) = tokio::sync::broadcast::channel::<Arc<str>>(WS_CLIENT_BUFFER);
+    let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    *server = Some(WsServer {
+        tx: tx.clone(),
+        shutdown: shutdown_tx,
+    });
+    drop(server);
+
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = &mut shutdown_rx => break,
+                accepted = listener.accept() => {
+                    let Ok((stream, peer)) = accepted else { continue };
+                    let rx = tx.subscribe();
+                    let app = app.clone();
+                    let token = token.clone();
+                    tokio::spawn(async move {
+                        if serve_ws_client(stream, rx, token.as_deref(), || {
+                            emit_rtt_status(&app, "info", &format!("Remote viewer connected: {peer}"));
+                        })
+                        .await
+                        {
+                            emit_rtt_status(&app, "info", &format!("Remote viewer left: {peer}"));
+                        }
+                    });
+                }
+            }
+        }
+    });
+
+    Ok(format!("WebSocket server listening on {addr} port {port}"))
+}
+
+/// Whether the handshake's URL carries `?token=<expected>`.
+fn ws_token_matches(uri: &tokio_tungstenite::tungstenite::http::Uri, expected: &str) -> bool {
+    uri.query()
+        .unwrap_or("")
+        .split('&')
+        .any(|pair| pair.strip_prefix("token=") == Some(expected))
+}
+
+/// Serve one client until it or the server goes away. False if the
+/// handshake failed or the token didn't match; `on_open` runs otherwise.
+async fn serve_ws_client(
+    stream: tokio::net::TcpStream,
+    mut rx: tokio::sync::broadcast::Receiver<Arc<str>>,
+    token: Option<&str>,
+    on_open: impl FnOnce(),
+) -> bool {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::sync::broadcast::error::RecvError;
+    use tokio_tungstenite::tungstenite::handshake::server::{
+        Callback, ErrorResponse, Request, Response,
+    };
+    use tokio_tungstenite::tungstenite::http::StatusCode;
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// Rejects a handshake without the server's token, if it has one
+    struct TokenCheck<'a>(Option<&'a str>);
+
+    impl Callback for TokenCheck<'_> {
+        fn on_request(
+            self,
+            request: &Request,
+            response: Response,
+        ) -> Result<Response, ErrorResponse> {
+            match self.0 {
+                Some(token) if !ws_token_matches(request.uri(), token) => {
+                    let mut err = ErrorResponse::new(Some("Invalid or missing token".to_string()));
+                    *err.status_mut() = StatusCode::UNAUTHORIZED;
+                    Err(err)
+                }
+                _ => Ok(response),
+            }
+        }
+    }
+
+    let Ok(ws) = tokio_tungstenite::accept_hdr_async(stream, TokenCheck(token)).await else {
+        return false;
+    };
+    on_open();
+    let (mut sink, mut incoming) = ws.split();
+    loop {
+        tokio::select! {
+            msg = rx.recv() => {
+                let text = match msg {
+                    Ok(json) => json.to_string(),
+                    Err(RecvError::Lagged(skipped)) => format!("{{\"skipped\":{skipped}}}"),
+                    // Server stopped
+                    Err(RecvError::Closed) => break,
+                };
+                if sink.send(Message::text(text)).await.is_err() {
+                    break;
+                }
+            }
+            msg = incoming.next() => match msg {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Read-only stream — anything the client sends is ignored
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    let _ = sink.close().await;
+    true
+}
+
+#[tauri::command]
+async fn stop_server(app: AppHandle) -> Result<String, AppError> {
+    let state = app.state::<AppState>();
+    // Dropping the sender closes every client's receiver
+    let server = state
+        .ws_server
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| AppError::NotConnected("WebSocket server not running".to_string()))?;
+    let _ = server.shutdown.send(());
+    Ok("WebSocket server stopped".to_string())
+}
+
+// ── Syslog forwarding ──
+
+/// Messages queued for the syslog server; past this, new entries are dropped
+/// rather than holding up capture
+const SYSLOG_BUFFER: usize = 4096;
+/// RFC 5426 only promises 2048-byte datagrams get through
+const SYSLOG_UDP_MAX: usize = 2048;
+/// Ends a message cut to fit `SYSLOG_UDP_MAX`
+const SYSLOG_TRUNCATED: &str = " [truncated]";
+const SYSLOG_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+/// Wait between TCP reconnects while the server is down
+const SYSLOG_RETRY: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Forwarding set up by `log_to_syslog`. The network side runs in its own
+/// task; `emit_log` only formats and queues.
+struct SyslogSink {
+    tx: tokio::sync::mpsc::Sender<String>,
+    facility: u8,
+    /// HOSTNAME field: the profile name, which says which device sent the line
+    hostname: String,
+    /// Entries that didn't fit in the queue
+    dropped: AtomicU64,
+    /// "udp://host:port" or "tcp://host:port"
+    target: String,
+}
+
+impl SyslogSink {
+    fn send(&self, entry: &LogEntry) {
+        let msg = syslog_message(entry, self.facility, &self.hostname);
+        if self.tx.try_send(msg).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// "local0" .. "local7", a few common names, or the number itself.
+fn syslog_facility(name: &str) -> Option<u8> {
+    let name = name.trim().to_ascii_lowercase();
+    if let Some(n) = name.strip_prefix("local") {
+        return n.parse::<u8>().ok().filter(|n| *n <= 7).map(|n| 16 + n);
+    }
+    match name.as_str() {
+        "kern" => Some(0),
+        "user" => Some(1),
+        "daemon" => Some(3),
+        _ => name.parse::<u8>().ok().filter(|n| *n <= 23),
+    }
+}
+
+fn syslog_severity(level: &str) -> u8 {
+    match level {
+        "error" => 3,
+        "warn" => 4,
+        // User actions (markers, snapshots) stand out from routine output
+        "marker" | "snapshot" => 5,
+        "debug" => 7,
+        _ => 6,
+    }
+}
+
+/// A header field: printable ASCII without spaces, at most `max` long, or
+/// "-" when there's nothing left.
+fn syslog_field(value: &str, max: usize) -> String {
+    let field: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max)
+        .collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+/// RFC 5424 line: the tag goes in APP-NAME, the level sets the severity.
+fn syslog_message(entry: &LogEntry, facility: u8, hostname: &str) -> String {
+    let time = entry
+        .host_timestamp
+        .map(rfc3339_utc)
+        .unwrap_or_else(|| "-".to_string());
+    let app_name = syslog_field(entry.tag.as_deref().unwrap_or("rtt-viewer"), 48);
+    format!(
+        "<{}>1 {time} {hostname} {app_name} - - - {}",
+        u32::from(facility) * 8 + u32::from(syslog_severity(&entry.level)),
+        entry.message
+    )
+}
+
+enum SyslogTransport {
+    Udp(tokio::net::UdpSocket),
+    Tcp(std::net::SocketAddr, Option<tokio::net::TcpStream>),
+}
+
+impl SyslogTransport {
+    async fn connect_tcp(addr: std::net::SocketAddr) -> std::io::Result<tokio::net::TcpStream> {
+        tokio::time::timeout(SYSLOG_CONNECT_TIMEOUT, tokio::net::TcpStream::connect(addr))
+            .await
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "connect timed out"))?
+    }
+
+    /// UDP sends one datagram per message; TCP frames it with its length
+    /// (RFC 6587 octet counting), so multi-line messages stay whole.
+    async fn send(&mut self, msg: &str) -> std::io::Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        match self {
+            SyslogTransport::Udp(socket) => {
+                socket.send(udp_datagram(msg).as_bytes()).await.map(|_| ())
+            }
+            SyslogTransport::Tcp(addr, stream) => {
+                if stream.is_none() {
+                    *stream = Some(Self::connect_tcp(*addr).await?);
+                }
+                let framed = format!("{} {msg}", msg.len());
+                let result = stream.as_mut().unwrap().write_all(framed.as_bytes()).await;
+                if result.is_err() {
+                    *stream = None;
+                }
+                result
+            }
+        }
+    }
+}
+
+/// `msg` as it fits in one datagram: a longer one is cut on a character
+/// boundary and ends in `SYSLOG_TRUNCATED`, so the server can tell.
+fn udp_datagram(msg: &str) -> std::borrow::Cow<'_, str> {
+    if msg.len() <= SYSLOG_UDP_MAX {
+        return msg.into();
+    }
+    let mut end = SYSLOG_UDP_MAX - SYSLOG_TRUNCATED.len();
+    while !msg.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{SYSLOG_TRUNCATED}", &msg[..end]).into()
+}
+
+/// Drain the queue until `stop_syslog` drops the sender. While the server is
+/// unreachable, messages are thrown away and a single warning is logged, and
+/// a TCP connection is retried every `SYSLOG_RETRY`.
+async fn run_syslog(
+    app: AppHandle,
+    mut transport: SyslogTransport,
+    mut rx: tokio::sync::mpsc::Receiver<String>,
+    target: String,
+) {
+    let mut failing_since: Option<std::time::Instant> = None;
+    while let Some(msg) = rx.recv().await {
+        if failing_since.is_some_and(|t| t.elapsed() < SYSLOG_RETRY)
+            && matches!(transport, SyslogTransport::Tcp(_, None))
+        {
+            continue;
+        }
+        match transport.send(&msg).await {
+            // A UDP socket reports an ICMP unreachable on the send after it,
+            // so sends alternate between failing and not while the server is
+            // down; only call it back up after a quiet `SYSLOG_RETRY`
+            Ok(()) => {
+                if failing_since.is_some_and(|t| t.elapsed() >= SYSLOG_RETRY) {
+                    failing_since = None;
+                    emit_rtt_status(
+                        &app,
+                        "info",
+                        &format!("Syslog forwarding to {target} resumed"),
+                    );
+                }
+            }
+            Err(e) => {
+                if failing_since.is_none() {
+                    emit_rtt_status(
+                        &app,
+                        "warn",
+                        &format!("Syslog server {target} unreachable, dropping messages: {e}"),
+                    );
+                }
+                failing_since = Some(std::time::Instant::now());
+            }
+        }
+    }
+}
+
+/// Forward every `rtt-log` entry to a syslog server as RFC 5424 over UDP
+/// (default) or TCP. `facility` is e.g. "local0" or a number. Capture never
+/// waits on the network: when the server is slow or down, entries are dropped.
+#[tauri::command]
+async fn log_to_syslog(
+    app: AppHandle,
+    host: String,
+    port: u16,
+    facility: String,
+    protocol: Option<String>,
+) -> Result<String, AppError> {
+    let state = app.state::<AppState>();
+    let facility_code = syslog_facility(&facility).ok_or_else(|| {
+        AppError::InvalidInput(format!(
+            "Unknown syslog facility: {facility} (e.g. local0..local7, user, or 0-23)"
+        ))
+    })?;
+    let protocol = protocol.as_deref().unwrap_or("udp").to_ascii_lowercase();
+    let addr = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| AppError::InvalidInput(format!("Cannot resolve syslog host: {host}")))?;
+    let transport = match protocol.as_str() {
+        "udp" => {
+            let bind: std::net::SocketAddr = if addr.is_ipv4() {
+                ([0, 0, 0, 0], 0).into()
+            } else {
+                (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+            };
+            let socket = tokio::net::UdpSocket::bind(bind)
+                .await
+                .map_err(|e| AppError::Io(format!("Cannot open UDP socket: {e}")))?;
+            socket
+                .connect(addr)
+                .await
+                .map_err(|e| AppError::Io(format!("Cannot reach {addr}: {e}")))?;
+            SyslogTransport::Udp(socket)
+        }
+        "tcp" => {
+            let stream = SyslogTransport::connect_tcp(addr)
+                .await
+                .map_err(|e| AppError::Io(format!("Cannot connect to {addr}: {e}")))?;
+            SyslogTransport::Tcp(addr, Some(stream))
+        }
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Unknown syslog protocol: {protocol} (expected \"udp\" or \"tcp\")"
+            )))
+        }
+    };
+
+    let hostname = state
+        .active_profile
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|p| syslog_field(&p.name, 255))
+        .unwrap_or_else(|| "-".to_string());
+    let target = format!("{protocol}://{host}:{port}");
+    let (tx, rx) = tokio::sync::mpsc::channel(SYSLOG_BUFFER);
+    {
+        // Checked only now, under the lock that stores it, so two calls
+        // racing through the setup above can't both start
+        let mut syslog = state.syslog.lock().unwrap();
+        if syslog.is_some() {
+            return Err(AppError::InvalidInput(
+                "Syslog forwarding already running".to_string(),
+            ));
+        }
+        *syslog = Some(SyslogSink {
+            tx,
+            facility: facility_code,
+            hostname,
+            dropped: AtomicU64::new(0),
+            target: target.clone(),
+        });
+    }
+    tokio::spawn(run_syslog(app.clone(), transport, rx, target.clone()));
+    Ok(format!("Forwarding logs to {target}"))
+}
+
+#[tauri::command]
+async fn stop_syslog(app: AppHandle) -> Result<String, AppError> {
+    // Dropping the sender ends `run_syslog` once the queue is drained
+    let sink = app.state::<AppState>().syslog.lock().unwrap().take();
+    let Some(sink) = sink else {
+        return Err(AppError::NotConnected(
+            "Syslog forwarding not running".to_string(),
+        ));
+    };
+    let dropped = sink.dropped.load(Ordering::Relaxed);
+    Ok(if dropped > 0 {
+        format!(
+            "Stopped forwarding to {} ({dropped} entries dropped while the queue was full)",
+            sink.target
+        )
+    } else {
+        format!("Stopped forwarding to {}", sink.target)
+    })
+}
+
+// ── MQTT forwarding ──
+
+/// Entries held for the broker while it's unreachable; past this, new
+/// entries are dropped rather than holding up capture
+const MQTT_BUFFER: usize = 10_000;
+const MQTT_KEEP_ALIVE: std::time::Duration = std::time::Duration::from_secs(30);
+/// Wait between reconnects while the broker is down
+const MQTT_RETRY: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Publishing set up by `log_to_mqtt`. The client queues into its event
+/// loop's bounded channel, so `emit_log` never waits on the network.
+struct MqttSink {
+    client: rumqttc::AsyncClient,
+    topic: String,
+    qos: rumqttc::QoS,
+    /// Publish to "topic/level" instead of "topic"
+    per_level: bool,
+    /// Entries that didn't fit in the buffer
+    dropped: AtomicU64,
+    /// "host:port/topic", for messages
+    target: String,
+    shutdown: tokio::sync::oneshot::Sender<()>,
+}
+
+impl MqttSink {
+    fn send(&self, entry: &LogEntry) {
+        let Ok(json) = serde_json::to_vec(entry) else {
+            return;
+        };
+        let topic = if self.per_level {
+            format!("{}/{}", self.topic, entry.level)
+        } else {
+            self.topic.clone()
+        };
+        if self
+            .client
+            .try_publish(topic, self.qos, false, json)
+            .is_err()
+        {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Drive the client until `stop_mqtt`. The event loop reconnects on the next
+/// poll after an error, and resends what the broker hadn't acknowledged; a
+/// single warning is logged per outage.
+async fn run_mqtt(
+    app: AppHandle,
+    mut eventloop: rumqttc::EventLoop,
+    mut shutdown: tokio::sync::oneshot::Receiver<()>,
+    target: String,
+) {
+    let mut down = false;
+    let mut backoff = false;
+    loop {
+        // Waiting out a failed connect still answers `stop_mqtt` right away
+        if std::mem::take(&mut backoff) {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = tokio::time::sleep(MQTT_RETRY) => {}
+            }
+        }
+        tokio::select! {
+            _ = &mut shutdown => break,
+            event = eventloop.poll() => match event {
+                Ok(rumqttc::Event::Incoming(rumqttc::Packet::ConnAck(_))) if down => {
+                    down = false;
+                    emit_rtt_status(&app, "info", &format!("MQTT broker {target} reachable again"));
+                }
+                Ok(_) => {}
+                Err(rumqttc::ConnectionError::RequestsDone) => break,
+                Err(e) => {
+                    if !down {
+                        down = true;
+                        emit_rtt_status(
+                            &app,
+                            "warn",
+                            &format!(
+                                "MQTT broker {target} unreachable, buffering up to {MQTT_BUFFER} entries: {e}"
+                            ),
+                        );
+                    }
+                    backoff = true;
+                }
+            },
+        }
+    }
+    // Let the broker know, if it's listening
+    let _ = tokio::time::timeout(std::time::Duration::from_secs(1), async {
+        while eventloop.poll().await.is_ok() {}
+    })
+    .await;
+}
+
+/// Optional settings of `log_to_mqtt`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MqttPublishOptions {
+    /// 0, 1 or 2
+    qos: u8,
+    /// Publish to "topic/error", "topic/info" and so on
+    per_level: bool,
+    /// "rtt-viewer-<pid>" when unset
+    client_id: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+/// "host", "host:port", "[v6addr]:port" or a bare IPv6 address into host and
+/// port (1883 by default). An IPv6 address only takes a port in brackets.
+fn split_broker(broker: &str) -> Result<(&str, u16), AppError> {
+    let parse_port = |port: &str| {
+        port.parse::<u16>()
+            .map_err(|_| AppError::InvalidInput(format!("Invalid MQTT port: {port}")))
+    };
+    if let Some(rest) = broker.strip_prefix('[') {
+        let (host, after) = rest.split_once(']').ok_or_else(|| {
+            AppError::InvalidInput(format!("Invalid MQTT broker: {broker} (no closing ])"))
+        })?;
+        return match after {
+            "" => Ok((host, 1883)),
+            _ => match after.strip_prefix(':') {
+                Some(port) => Ok((host, parse_port(port)?)),
+                None => Err(AppError::InvalidInput(format!(
+                    "Invalid MQTT broker: {broker}"
+                ))),
+            },
+        };
+    }
+    match broker.split_once(':') {
+        Some((host, port)) if !port.contains(':') => Ok((host, parse_port(port)?)),
+        _ => Ok((broker, 1883)),
+    }
+}
+
+/// Publish every `rtt-log` entry as JSON (the same shape as `rtt-log`) to an
+/// MQTT broker. `broker` is "host", "host:port" or "[v6addr]:port" (port 1883
+/// by default).
+/// While the broker is down, up to `MQTT_BUFFER` entries wait for the
+/// reconnect and later ones are dropped.
+#[tauri::command]
+async fn log_to_mqtt(
+    app: AppHandle,
+    broker: String,
+    topic: String,
+    options: Option<MqttPublishOptions>,
+) -> Result<String, AppError> {
+    let MqttPublishOptions {
+        qos,
+        per_level,
+        client_id,
+        username,
+        password,
+    } = options.unwrap_or_default();
+    let state = app.state::<AppState>();
+    if state.mqtt.lock().unwrap().is_some() {
+        return Err(AppError::InvalidInput(
+            "MQTT publishing already running".to_string(),
+        ));
+    }
+    let broker = broker.trim();
+    let broker = broker.strip_prefix("mqtt://").unwrap_or(broker);
+    let (host, port) = split_broker(broker)?;
+    if host.is_empty() {
+        return Err(AppError::InvalidInput(
+            "MQTT broker host is empty".to_string(),
+        ));
+    }
+    let topic = topic.trim().trim_end_matches('/');
+    if topic.is_empty() || topic.contains(['+', '#']) {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid MQTT topic: \"{topic}\" (must be non-empty, without + or #)"
+        )));
+    }
+    let qos = match qos {
+        0 => rumqttc::QoS::AtMostOnce,
+        1 => rumqttc::QoS::AtLeastOnce,
+        2 => rumqttc::QoS::ExactlyOnce,
+        n => {
+            return Err(AppError::InvalidInput(format!(
+                "Invalid MQTT QoS: {n} (expected 0, 1 or 2)"
+            )))
+        }
+    };
+
+    let client_id = client_id
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| format!("rtt-viewer-{}", std::process::id()));
+    let mut mqtt_options = rumqttc::MqttOptions::new(client_id, host, port);
+    mqtt_options.set_keep_alive(MQTT_KEEP_ALIVE);
+    if let Some(username) = username.filter(|u| !u.is_empty()) {
+        mqtt_options.set_credentials(username, password.unwrap_or_default());
+    }
+    let (client, eventloop) = rumqttc::AsyncClient::new(mqtt_options, MQTT_BUFFER);
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+    let target = format!("{host}:{port}/{topic}");
+    *state.mqtt.lock().unwrap() = Some(MqttSink {
+        client,
+        topic: topic.to_string(),
+        qos,
+        per_level,
+        dropped: AtomicU64::new(0),
+        target: target.clone(),
+        shutdown: shutdown_tx,
+    });
+    tokio::spawn(run_mqtt(
+        app.clone(),
+        eventloop,
+        shutdown_rx,
+        target.clone(),
+    ));
+    Ok(format!("Publishing logs to {target}"))
+}
+
+#[tauri::command]
+async fn stop_mqtt(app: AppHandle) -> Result<String, AppError> {
+    let sink = app.state::<AppState>().mqtt.lock().unwrap().take();
+    let Some(sink) = sink else {
+        return Err(AppError::NotConnected(
+            "MQTT publishing not running".to_string(),
+        ));
+    };
+    let _ = sink.client.try_disconnect();
+    let _ = sink.shutdown.send(());
+    let dropped = sink.dropped.load(Ordering::Relaxed);
+    Ok(if dropped > 0 {
+        format!(
+            "Stopped publishing to {} ({dropped} entries dropped while the buffer was full)",
+            sink.target
+        )
+    } else {
+        format!("Stopped publishing to {}", sink.target)
+    })
+}
+
+// ── Search index ──
+
+/// Line text kept for searching; the oldest lines are dropped past this
+const MAX_INDEX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Inverted index over emitted lines: lowercase word → ids of the lines holding it.
+/// Ids almost always arrive in order, so eviction usually pops from the front.
+struct SearchIndex {
+    enabled: bool,
+    lines: std::collections::VecDeque<(u64, Arc<str>)>,
+    bytes: usize,
+    postings: HashMap<Arc<str>, std::collections::VecDeque<u64>>,
+    /// Byte trigram → indexed words containing it, so a partial word finds
+    /// its words without walking the whole vocabulary
+    grams: HashMap<[u8; 3], HashSet<Arc<str>>>,
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lines: Default::default(),
+            bytes: 0,
+            postings: HashMap::new(),
+            grams: HashMap::new(),
+        }
+    }
+}
+
+/// Lowercase words of a line, each once.
+fn index_tokens(text: &str) -> HashSet<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|t| !t.is_empty() && t.len() <= 64)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn trigrams(word: &str) -> impl Iterator<Item = [u8; 3]> + '_ {
+    word.as_bytes().windows(3).map(|w| [w[0], w[1], w[2]])
+}
+
+impl SearchIndex {
+    fn insert(&mut self, entry: &LogEntry) {
+        if !self.enabled {
+            return;
+        }
+        let text = if entry.raw.is_empty() {
+            &entry.message
+        } else {
+            &entry.raw
+        };
+        for token in index_tokens(text) {
+            match self.postings.get_mut(token.as_str()) {
+                Some(ids) => ids.push_back(entry.id),
+                None => {
+                    let word: Arc<str> = token.into();
+                    for gram in trigrams(&word) {
+                        self.grams.entry(gram).or_default().insert(word.clone());
+                    }
+                    self.postings.insert(word, [entry.id].into());
+                }
+            }
+        }
+        self.bytes += text.len();
+        self.lines.push_back((entry.id, text.as_str().into()));
+        while self.bytes > MAX_INDEX_BYTES {
+            self.evict_oldest();
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        let Some((id, text)) = self.lines.pop_front() else {
+            return;
+        };
+        self.bytes -= text.len();
+        for token in index_tokens(&text) {
+            let Some(ids) = self.postings.get_mut(token.as_str()) else {
+                continue;
+            };
+            if ids.front() == Some(&id) {
+                ids.pop_front();
+            } else {
+                // A status line from another thread overtook this one
+                ids.retain(|&i| i != id);
+            }
+            if !ids.is_empty() {
+                continue;
+            }
+            self.postings.remove(token.as_str());
+            for gram in trigrams(&token) {
+                if let Some(words) = self.grams.get_mut(&gram) {
+                    words.remove(token.as_str());
+                    if words.is_empty() {
+                        self.grams.remove(&gram);
+                    }
+                }
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.lines.clear();
+        self.postings.clear();
+        self.grams.clear();
+        self.bytes = 0;
+    }
+
+    fn line(&self, id: u64) -> Option<&Arc<str>> {
+        // Ids are ascending but not contiguous (status lines, markers, other sources)
+        let pos = self
+            .lines
+            .binary_search_by_key(&id, |(i, _)| *i)
+            .ok()
+            .or_else(|| self.lines.iter().position(|(i, _)| *i == id))?;
+        Some(&self.lines[pos].1)
+    }
+
+    /// Ids of lines holding an indexed word that contains `part`, through the
+    /// trigram map. `part` must be at least three bytes.
+    fn ids_containing(&self, part: &str) -> HashSet<u64> {
+        let mut sets: Vec<&HashSet<Arc<str>>> = Vec::new();
+        for gram in trigrams(part) {
+            match self.grams.get(&gram) {
+                Some(words) => sets.push(words),
+                None => return HashSet::new(),
+            }
+        }
+        sets.sort_by_key(|words| words.len());
+        let Some((smallest, rest)) = sets.split_first() else {
+            return HashSet::new();
+        };
+        smallest
+            .iter()
+            .filter(|word| rest.iter().all(|words| words.contains(*word)))
+            .filter(|word| word.contains(part))
+            .filter_map(|word| self.postings.get(word))
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// Lines that may contain `query`, copied out so they can be checked
+    /// without holding the index. Every word of the query narrows the
+    /// candidates (any indexed word containing it, since the query's edge words
+    /// may be partial); words under three bytes are left to the check. `None`
+    /// if no word narrows anything and every line has to be scanned.
+    fn substring_candidates(&self, query: &str) -> Option<Vec<(u64, Arc<str>)>> {
+        let mut candidates: Option<HashSet<u64>> = None;
+        for part in index_tokens(query).iter().filter(|p| p.len() >= 3) {
+            let ids = self.ids_containing(part);
+            candidates = Some(match candidates {
+                None => ids,
+                Some(prev) => prev.intersection(&ids).copied().collect(),
+            });
+        }
+        let mut ids: Vec<u64> = candidates?.into_iter().collect();
+        ids.sort_unstable();
+        Some(
+            ids.into_iter()
+                .filter_map(|id| self.line(id).map(|line| (id, line.clone())))
+                .collect(),
+        )
+    }
+
+    fn snapshot(&self) -> Vec<(u64, Arc<str>)> {
+        self.lines.iter().cloned().collect()
+    }
+}
+
+/// Ids of indexed lines matching `query`, oldest first. The index is locked
+/// only to copy out the lines to check, so emits aren't held up by a search.
+fn search_index(
+    index: &Mutex<SearchIndex>,
+    query: &str,
+    regex: bool,
+) -> Result<Vec<u64>, AppError> {
+    let re = regex
+        .then(|| Regex::new(query))
+        .transpose()
+        .map_err(|e| AppError::InvalidInput(format!("Invalid regex: {e}")))?;
+    let lines = {
+        let index = index.lock().unwrap();
+        if !index.enabled {
+            return Err(AppError::InvalidInput(
+                "Search index is disabled".to_string(),
+            ));
+        }
+        match re {
+            Some(_) => index.snapshot(),
+            None => index
+                .substring_candidates(query)
+                .unwrap_or_else(|| index.snapshot()),
+        }
+    };
+    let needle = query.to_lowercase();
+    Ok(lines
+        .into_iter()
+        .filter(|(_, line)| match &re {
+            Some(re) => re.is_match(line),
+            None => line.to_lowercase().contains(&needle),
+        })
+        .map(|(id, _)| id)
+        .collect())
+}
+
+/// Ids of indexed lines matching `query`, oldest first: case-insensitive
+/// substring through the index, or a full scan when `regex` is set.
+#[tauri::command]
+async fn search_logs(app: AppHandle, query: String, regex: bool) -> Result<Vec<u64>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        search_index(&app.state::<AppState>().search_index, &query, regex)
+    })
+    .await
+    .map_err(|e| AppError::Other(format!("Search task failed: {e}")))?
+}
+
+/// Turn the index on or off; off also frees it.
+#[tauri::command]
+async fn set_search_index(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let mut index = state.search_index.lock().unwrap();
+    index.enabled = enabled;
+    if !enabled {
+        index.clear();
+    }
+    Ok(())
+}
+
+/// Forget indexed lines, e.g. when the frontend clears its log.
+#[tauri::command]
+async fn clear_search_index(app: AppHandle) -> Result<(), AppError> {
+    app.state::<AppState>().search_index.lock().unwrap().clear();
+    Ok(())
+}
+
+// ── Live views ──
+
+/// Ids handed out by `create_view`
+static NEXT_VIEW_ID: AtomicU32 = AtomicU32::new(1);
+
+/// What a view keeps: a preset-style filter, optionally narrowed to some tags
+/// and to lines containing `text`.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ViewFilter {
+    #[serde(flatten)]
+    filter: LogFilter,
+    /// Only these tags; empty keeps every tag
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Case-insensitive substring of the raw line
+    text: Option<String>,
+}
+
+/// A named, continuously updated filtered stream. Matching entries go out as
+/// `view-log:{id}` alongside `rtt-log`, so a pane never re-filters the buffer.
+struct LogView {
+    id: u32,
+    name: String,
+    filter: EmitFilter,
+    tags: HashSet<String>,
+    /// Lowercased
+    text: Option<String>,
+}
+
+impl LogView {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        // User-requested, never filtered out (same as the main view)
+        if entry.level == "marker" || entry.level == "snapshot" {
+            return true;
+        }
+        self.filter.allows(entry)
+            && (self.tags.is_empty() || entry.tag.as_ref().is_some_and(|t| self.tags.contains(t)))
+            && self
+                .text
+                .as_ref()
+                .is_none_or(|text| entry.raw.to_lowercase().contains(text))
+    }
+}
+
+/// Ids of the views `entry` belongs in.
+fn matching_views(views: &[LogView], entry: &LogEntry) -> Vec<u32> {
+    views
+        .iter()
+        .filter(|view| view.matches(entry))
+        .map(|view| view.id)
+        .collect()
+}
+
+/// Send `entry` to its views. Called for every parsed entry, including those
+/// the emit filter keeps off `rtt-log`.
+fn emit_to_views(app: &AppHandle, entry: &LogEntry) {
+    let ids = matching_views(&app.state::<AppState>().views.lock().unwrap(), entry);
+    for id in ids {
+        let _ = app.emit(&format!("view-log:{id}"), entry);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ViewInfo {
+    id: u32,
+    name: String,
+}
+
+/// Start a live view; entries from now on that pass `filter` arrive as
+/// `view-log:{id}`, whether or not the emit filter lets them onto `rtt-log`.
+/// The frontend seeds it with what it already has.
+#[tauri::command]
+async fn create_view(app: AppHandle, name: String, filter: ViewFilter) -> Result<u32, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("View name is empty".to_string()));
+    }
+    let id = NEXT_VIEW_ID.fetch_add(1, Ordering::Relaxed);
+    let view = LogView {
+        id,
+        name: name.to_string(),
+        filter: EmitFilter::from_preset(&filter.filter),
+        tags: filter.tags.into_iter().collect(),
+        text: filter
+            .text
+            .map(|t| t.to_lowercase())
+            .filter(|t| !t.is_empty()),
+    };
+    app.state::<AppState>().views.lock().unwrap().push(view);
+    Ok(id)
+}
+
+#[tauri::command]
+async fn list_views(app: AppHandle) -> Result<Vec<ViewInfo>, AppError> {
+    Ok(app
+        .state::<AppState>()
+        .views
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|v| ViewInfo {
+            id: v.id,
+            name: v.name.clone(),
+        })
+        .collect())
+}
+
+#[tauri::command]
+async fn close_view(app: AppHandle, id: u32) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let mut views = state.views.lock().unwrap();
+    let before = views.len();
+    views.retain(|v| v.id != id);
+    if views.len() == before {
+        return Err(AppError::InvalidInput(format!("No view with id {id}")));
+    }
+    Ok(())
+}
+
+// ── Flight recorder ──
+
+/// Longest window `arm_flight_recorder` accepts
+const FLIGHT_RECORDER_MAX_WINDOW_S: f64 = 3600.0;
+
+/// Entry text the recorder holds; past this the oldest entries go even if
+/// they are still inside the window
+const FLIGHT_RECORDER_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Keeps the last `window` of entries. Once the trigger fires it records a
+/// quarter window more for context after the event, then holds still until
+/// dumped, so later traffic can't push the lead-up out.
+struct FlightRecorder {
+    window: std::time::Duration,
+    trigger: Option<Regex>,
+    ring: std::collections::VecDeque<(std::time::Instant, LogEntry)>,
+    /// Text bytes of the entries in `ring`
+    bytes: usize,
+    /// Id of the entry that matched, and when
+    fired: Option<(u64, std::time::Instant)>,
+    frozen: bool,
+}
+
+/// What an entry costs against `FLIGHT_RECORDER_MAX_BYTES`
+fn recorded_bytes(entry: &LogEntry) -> usize {
+    entry.raw.len() + entry.message.len()
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum FlightRecorderEvent {
+    /// The trigger matched; context after it is still being collected
+    Fired { id: u64, message: String },
+    /// The window is complete and waiting for `dump_flight_recorder`
+    Ready { entries: usize },
+}
+
+impl FlightRecorder {
+    fn new(window: std::time::Duration, trigger: Option<Regex>) -> Self {
+        Self {
+            window,
+            trigger,
+            ring: std::collections::VecDeque::new(),
+            bytes: 0,
+            fired: None,
+            frozen: false,
+        }
+    }
+
+    /// Hold still once the context after the trigger is complete. Called for
+    /// each entry and from a timer, since the device may go quiet after the
+    /// event it logged.
+    fn tick(&mut self, now: std::time::Instant) -> Option<FlightRecorderEvent> {
+        let (_, at) = self.fired?;
+        if self.frozen || now.duration_since(at) < self.window / 4 {
+            return None;
+        }
+        Some(self.freeze())
+    }
+
+    fn freeze(&mut self) -> FlightRecorderEvent {
+        self.frozen = true;
+        FlightRecorderEvent::Ready {
+            entries: self.ring.len(),
+        }
+    }
+
+    fn record(&mut self, entry: &LogEntry) -> Option<FlightRecorderEvent> {
+        if self.frozen {
+            return None;
+        }
+        let now = std::time::Instant::now();
+        if let Some(event) = self.tick(now) {
+            return Some(event);
+        }
+        let size = recorded_bytes(entry);
+        if self.fired.is_some() && self.bytes + size > FLIGHT_RECORDER_MAX_BYTES {
+            // Making room would push out the lead-up; stop short instead
+            return Some(self.freeze());
+        }
+        self.ring.push_back((now, entry.clone()));
+        self.bytes += size;
+        if self.fired.is_none() {
+            while self.ring.front().is_some_and(|(t, _)| {
+                now.duration_since(*t) > self.window || self.bytes > FLIGHT_RECORDER_MAX_BYTES
+            }) {
+                if let Some((_, old)) = self.ring.pop_front() {
+                    self.bytes -= recorded_bytes(&old);
+                }
+            }
+        }
+        let hit = self.fired.is_none()
+            && self
+                .trigger
+                .as_ref()
+                .is_some_and(|re| re.is_match(&entry.raw));
+        hit.then(|| {
+            self.fired = Some((entry.id, now));
+            FlightRecorderEvent::Fired {
+                id: entry.id,
+                message: entry.message.clone(),
+            }
+        })
+    }
+}
+
+/// Start keeping the last `window_s` seconds of logs (replacing a recorder
+/// that is already armed). With a pattern, the first line whose raw text
+/// matches it ends the window; without one, only a manual dump does.
+#[tauri::command]
+async fn arm_flight_recorder(
+    app: AppHandle,
+    window_s: f64,
+    trigger_pattern: Option<String>,
+) -> Result<(), AppError> {
+    if !(window_s > 0.0 && window_s <= FLIGHT_RECORDER_MAX_WINDOW_S) {
+        return Err(AppError::InvalidInput(format!(
+            "Flight recorder window must be 0-{FLIGHT_RECORDER_MAX_WINDOW_S} s, got {window_s}"
+        )));
+    }
+    let trigger = trigger_pattern
+        .filter(|p| !p.is_empty())
+        .map(|p| Regex::new(&p))
+        .transpose()
+        .map_err(|e| AppError::InvalidInput(format!("Invalid trigger pattern: {e}")))?;
+    *app.state::<AppState>().flight_recorder.lock().unwrap() = Some(FlightRecorder::new(
+        std::time::Duration::from_secs_f64(window_s),
+        trigger,
+    ));
+    Ok(())
+}
+
+#[tauri::command]
+async fn disarm_flight_recorder(app: AppHandle) -> Result<(), AppError> {
+    app.state::<AppState>()
+        .flight_recorder
+        .lock()
+        .unwrap()
+        .take();
+    Ok(())
+}
+
+/// Write the recorded window as a text export, with a marker line before the
+/// entry that fired the trigger. The recorder then starts over, still armed.
+#[tauri::command]
+async fn dump_flight_recorder(app: AppHandle, path: String) -> Result<String, AppError> {
+    let state = app.state::<AppState>();
+    let mut guard = state.flight_recorder.lock().unwrap();
+    let recorder = guard
+        .as_mut()
+        .ok_or_else(|| AppError::InvalidInput("Flight recorder is not armed".to_string()))?;
+    let mut entries: Vec<LogEntry> = recorder.ring.drain(..).map(|(_, e)| e).collect();
+    recorder.bytes = 0;
+    let fired = recorder.fired.take().map(|(id, _)| id);
+    recorder.frozen = false;
+    drop(guard);
+
+    let count = entries.len();
+    if let Some(pos) = fired.and_then(|id| entries.iter().position(|e| e.id == id)) {
+        // Not a log entry, so no id: taking one from SEQ would show up as a
+        // lost event in the UI's gap count
+        entries.insert(
+            pos,
+            LogEntry {
+                level: "marker".to_string(),
+                message: "flight recorder trigger".to_string(),
+                ..Default::default()
+            },
+        );
+    }
+    std::fs::write(&path, entries_to_text(&entries) + "\n")?;
+    Ok(format!("Dumped {count} entries to {path}"))
+}
+
+// ── Export formatting ──
+
+const DEFAULT_EXPORT_TEMPLATE: &str = "rtt-logs-{date}-{time}";
+
+/// File extension for each export format
+fn export_extension(format: &str) -> Result<&'static str, AppError> {
+    match format {
+        "json" => Ok("json"),
+        "ndjson" => Ok("ndjson"),
+        "csv" => Ok("csv"),
+        "txt" => Ok("txt"),
+        "html" => Ok("html"),
+        "sqlite" => Ok("db"),
+        _ => Err(AppError::InvalidInput(format!("Unknown format: {format}"))),
+    }
+}
+
+/// Fill in an export file name template. Values can't add path components:
+/// separators and other characters file systems reject become `_`.
+fn expand_export_template(template: &str, profile: Option<&Profile>, now_ms: u64) -> String {
+    let safe = |s: &str| -> String {
+        s.chars()
+            .map(|c| match c {
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect()
+    };
+    // UTC, like the timestamps the frontend exports used before
+    let stamp = rfc3339_utc(now_ms);
+    template
+        .replace("{profile}", &safe(profile.map_or("", |p| p.name.as_str())))
+        .replace("{chip}", &safe(profile.map_or("", |p| p.chip.as_str())))
+        .replace("{date}", &stamp[..10])
+        .replace("{time}", &stamp[11..19].replace(':', "-"))
+}
+
+/// Path the export dialogs start with for `format`: the profile's export
+/// folder and file name template, or a timestamped name in the dialog's
+/// default folder.
+#[tauri::command]
+async fn default_export_path(profile: Option<String>, format: String) -> Result<String, AppError> {
+    let ext = export_extension(&format)?;
+    let profile = profile.and_then(|name| load_profiles().into_iter().find(|p| p.name == name));
+    let template = profile
+        .as_ref()
+        .and_then(|p| p.export_filename_template.as_deref())
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or(DEFAULT_EXPORT_TEMPLATE);
+    let file_name = format!(
+        "{}.{ext}",
+        expand_export_template(template, profile.as_ref(), host_now_ms())
+    );
+    Ok(
+        match profile.as_ref().and_then(|p| p.export_dir.as_deref()) {
+            Some(dir) if !dir.trim().is_empty() => std::path::Path::new(dir)
+                .join(file_name)
+                .to_string_lossy()
+                .into_owned(),
+            _ => file_name,
+        },
+    )
+}
+
+/// Same shape as the frontend JSON export: every field except `raw`.
+fn entries_to_json(entries: &[LogEntry]) -> String {
+    let values: Vec<serde_json::Value> = entries
+        .iter()
+        .filter_map(|e| serde_json::to_value(e).ok())
+        .map(|mut v| {
+            if let Some(obj) = v.as_object_mut() {
+                obj.remove("raw");
+            }
+            v
+        })
+        .collect();
+    serde_json::to_string_pretty(&values).unwrap_or_default()
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+const CSV_HEADER: &str = "id,terminal,device_timestamp,level,tag,message";
+
+fn csv_row(e: &LogEntry) -> String {
+    let row = [
+        e.id.to_string(),
+        e.terminal.map(|t| t.to_string()).unwrap_or_default(),
+        e.device_timestamp.clone().unwrap_or_default(),
+        e.level.clone(),
+        e.tag.clone().unwrap_or_default(),
+        e.message.clone(),
+    ];
+    row.iter()
+        .map(|f| csv_field(f))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn entries_to_csv(entries: &[LogEntry]) -> String {
+    let mut out = String::from(CSV_HEADER);
+    for e in entries {
+        out.push('\n');
+        out.push_str(&csv_row(e));
+    }
+    out
+}
+
+/// Text-export prefix of `insert_marker` lines (same as log-io.js)
+const MARKER_PREFIX: &str = ">>> MARKER:";
+
+fn entries_to_text(entries: &[LogEntry]) -> String {
+    entries
+        .iter()
+        .map(|e| {
+            if e.level == "marker" {
+                return format!("{:>5} {MARKER_PREFIX} {}", e.id, e.message);
+            }
+            let mut parts = vec![format!("{:>5}", e.id)];
+            if let Some(core) = &e.core_name {
+                parts.push(format!("[{core}]"));
+            }
+            if let Some(t) = e.terminal {
+                parts.push(format!("T{t}"));
+            }
+            if let Some(ts) = &e.device_timestamp {
+                parts.push(ts.clone());
+            }
+            let lvl: String = e.level.to_uppercase().chars().take(3).collect();
+            parts.push(format!("[{lvl}]"));
+            if let Some(tag) = &e.tag {
+                parts.push(format!("<{tag}>"));
+            }
+            parts.push(if e.message.is_empty() {
+                e.raw.clone()
+            } else {
+                e.message.clone()
+            });
+            parts.join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Line endings for exported text.
+#[derive(Debug, Clone, Copy)]
+enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    /// "lf" | "crlf" | "native"; `None` keeps the historical LF.
+    fn parse(s: Option<&str>) -> Result<Self, AppError> {
+        match s {
+            None | Some("lf") => Ok(Self::Lf),
+            Some("crlf") => Ok(Self::CrLf),
+            Some("native") if cfg!(windows) => Ok(Self::CrLf),
+            Some("native") => Ok(Self::Lf),
+            Some(other) => Err(AppError::InvalidInput(format!(
+                "Unknown line ending: {other} (expected lf, crlf or native)"
+            ))),
+        }
+    }
+
+    /// Normalize every line break in `text`, whatever mix it arrived with.
+    fn apply(self, text: &str) -> String {
+        let lf = text.replace("\r\n", "\n");
+        match self {
+            Self::Lf => lf,
+            Self::CrLf => lf.replace('\n', "\r\n"),
+        }
+    }
+}
+
+/// How `ascii_only` exports write non-ASCII text, in a form that keeps each
+/// format valid: `\uXXXX` in JSON, `&#N;` in HTML, `\xNN` per UTF-8 byte otherwise.
+#[derive(Debug, Clone, Copy)]
+enum AsciiEscape {
+    Bytes,
+    Json,
+    Html,
+}
+
+impl AsciiEscape {
+    /// Escaping for an export format or file extension
+    fn for_format(format: &str) -> Self {
+        match format.to_ascii_lowercase().as_str() {
+            "json" | "ndjson" => Self::Json,
+            "html" | "htm" => Self::Html,
+            _ => Self::Bytes,
+        }
+    }
+
+    fn apply(self, text: &str) -> String {
+        if text.is_ascii() {
+            return text.to_string();
+        }
+        let mut out = String::with_capacity(text.len() + 16);
+        for c in text.chars() {
+            if c.is_ascii() {
+                out.push(c);
+                continue;
+            }
+            match self {
+                Self::Bytes => {
+                    for b in c.encode_utf8(&mut [0; 4]).bytes() {
+                        out.push_str(&format!("\\x{b:02X}"));
+                    }
+                }
+                Self::Json => {
+                    for unit in c.encode_utf16(&mut [0; 2]) {
+                        out.push_str(&format!("\\u{unit:04x}"));
+                    }
+                }
+                Self::Html => out.push_str(&format!("&#{};", c as u32)),
+            }
+        }
+        out
+    }
+}
+
+/// Apply `AsciiEscape` when the caller asked for ASCII-only output.
+fn ascii_only_text(text: String, ascii_only: Option<bool>, format: &str) -> String {
+    if ascii_only.unwrap_or(false) {
+        AsciiEscape::for_format(format).apply(&text)
+    } else {
+        text
+    }
+}
+
+/// Format entries the same way the frontend exporters do ("json" | "csv" | "txt").
+fn format_entries(entries: &[LogEntry], format: &str) -> Result<String, AppError> {
+    match format {
+        "json" => Ok(entries_to_json(entries)),
+        "csv" => Ok(entries_to_csv(entries)),
+        "txt" => Ok(entries_to_text(entries)),
+        _ => Err(AppError::InvalidInput(format!("Unknown format: {format}"))),
+    }
+}
+
+/// One compact JSON object per line (same fields as `entries_to_json`), so a
+/// file can grow by appending and still parse line by line.
+fn entries_to_ndjson(entries: &[LogEntry]) -> String {
+    entries
+        .iter()
+        .filter_map(|e| serde_json::to_value(e).ok())
+        .map(|mut v| {
+            if let Some(obj) = v.as_object_mut() {
+                obj.remove("raw");
+            }
+            v.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Append entries newer than the last call for `path` ("ndjson" | "csv" | "txt";
+/// "json" means NDJSON here). The CSV header is only written into an empty
+/// file. Returns the last id now in the file, for the caller's next batch.
+#[tauri::command]
+async fn append_export(
+    app: AppHandle,
+    path: String,
+    entries: Vec<LogEntry>,
+    format: String,
+    line_ending: Option<String>,
+    ascii_only: Option<bool>,
+) -> Result<u64, AppError> {
+    use std::io::Write;
+
+    let line_ending = LineEnding::parse(line_ending.as_deref())?;
+    let state = app.state::<AppState>();
+    let mut exported = state.append_exports.lock().unwrap();
+    let last = exported.get(&path).copied();
+    let fresh: Vec<LogEntry> = entries
+        .into_iter()
+        .filter(|e| last.is_none_or(|last| e.id > last))
+        .collect();
+    let Some(newest) = fresh.iter().map(|e| e.id).max() else {
+        return Ok(last.unwrap_or(0));
+    };
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    let empty = file.metadata()?.len() == 0;
+    let mut text = match format.as_str() {
+        "json" | "ndjson" => entries_to_ndjson(&fresh),
+        "csv" if empty => entries_to_csv(&fresh),
+        "csv" => fresh.iter().map(csv_row).collect::<Vec<_>>().join("\n"),
+        "txt" => entries_to_text(&fresh),
+        _ => return Err(AppError::InvalidInput(format!("Unknown format: {format}"))),
+    };
+    text.push('\n');
+    let text = ascii_only_text(text, ascii_only, &format);
+    file.write_all(line_ending.apply(&text).as_bytes())?;
+    exported.insert(path, newest);
+    Ok(newest)
+}
+
+// ── HTML export ──
+
+/// `TAG_COLORS` from log-engine.js, so exported tags match the live view
+const TAG_PALETTE: [&str; TAG_PALETTE_SIZE as usize] = [
+    "#79c0ff", "#7ee787", "#ffa657", "#ff7b72", "#d2a8ff", "#56d4dd", "#f778ba", "#e3b341",
+    "#a5d6ff", "#ffd8b5",
+];
+
+fn html_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Page shell for `entries_to_html`. `{{title}}` and `{{rows}}` are filled in;
+/// the script only hides rows, so the report reads fine with scripts blocked.
+const HTML_EXPORT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{{title}}</title>
+<style>
+body { margin:0; background:#0d1117; color:#e6edf3; font:12px 'SF Mono', 'Cascadia Code', Menlo, monospace; }
+header { position:sticky; top:0; display:flex; gap:10px; align-items:center; padding:8px 12px; background:#161b22; border-bottom:1px solid #30363d; }
+header h1 { font-size:13px; font-weight:600; margin-right:auto; }
+header input[type=text] { background:#0d1117; color:inherit; border:1px solid #30363d; border-radius:4px; padding:4px 8px; font:inherit; width:240px; }
+.row { display:flex; gap:8px; padding:2px 12px; border-left:3px solid transparent; }
+.row.error { background:#3d1f1f22; border-left-color:#ff7b72; }
+.row.warn { background:#3d2e1a22; border-left-color:#ffa657; }
+.row.marker { background:#1a2d3d; border-left-color:#79c0ff; }
+.seq { color:#575f69; min-width:40px; text-align:right; }
+.ts { color:#56d4dd; opacity:.6; min-width:110px; }
+.lvl { min-width:36px; font-weight:600; text-transform:uppercase; }
+.lvl.error { color:#ff7b72; } .lvl.warn { color:#ffa657; } .lvl.info { color:#7ee787; } .lvl.debug { color:#8b949e; }
+.lvl.raw { color:#575f69; font-style:italic; } .lvl.marker { color:#79c0ff; }
+.tag { padding:0 5px; border-radius:3px; min-width:80px; }
+.msg { flex:1; white-space:pre-wrap; word-break:break-all; }
+.msg summary { cursor:pointer; }
+</style>
+</head>
+<body>
+<header>
+<h1>{{title}}</h1>
+<input type="text" id="q" placeholder="Filter text…">
+<label><input type="checkbox" class="lv" value="error" checked> error</label>
+<label><input type="checkbox" class="lv" value="warn" checked> warn</label>
+<label><input type="checkbox" class="lv" value="info" checked> info</label>
+<label><input type="checkbox" class="lv" value="debug" checked> debug</label>
+<label><input type="checkbox" class="lv" value="other" checked> other</label>
+</header>
+<main>
+{{rows}}
+</main>
+<script>
+const known = ['error', 'warn', 'info', 'debug'];
+function apply() {
+  const q = document.getElementById('q').value.toLowerCase();
+  const on = new Set([...document.querySelectorAll('.lv:checked')].map(c => c.value));
+  for (const row of document.querySelectorAll('.row')) {
+    const lvl = known.includes(row.dataset.level) ? row.dataset.level : 'other';
+    row.hidden = !on.has(lvl) || (q && !row.textContent.toLowerCase().includes(q));
+  }
+}
+document.querySelector('header').addEventListener('input', apply);
+</script>
+</body>
+</html>
+"#;
+
+/// Standalone report: levels and tags colored as in the viewer, multi-line
+/// messages collapsed to their first line, and a text/level filter bar.
+fn entries_to_html(entries: &[LogEntry], title: &str) -> String {
+    let mut rows = String::new();
+    for e in entries {
+        let level = html_escape(&e.level);
+        rows.push_str(&format!(
+            "<div class=\"row {level}\" data-level=\"{level}\"><span class=\"seq\">{}</span>",
+            e.id
+        ));
+        let ts = e
+            .device_timestamp
+            .clone()
+            .or_else(|| e.host_wallclock.clone())
+            .unwrap_or_default();
+        rows.push_str(&format!("<span class=\"ts\">{}</span>", html_escape(&ts)));
+        rows.push_str(&format!("<span class=\"lvl {level}\">{level}</span>"));
+        if let Some(tag) = &e.tag {
+            let color = TAG_PALETTE
+                [e.tag_color.unwrap_or_else(|| tag_color_index(tag)) as usize % TAG_PALETTE.len()];
+            rows.push_str(&format!(
+                "<span class=\"tag\" style=\"color:{color};background:{color}18\">{}</span>",
+                html_escape(tag)
+            ));
+        }
+        let message = if e.message.is_empty() {
+            &e.raw
+        } else {
+            &e.message
+        };
+        match message.split_once('\n') {
+            Some((first, rest)) => rows.push_str(&format!(
+                "<details class=\"msg\"><summary>{}</summary>{}</details></div>\n",
+                html_escape(first),
+                html_escape(rest)
+            )),
+            None => rows.push_str(&format!(
+                "<span class=\"msg\">{}</span></div>\n",
+                html_escape(message)
+            )),
+        }
+    }
+    HTML_EXPORT_TEMPLATE
+        .replace("{{title}}", &html_escape(title))
+        .replace("{{rows}}", &rows)
+}
+
+/// Write entries as a self-contained HTML report for people without the viewer.
+#[tauri::command]
+async fn export_logs_html(
+    path: String,
+    entries: Vec<LogEntry>,
+    ascii_only: Option<bool>,
+) -> Result<String, AppError> {
+    let title = format!("RTT log — {} entries", entries.len());
+    let html = ascii_only_text(entries_to_html(&entries, &title), ascii_only, "html");
+    std::fs::write(&path, html)?;
+    Ok(format!("Exported {} entries to {path}", entries.len()))
+}
+
+// ── SQLite export ──
+
+const SQLITE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS logs (
+    id INTEGER NOT NULL,
+    host_ts INTEGER,
+    device_ts TEXT,
+    level TEXT NOT NULL,
+    tag TEXT,
+    terminal INTEGER,
+    channel INTEGER,
+    message TEXT NOT NULL,
+    raw TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS logs_level ON logs(level);
+CREATE INDEX IF NOT EXISTS logs_tag ON logs(tag);
+";
+
+fn sqlite_error(e: rusqlite::Error) -> AppError {
+    AppError::Io(format!("SQLite: {e}"))
+}
+
+/// Insert `entries` into `logs` inside one transaction.
+fn insert_entries_sqlite(
+    conn: &mut rusqlite::Connection,
+    entries: &[LogEntry],
+) -> Result<(), AppError> {
+    let tx = conn.transaction().map_err(sqlite_error)?;
+    {
+        let mut stmt = tx
+            .prepare_cached(
+                "INSERT INTO logs (id, host_ts, device_ts, level, tag, terminal, channel, message, raw)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )
+            .map_err(sqlite_error)?;
+        for e in entries {
+            stmt.execute(rusqlite::params![
+                e.id as i64,
+                e.host_timestamp.map(|t| t as i64),
+                e.device_timestamp,
+                e.level,
+                e.tag,
+                e.terminal,
+                e.channel.map(|c| c as i64),
+                e.message,
+                e.raw,
+            ])
+            .map_err(sqlite_error)?;
+        }
+    }
+    tx.commit().map_err(sqlite_error)
+}
+
+/// Commit the live log once this many entries are pending...
+const SQLITE_BATCH: usize = 500;
+/// ...or once the oldest pending entry is this old
+const SQLITE_COMMIT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Live recording of parsed entries. WAL mode lets other tools query the file
+/// while it's written, and a crash loses at most one uncommitted batch.
+struct SqliteLog {
+    conn: rusqlite::Connection,
+    path: String,
+    pending: Vec<LogEntry>,
+    last_commit: std::time::Instant,
+}
+
+impl SqliteLog {
+    /// Appends to an existing database, so a reconnect keeps one record.
+    fn open(path: &str) -> Result<Self, AppError> {
+        let conn = rusqlite::Connection::open(path).map_err(sqlite_error)?;
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+            .map_err(sqlite_error)?;
+        conn.execute_batch(SQLITE_SCHEMA).map_err(sqlite_error)?;
+        Ok(Self {
+            conn,
+            path: path.to_string(),
+            pending: Vec::new(),
+            last_commit: std::time::Instant::now(),
+        })
+    }
+
+    fn push(&mut self, entry: &LogEntry) -> Result<(), AppError> {
+        self.pending.push(entry.clone());
+        if self.pending.len() >= SQLITE_BATCH {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush_if_due(&mut self) -> Result<(), AppError> {
+        if self.last_commit.elapsed() >= SQLITE_COMMIT_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), AppError> {
+        if !self.pending.is_empty() {
+            insert_entries_sqlite(&mut self.conn, &self.pending)?;
+            self.pending.clear();
         }
+        self.last_commit = std::time::Instant::now();
+        Ok(())
+    }
+}
+
+impl Drop for SqliteLog {
+    fn drop(&mut self) {
+        let _ = self.flush();
     }
+}
 
-    fn reset(&mut self) {
-        self.line_buf.clear();
+/// Run `f` on the live SQLite log, if one is open. A failure stops the
+/// recording (the session itself carries on) and says so in the log.
+fn with_sqlite_log(app: &AppHandle, f: impl FnOnce(&mut SqliteLog) -> Result<(), AppError>) {
+    let state = app.state::<AppState>();
+    let mut guard = state.sqlite_log.lock().unwrap();
+    let Some(db) = guard.as_mut() else {
+        return;
+    };
+    if let Err(e) = f(db) {
+        let path = db.path.clone();
+        *guard = None;
+        drop(guard);
+        emit_rtt_status(app, "error", &format!("Stopped recording to {path}: {e}"));
     }
+}
 
-    /// Parse raw RTT bytes, emit log entries. Returns Err if the app channel is closed.
-    fn process_bytes(&mut self, buf: &[u8], count: usize, app: &AppHandle) -> Result<(), ()> {
-        let mut i = 0;
-        while i < count {
-            match buf[i] {
-                0xFF => {
-                    i += 1;
-                    if i < count && buf[i].is_ascii_digit() {
-                        self.current_terminal = buf[i] - b'0';
-                        i += 1;
-                    }
-                }
-                0x1B => {
-                    // Skip ANSI escape sequence
-                    i += 1;
-                    if i < count && buf[i] == b'[' {
-                        i += 1;
-                        while i < count && !buf[i].is_ascii_alphabetic() {
-                            i += 1;
-                        }
-                        if i < count {
-                            i += 1;
-                        }
-                    }
-                }
-                b'\n' => {
-                    let line = self.line_buf.trim_end().to_string();
-                    self.line_buf.clear();
-                    i += 1;
+/// Write entries to a fresh SQLite database (replacing `path`) with a `logs`
+/// table indexed on level and tag, for ad-hoc SQL in any SQLite tool.
+#[tauri::command]
+async fn export_logs_sqlite(path: String, entries: Vec<LogEntry>) -> Result<String, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if std::path::Path::new(&path).exists() {
+            std::fs::remove_file(&path)?;
+        }
+        let mut conn = rusqlite::Connection::open(&path).map_err(sqlite_error)?;
+        conn.execute_batch(SQLITE_SCHEMA).map_err(sqlite_error)?;
+        insert_entries_sqlite(&mut conn, &entries)?;
+        Ok(format!("Exported {} entries to {path}", entries.len()))
+    })
+    .await
+    .map_err(|e| AppError::Other(format!("SQLite export task failed: {e}")))?
+}
 
-                    if line.is_empty() {
-                        continue;
-                    }
+#[tauri::command]
+async fn copy_to_clipboard(
+    app: AppHandle,
+    entries: Vec<LogEntry>,
+    format: String,
+    line_ending: Option<String>,
+    ascii_only: Option<bool>,
+) -> Result<String, AppError> {
+    let line_ending = LineEnding::parse(line_ending.as_deref())?;
+    let text = ascii_only_text(format_entries(&entries, &format)?, ascii_only, &format);
+    let text = line_ending.apply(&text);
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| AppError::Other(format!("Failed to write clipboard: {e}")))?;
+    Ok(format!("Copied {} entries", entries.len()))
+}
 
-                    let mut entry = parse_line(&line);
-                    entry.terminal = Some(self.current_terminal);
-                    if app.emit("rtt-log", &entry).is_err() {
-                        return Err(());
-                    }
-                }
-                b if b < 0x20 && b != b'\r' && b != b'\t' => {
-                    i += 1;
-                }
-                _ => {
-                    self.line_buf.push(buf[i] as char);
-                    i += 1;
-                }
+#[tauri::command]
+async fn read_text_file(path: String) -> Result<String, AppError> {
+    Ok(std::fs::read_to_string(&path)?)
+}
+
+/// `ascii_only` picks its escaping from the file extension, see `AsciiEscape`.
+#[tauri::command]
+async fn write_text_file(
+    path: String,
+    contents: String,
+    line_ending: Option<String>,
+    ascii_only: Option<bool>,
+) -> Result<(), AppError> {
+    let extension = std::path::Path::new(&path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    let contents = ascii_only_text(contents, ascii_only, extension);
+    let contents = match line_ending {
+        Some(le) => LineEnding::parse(Some(&le))?.apply(&contents),
+        None => contents,
+    };
+    Ok(std::fs::write(&path, &contents)?)
+}
+
+// ── Command line ──
+
+const CLI_USAGE: &str = "\
+Usage: rtt-viewer [--profile NAME | --chip NAME [--rtt-address HEX] [--core N] | --mock | --mock-jitter]
+                  [--probe N | --probe-serial SERIAL] [--headless]
+
+  --profile NAME        Connect with a saved profile on startup
+  --chip NAME           probe-rs target name, e.g. nRF52840_xxAA (overrides the profile's)
+  --rtt-address HEX     Control block address (default: scan RAM)
+  --core N              Core index (default 0)
+  --mock                Start the mock source instead of a probe
+  --mock-jitter         Same, with duplicate, out-of-order and wrapping timestamps
+  --probe N             Probe index from the probe list (default 0)
+  --probe-serial SERIAL Pick the probe by serial number
+  --headless            Print log entries as JSON lines on stdout instead of opening the GUI";
+
+#[derive(Debug, Clone, Default)]
+struct CliArgs {
+    headless: bool,
+    mock: bool,
+    mock_jitter: bool,
+    profile: Option<String>,
+    chip: Option<String>,
+    rtt_address: Option<String>,
+    core: Option<usize>,
+    probe: Option<usize>,
+    probe_serial: Option<String>,
+}
+
+impl CliArgs {
+    fn wants_session(&self) -> bool {
+        self.mock || self.profile.is_some() || self.chip.is_some()
+    }
+
+    /// The saved profile named by --profile, with --chip/--rtt-address/--core
+    /// laid over it; or an unsaved one built from the flags alone.
+    fn resolve_profile(&self) -> Result<Option<Profile>, AppError> {
+        let mut profile = match &self.profile {
+            Some(name) => load_profiles()
+                .into_iter()
+                .find(|p| &p.name == name)
+                .ok_or_else(|| AppError::InvalidInput(format!("Profile '{name}' not found")))?,
+            None => match &self.chip {
+                Some(chip) => Profile {
+                    name: chip.clone(),
+                    chip: chip.clone(),
+                    ..Default::default()
+                },
+                None => return Ok(None),
+            },
+        };
+        if let Some(chip) = &self.chip {
+            profile.chip = chip.clone();
+        }
+        if self.rtt_address.is_some() {
+            profile.rtt_address = self.rtt_address.clone();
+        }
+        if self.core.is_some() {
+            profile.core = self.core;
+        }
+        Ok(Some(profile))
+    }
+
+    fn resolve_probe_index(&self) -> Result<Option<usize>, AppError> {
+        let Some(serial) = &self.probe_serial else {
+            return Ok(self.probe);
+        };
+        get_lister()
+            .list_all()
+            .iter()
+            .position(|p| p.serial_number.as_deref() == Some(serial))
+            .map(Some)
+            .ok_or_else(|| AppError::NoProbe(format!("No probe with serial '{serial}'")))
+    }
+}
+
+fn parse_cli_args(mut args: impl Iterator<Item = String>) -> Result<CliArgs, String> {
+    let mut cli = CliArgs::default();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or_else(|| format!("{flag} needs a value"));
+        match arg.as_str() {
+            "--headless" => cli.headless = true,
+            "--mock" => cli.mock = true,
+            "--mock-jitter" => {
+                cli.mock = true;
+                cli.mock_jitter = true;
+            }
+            "--profile" => cli.profile = Some(value("--profile")?),
+            "--probe-serial" => cli.probe_serial = Some(value("--probe-serial")?),
+            "--chip" => cli.chip = Some(value("--chip")?),
+            "--rtt-address" => {
+                let addr = value("--rtt-address")?;
+                validate_rtt_address_str(&addr).map_err(|e| format!("--rtt-address: {e}"))?;
+                cli.rtt_address = Some(addr);
             }
+            "--core" => {
+                let n = value("--core")?;
+                cli.core = Some(
+                    n.parse()
+                        .map_err(|_| format!("--core: '{n}' is not a number"))?,
+                );
+            }
+            "--probe" => {
+                let n = value("--probe")?;
+                cli.probe = Some(
+                    n.parse()
+                        .map_err(|_| format!("--probe: '{n}' is not a number"))?,
+                );
+            }
+            "-h" | "--help" => return Err(String::new()),
+            // Launchers sometimes pass their own single-dash args (e.g. macOS -psn_*)
+            other if other.starts_with("--") => return Err(format!("Unknown option {other}")),
+            _ => {}
         }
-        Ok(())
     }
+    if cli.probe.is_some() && cli.probe_serial.is_some() {
+        return Err("Use either --probe or --probe-serial, not both".to_string());
+    }
+    if cli.mock && (cli.headless || cli.profile.is_some() || cli.chip.is_some()) {
+        return Err("--mock can't be combined with a probe session".to_string());
+    }
+    Ok(cli)
 }
 
-// ── RTT read loop — returns when connection is lost or user stops ──
+/// What the command line asked the GUI to start, handed to the frontend once.
+#[derive(Debug, Clone, Serialize)]
+struct StartupRequest {
+    mock: bool,
+    mock_jitter: bool,
+    profile: Option<Profile>,
+    probe_index: Option<usize>,
+}
 
-enum ReadResult {
-    Disconnected, // connection lost, should reconnect
-    Stopped,      // user requested stop
-    AppClosed,    // webview gone
+/// Taken once so a webview reload doesn't start the session again.
+#[tauri::command]
+async fn take_startup(app: AppHandle) -> Result<Option<StartupRequest>, AppError> {
+    let Some(cli) = app.state::<AppState>().startup.lock().unwrap().take() else {
+        return Ok(None);
+    };
+    let resolved = cli
+        .resolve_profile()
+        .and_then(|profile| Ok((profile, cli.resolve_probe_index()?)));
+    match resolved {
+        Ok((profile, probe_index)) => Ok(Some(StartupRequest {
+            mock: cli.mock,
+            mock_jitter: cli.mock_jitter,
+            profile,
+            probe_index,
+        })),
+        Err(e) => {
+            emit_rtt_status(&app, "error", &format!("Startup: {e}"));
+            Ok(None)
+        }
+    }
 }
 
-fn rtt_read_loop(
-    rtt: &mut probe_rs::rtt::Rtt,
-    core: &mut probe_rs::Core<'_>,
-    parser: &mut RttParser,
-    stop_flag: &Arc<AtomicBool>,
-    app: &AppHandle,
-) -> ReadResult {
-    let mut buf = [0u8; 4096];
-    let mut consecutive_errors = 0u32;
+/// Stream entries to stdout with the GUI's session loop, reconnects included,
+/// until stdout closes, the idle timeout runs out or a fatal error.
+fn run_headless(cli: &CliArgs) -> Result<(), AppError> {
+    let profile = cli.resolve_profile()?.ok_or_else(|| {
+        AppError::InvalidInput("--headless needs --profile or --chip".to_string())
+    })?;
+    if let Some(path) = &profile.target_yaml {
+        register_target_yaml(path, &profile.chip)?;
+    }
 
-    loop {
-        if stop_flag.load(Ordering::Relaxed) {
-            emit_rtt_status(app, "info", "Disconnected by user.");
-            return ReadResult::Stopped;
+    // --probe/--probe-serial on the command line win over the profile's probe
+    let probe_index = {
+        let cli = cli.clone();
+        enumerate_on_runtime(move || cli.resolve_probe_index())?
+    };
+    let probe_info = match (probe_index, profile.probe_serial.clone()) {
+        (Some(idx), _) => enumerate_on_runtime(move || select_probe(idx))?,
+        (None, Some(serial)) => {
+            let listed = serial.clone();
+            enumerate_on_runtime(move || find_probe_by_serial(&listed))
+                .ok_or_else(|| AppError::NoProbe(format!("No probe with serial '{serial}'")))?
         }
+        (None, None) => enumerate_on_runtime(|| select_probe(0))?,
+    };
+    // Nothing sends commands without a GUI
+    let (_commands_tx, commands_rx) = std::sync::mpsc::channel();
+    run_rtt_session(
+        LogSink::Stdout,
+        profile,
+        probe_info,
+        &Arc::new(AtomicBool::new(false)),
+        commands_rx,
+    )
+}
 
-        let mut got_data = false;
+fn main() {
+    let cli = match parse_cli_args(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        // Empty message means --help
+        Err(e) if e.is_empty() => {
+            println!("{CLI_USAGE}");
+            return;
+        }
+        Err(e) => {
+            eprintln!("{e}\n\n{CLI_USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if cli.headless {
+        if let Err(e) = run_headless(&cli) {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+    let startup = cli.wants_session().then_some(cli);
+    let emit_filter = load_filter_presets()
+        .active_filter()
+        .map(EmitFilter::from_preset)
+        .unwrap_or_default();
 
-        if let Some(ch) = rtt.up_channels().into_iter().next() {
-            match ch.read(core, &mut buf) {
-                Ok(count) if count > 0 => {
-                    got_data = true;
-                    consecutive_errors = 0;
-                    if parser.process_bytes(&buf, count, app).is_err() {
-                        return ReadResult::AppClosed;
-                    }
-                }
-                Ok(_) => {}
-                Err(e) => {
-                    consecutive_errors += 1;
-                    if consecutive_errors >= 3 {
-                        emit_rtt_status(
-                            app,
-                            "warn",
-                            &format!("Lost connection: {e}. Reconnecting..."),
-                        );
-                        parser.reset();
-                        return ReadResult::Disconnected;
-                    }
-                    std::thread::sleep(std::time::Duration::from_millis(100));
+    tauri::Builder::default()
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .manage(AppState {
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            emit_filter: Mutex::new(emit_filter),
+            rtt_running: AtomicBool::new(false),
+            active_profile: Mutex::new(None),
+            hot_reload: AtomicBool::new(false),
+            reattach: AtomicBool::new(false),
+            elf_watcher: Mutex::new(None),
+            elf_parse_gen: AtomicU64::new(0),
+            probe_caps: Mutex::new(HashMap::new()),
+            session_probe: Mutex::new(None),
+            startup: Mutex::new(startup),
+            ws_server: Mutex::new(None),
+            syslog: Mutex::new(None),
+            mqtt: Mutex::new(None),
+            rtt_commands: Mutex::new(None),
+            time_base: Mutex::new(TimeBase::default()),
+            status_routing: Mutex::new(StatusRouting::default()),
+            search_index: Mutex::new(SearchIndex::default()),
+            sqlite_log: Mutex::new(None),
+            error_threshold: AtomicU32::new(DEFAULT_ERROR_THRESHOLD),
+            append_exports: Mutex::new(HashMap::new()),
+            read_mode: Mutex::new(ReadMode::default()),
+            emit_batching: Mutex::new(EmitBatching::default()),
+            views: Mutex::new(Vec::new()),
+            flight_recorder: Mutex::new(None),
+            connect_timing: Mutex::new(None),
+            last_error: Mutex::new(Vec::new()),
+            control_blocks: Mutex::new(Vec::new()),
+            svd: Mutex::new(None),
+        })
+        .setup(|app| {
+            let file_menu = SubmenuBuilder::new(app.handle(), "File")
+                .text("import", "Import Logs…")
+                .text("replay", "Replay Session…")
+                .text("diff", "Diff Against Session…")
+                .separator()
+                .quit()
+                .build()?;
+            let export_menu = SubmenuBuilder::new(app.handle(), "Export")
+                .text("export_json", "Export as JSON…")
+                .text("export_csv", "Export as CSV…")
+                .text("export_txt", "Export as Text…")
+                .text("export_sqlite", "Export as SQLite…")
+                .text("export_html", "Export as HTML…")
+                .text("export_continuous", "Export Continuously…")
+                .separator()
+                .text("copy_json", "Copy Visible as JSON")
+                .text("copy_csv", "Copy Visible as CSV")
+                .text("copy_txt", "Copy Visible as Text")
+                .build()?;
+            let tools_menu = SubmenuBuilder::new(app.handle(), "Tools")
+                .text("save_filter_preset", "Save Filter Preset…")
+                .text("apply_filter_preset", "Apply Filter Preset…")
+                .text("create_view", "Open Filtered View…")
+                .text("log_timeline", "Log Volume Timeline")
+                .text("measure_latency", "Measure Latency…")
+                .text("flight_recorder", "Flight Recorder…")
+                .separator()
+                .text("record_sqlite", "Record Live to SQLite…")
+                .text("remote_server", "Remote Viewing Server…")
+                .text("gdb_server", "GDB Server…")
+                .text("syslog", "Forward to Syslog…")
+                .text("mqtt", "Publish to MQTT…")
+                .text("benchmark", "Benchmark Throughput…")
+                .build()?;
+            let target_menu = SubmenuBuilder::new(app.handle(), "Target")
+                .text("read_device_id", "Read Device ID")
+                .text("control_blocks", "Control Blocks…")
+                .text("read_register", "Read Peripheral Register…")
+                .text("poke_memory", "Write Memory…")
+                .text("cycle_counter", "Cycle Counter…")
+                .text("unwind_fault", "Fault Backtrace")
+                .text("verify_firmware", "Verify Firmware Against ELF")
+                .text("enter_bootloader", "Restart into Bootloader")
+                .separator()
+                .text("last_error", "Last Connection Error…")
+                .build()?;
+            let settings_menu = SubmenuBuilder::new(app.handle(), "Settings")
+                .text("line_ending", "Export Line Endings…")
+                .text("ascii_only", "ASCII-Only Exports…")
+                .text("status_in_log", "Status Messages in Log…")
+                .separator()
+                .text("error_threshold", "Disconnect After Errors…")
+                .text("read_mode", "Read Mode…")
+                .text("emit_batching", "Render Batching…")
+                .build()?;
+
+            let menu = MenuBuilder::new(app.handle())
+                .items(&[
+                    &file_menu,
+                    &export_menu,
+                    &tools_menu,
+                    &target_menu,
+                    &settings_menu,
+                ])
+                .build()?;
+
+            app.set_menu(menu)?;
+
+            if let Some(window) = app.get_webview_window("main") {
+                restore_window_geometry(&window);
+            }
+            Ok(())
+        })
+        .on_menu_event(|app, event| {
+            let _ = app.emit("menu-event", event.id().0.as_str());
+        })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                // Nowhere left to show it
+                if let Err(e) = save_window_geometry(window) {
+                    eprintln!("{e}");
                 }
             }
+        })
+        .invoke_handler(tauri::generate_handler![
+            start_rtt,
+            restart_rtt,
+            recover_target,
+            rtt_ping,
+            add_watch,
+            remove_watch,
+            set_log_trigger,
+            clear_log_trigger,
+            core_snapshot,
+            unwind_fault,
+            start_gdb_server,
+            stop_gdb_server,
+            benchmark_rtt,
+            channel_fill,
+            start_mock,
+            take_startup,
+            start_server,
+            stop_server,
+            log_to_syslog,
+            stop_syslog,
+            log_to_mqtt,
+            stop_mqtt,
+            replay_session,
+            diff_sessions,
+            start_swo,
+            start_fifo,
+            stop_source,
+            set_terminal_filter,
+            set_channel_filter,
+            save_filter_preset,
+            list_filter_presets,
+            apply_filter_preset,
+            delete_filter_preset,
+            set_time_base,
+            set_status_in_log,
+            set_error_threshold,
+            set_read_mode,
+            set_emit_batching,
+            create_view,
+            list_views,
+            close_view,
+            arm_flight_recorder,
+            disarm_flight_recorder,
+            dump_flight_recorder,
+            insert_marker,
+            search_logs,
+            set_search_index,
+            clear_search_index,
+            perf_counters,
+            last_connect_timing,
+            last_error_detail,
+            list_control_blocks,
+            poke_memory,
+            log_histogram,
+            measure_latency,
+            reset_perf,
+            list_probes,
+            get_profiles,
+            validate_rtt_address,
+            load_ui_state,
+            save_ui_state,
+            save_profile,
+            delete_profile,
+            rename_profile,
+            duplicate_profile,
+            list_example_profiles,
+            install_example_profile,
+            extract_rtt_address_from_elf,
+            cancel_elf_parse,
+            recent_elfs,
+            extract_rtt_address_from_map,
+            suggest_chips_for_elf,
+            list_chip_families,
+            read_text_file,
+            write_text_file,
+            copy_to_clipboard,
+            export_logs_sqlite,
+            export_logs_html,
+            append_export,
+            default_export_path,
+            enter_bootloader,
+            read_device_id,
+            read_cycle_counter,
+            reset_cycle_counter,
+            verify_firmware_match,
+            load_svd,
+            read_register,
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        // Window close and the Quit menu item both end up here
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                shutdown(app);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // ── Read mode ──
+
+    /// Reads a mock channel the way `rtt_read_loop` does, drain then pace, on
+    /// a simulated clock that advances by the sleeps `IdleBackoff` asks for.
+    /// The device writes bursts of lines 2 ms apart with quiet gaps between,
+    /// like a device logging an event. Returns the mean time from write to read.
+    fn mock_read_latency(mode: ReadMode) -> Duration {
+        let writes: Vec<Duration> = (0..8u32)
+            .flat_map(|burst| {
+                (0..5u32).map(move |line| Duration::from_millis((burst * 50 + line * 2).into()))
+            })
+            .collect();
+        // One read pass over the probe
+        let pass = Duration::from_micros(200);
+        let mut backoff = IdleBackoff::default();
+        let (mut now, mut read) = (Duration::ZERO, 0);
+        let mut latencies = Vec::new();
+        while read < writes.len() {
+            now += pass;
+            let ready = writes[read..].iter().take_while(|&&at| at <= now).count();
+            latencies.extend(writes[read..read + ready].iter().map(|&at| now - at));
+            read += ready;
+            now += backoff.idle(mode, ready > 0).unwrap_or_default();
+        }
+        latencies.iter().sum::<Duration>() / latencies.len() as u32
+    }
+
+    #[test]
+    fn block_mode_cuts_read_latency() {
+        let poll = mock_read_latency(ReadMode::Poll);
+        let block = mock_read_latency(ReadMode::Block);
+        assert!(
+            block < poll * 3 / 4,
+            "block {block:?} should beat poll {poll:?}"
+        );
+    }
+
+    #[test]
+    fn block_mode_backs_off_when_quiet() {
+        let mut backoff = IdleBackoff::default();
+        let idle: Vec<_> = (0..7)
+            .map(|_| backoff.idle(ReadMode::Block, false).unwrap())
+            .collect();
+        let ms = Duration::from_millis;
+        assert_eq!(
+            idle,
+            [
+                Duration::from_micros(500),
+                ms(1),
+                ms(2),
+                ms(4),
+                ms(8),
+                POLL_INTERVAL,
+                POLL_INTERVAL
+            ]
+        );
+        // Data starts it over
+        assert_eq!(backoff.idle(ReadMode::Block, true), None);
+        assert_eq!(backoff.idle(ReadMode::Block, false), Some(BACKOFF_MIN));
+        assert_eq!(backoff.idle(ReadMode::Poll, false), Some(POLL_INTERVAL));
+    }
+
+    // ── Profiles ──
+
+    fn named_profiles(names: &[&str]) -> Vec<Profile> {
+        names
+            .iter()
+            .map(|name| Profile {
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn profile_names(profiles: &[Profile]) -> Vec<&str> {
+        profiles.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn rename_profile_in_place() {
+        let mut profiles = named_profiles(&["app", "net", "bootloader"]);
+        let renamed = rename_in(&mut profiles, "net", "  net-core ").unwrap();
+        assert_eq!(renamed.as_deref(), Some("net-core"));
+        assert_eq!(profile_names(&profiles), ["app", "net-core", "bootloader"]);
+        assert!(rename_in(&mut profiles, "missing", "other").is_err());
+    }
+
+    #[test]
+    fn rename_profile_collisions() {
+        let mut profiles = named_profiles(&["app", "net"]);
+        for taken in ["app", " app ", ""] {
+            assert!(rename_in(&mut profiles, "net", taken).is_err(), "{taken:?}");
         }
+        assert_eq!(profile_names(&profiles), ["app", "net"]);
+    }
 
-        if !got_data {
-            std::thread::sleep(std::time::Duration::from_millis(10));
+    #[test]
+    fn rename_profile_to_itself() {
+        let mut profiles = named_profiles(&["app", "net"]);
+        for same in ["net", " net"] {
+            assert_eq!(rename_in(&mut profiles, "net", same).unwrap(), None);
         }
+        assert_eq!(profile_names(&profiles), ["app", "net"]);
     }
-}
 
-// ── Main command ──
+    // ── Status routing ──
 
-#[tauri::command]
-async fn start_rtt(
-    app: AppHandle,
-    chip: String,
-    rtt_address: Option<String>,
-    core_index: Option<usize>,
-    probe_index: Option<usize>,
-) -> Result<String, String> {
-    let state = app.state::<AppState>();
-    state.stop_flag.store(false, Ordering::Relaxed);
-    let stop_flag = state.stop_flag.clone();
-    let core_idx = core_index.unwrap_or(0);
-    let probe_idx = probe_index.unwrap_or(0);
+    #[test]
+    fn status_routing() {
+        use StatusRoute::*;
+        let bar_only = StatusRouting::default();
+        assert_eq!(bar_only.route("info", false), Bar);
+        assert_eq!(bar_only.route("info", true), Bar);
 
-    // Enumerate on Tauri async thread — safe for macOS HID
-    let lister = probe_rs::probe::list::Lister::new();
-    let probes = lister.list_all();
-    if probes.is_empty() {
-        return Err("No debug probes found".to_string());
+        // quiet_connect works whether or not status lines go in the log
+        for in_log in [false, true] {
+            let quiet = StatusRouting {
+                in_log,
+                quiet_connect: true,
+            };
+            let shown = if in_log { BarAndLog } else { Bar };
+            assert_eq!(quiet.route("info", true), Hidden);
+            assert_eq!(quiet.route("warn", true), shown);
+            assert_eq!(quiet.route("error", true), shown);
+            assert_eq!(quiet.route("info", false), shown);
+        }
     }
-    if probe_idx >= probes.len() {
-        return Err(format!(
-            "Probe index {probe_idx} out of range (found {})",
-            probes.len()
-        ));
+
+    // ── Line parsing ──
+
+    /// (level, device timestamp, tag, message) of a parsed line
+    fn parsed(line: &str) -> (String, Option<String>, Option<String>, String) {
+        let e = parse_line(line, false);
+        (e.level, e.device_timestamp, e.tag, e.message)
     }
-    let probe_info = probes[probe_idx].clone();
-    drop(lister);
 
-    let msg = format!("RTT connecting ({chip}, core {core_idx}, probe {probe_idx})...");
+    fn owned(
+        level: &str,
+        timestamp: Option<&str>,
+        tag: Option<&str>,
+        message: &str,
+    ) -> (String, Option<String>, Option<String>, String) {
+        (
+            level.to_string(),
+            timestamp.map(str::to_string),
+            tag.map(str::to_string),
+            message.to_string(),
+        )
+    }
 
-    std::thread::spawn(move || {
-        let scan_region = parse_scan_region(&rtt_address);
-        let mut parser = RttParser::new();
+    #[test]
+    fn zephyr_lines() {
+        assert_eq!(
+            parsed("[00:00:01.234,567] <inf> main: Booting Zephyr OS build v3.5.0"),
+            owned(
+                "info",
+                Some("00:00:01.234,567"),
+                Some("main"),
+                "Booting Zephyr OS build v3.5.0"
+            )
+        );
+        assert_eq!(
+            parsed("[00:00:02.000,000] <wrn> bt_hci_core: No ID address"),
+            owned(
+                "warn",
+                Some("00:00:02.000,000"),
+                Some("bt_hci_core"),
+                "No ID address"
+            )
+        );
+    }
 
-        loop {
-            if stop_flag.load(Ordering::Relaxed) {
-                break;
+    #[test]
+    fn log_crate_lines() {
+        // rtt-target's log adapter, with the uptime in seconds
+        assert_eq!(
+            parsed("[0.123456 INFO app::net] link up, 100 Mbit/s"),
+            owned(
+                "info",
+                Some("00:00:00.123,456"),
+                Some("app::net"),
+                "link up, 100 Mbit/s"
+            )
+        );
+        // env_logger's padded level and wall-clock time: not a device timestamp
+        assert_eq!(
+            parsed("[2024-05-01T12:34:56Z WARN  sensor::bme280] temperature 81 C"),
+            owned("warn", None, Some("sensor::bme280"), "temperature 81 C")
+        );
+        assert_eq!(
+            parsed("[TRACE embassy_net::tcp] poll"),
+            owned("debug", None, Some("embassy_net::tcp"), "poll")
+        );
+        let kept = parse_line("[3725.5 ERROR app] bus fault", true);
+        assert_eq!(kept.message, "[app] bus fault");
+        assert_eq!(kept.device_timestamp.as_deref(), Some("01:02:05.500,000"));
+    }
+
+    #[test]
+    fn defmt_print_lines() {
+        assert_eq!(
+            parsed("0.002685 INFO  Hello, world!"),
+            owned("info", Some("00:00:00.002,685"), None, "Hello, world!")
+        );
+        assert_eq!(
+            parsed("12.000122 ERROR panicked at 'index out of bounds'"),
+            owned(
+                "error",
+                Some("00:00:12.000,122"),
+                None,
+                "panicked at 'index out of bounds'"
+            )
+        );
+        // The location line defmt-print prints under each message
+        assert_eq!(
+            parse_line("└─ hello::__cortex_m_rt_main @ src/bin/hello.rs:8", false).level,
+            "raw"
+        );
+    }
+
+    #[test]
+    fn rtt_logger_lines() {
+        assert_eq!(
+            parsed("INFO - Booting"),
+            owned("info", None, None, "Booting")
+        );
+        assert_eq!(
+            parsed("WARN - stack at 90%"),
+            owned("warn", None, None, "stack at 90%")
+        );
+        assert_eq!(
+            parsed("DEBUG - adc=512"),
+            owned("debug", None, None, "adc=512")
+        );
+    }
+
+    #[test]
+    fn unrecognised_lines_stay_raw() {
+        for line in ["hello", "INFO", "[ok] done", "1.5 apples", "WARN -"] {
+            let entry = parse_line(line, false);
+            assert_eq!(entry.level, "raw", "{line}");
+            assert_eq!(entry.message, line);
+        }
+    }
+
+    // ── Compressed channels ──
+
+    fn frame(payload: &[u8]) -> Vec<u8> {
+        let mut out = (payload.len() as u16).to_le_bytes().to_vec();
+        out.extend_from_slice(payload);
+        out
+    }
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // Raw blocks cut from `lz4 -c` (lz4 1.9.4) frames
+    const LZ4_TICKS: &[u8] = b"INF tick tick tick tick tick tick tick tick\n";
+    /// `LZ4_TICKS`: eight literals, a 31-byte match at offset 5, five literals
+    const LZ4_TICKS_BLOCK: &str = "8f494e46207469636b05000c507469636b0a";
+    const LZ4_DICT: &[u8] = b"ERR adc: sample 3 out of range, sample 3 out of range\n";
+    const LZ4_DICT_BLOCK: &str =
+        "fe10455252206164633a2073616d706c652033206f7574206f662072616e67652c170050616e67650a";
+    const LZ4_AFTER_DICT: &[u8] =
+        b"INF tick tick tick tick tick tick tick tick\nERR adc: sample 3 out of range\nERR adc: sample 4 out of range\n";
+    /// `LZ4_AFTER_DICT` compressed with `-D` on `LZ4_DICT`: its matches reach
+    /// back past the start of the block
+    const LZ4_AFTER_DICT_BLOCK: &str =
+        "8f494e46207469636b0500101f0a62000b0d1f001534810050616e67650a";
+
+    #[test]
+    fn lz4_overlapping_match() {
+        let mut out = Vec::new();
+        lz4_block_decode(&unhex(LZ4_TICKS_BLOCK), &mut out).unwrap();
+        assert_eq!(out, LZ4_TICKS);
+    }
+
+    #[test]
+    fn lz4_refers_back_into_earlier_frames() {
+        let mut decoder = FrameDecoder::new(ChannelCodec::Lz4);
+        let mut stream = frame(&unhex(LZ4_DICT_BLOCK));
+        stream.extend(frame(&unhex(LZ4_AFTER_DICT_BLOCK)));
+        let (out, bad) = decoder.push(&stream);
+        assert_eq!(bad, 0);
+        assert_eq!(out, [LZ4_DICT, LZ4_AFTER_DICT].concat());
+    }
+
+    #[test]
+    fn frames_split_across_reads() {
+        let mut stream = frame(&unhex(LZ4_DICT_BLOCK));
+        stream.extend(frame(&unhex(LZ4_AFTER_DICT_BLOCK)));
+        for chunk in [1, 2, 3, 7] {
+            let mut decoder = FrameDecoder::new(ChannelCodec::Lz4);
+            let mut out = Vec::new();
+            for piece in stream.chunks(chunk) {
+                let (bytes, bad) = decoder.push(piece);
+                assert_eq!(bad, 0);
+                out.extend(bytes);
             }
+            assert_eq!(
+                out,
+                [LZ4_DICT, LZ4_AFTER_DICT].concat(),
+                "{chunk}-byte reads"
+            );
+            assert!(decoder.partial.is_empty());
+        }
+    }
 
-            let probe = match probe_info.open() {
-                Ok(p) => p,
-                Err(e) => {
-                    emit_rtt_status(
-                        &app,
-                        "warn",
-                        &format!("Probe open failed: {e}. Retrying in 3s..."),
-                    );
-                    std::thread::sleep(std::time::Duration::from_secs(3));
-                    continue;
+    #[test]
+    fn corrupt_lz4_frame_is_dropped() {
+        let mut decoder = FrameDecoder::new(ChannelCodec::Lz4);
+        // Without the dictionary frame first, the matches point before the start
+        let after_dict = unhex(LZ4_AFTER_DICT_BLOCK);
+        let mut truncated = unhex(LZ4_TICKS_BLOCK);
+        truncated.truncate(10);
+        let mut stream = frame(&after_dict);
+        stream.extend(frame(&truncated));
+        stream.extend(frame(&unhex(LZ4_TICKS_BLOCK)));
+        let (out, bad) = decoder.push(&stream);
+        assert_eq!(bad, (after_dict.len() + truncated.len()) as u64);
+        assert_eq!(out, LZ4_TICKS);
+    }
+
+    /// A heatshrink token: a literal byte, or (offset back, byte count)
+    enum Hs {
+        Lit(u8),
+        Ref(usize, usize),
+    }
+
+    /// Assemble a heatshrink stream bit by bit, zero-padded to a whole byte.
+    fn heatshrink_stream(tokens: &[Hs], window_bits: u8, lookahead_bits: u8) -> Vec<u8> {
+        let mut bits = Vec::new();
+        let mut put = |value: usize, n: u8| {
+            bits.extend((0..n).rev().map(|i| (value >> i) & 1 == 1));
+        };
+        for token in tokens {
+            match *token {
+                Hs::Lit(b) => {
+                    put(1, 1);
+                    put(b as usize, 8);
                 }
-            };
+                Hs::Ref(offset, count) => {
+                    put(0, 1);
+                    put(offset - 1, window_bits);
+                    put(count - 1, lookahead_bits);
+                }
+            }
+        }
+        bits.chunks(8)
+            .map(|byte| {
+                byte.iter()
+                    .enumerate()
+                    .fold(0, |acc, (i, &bit)| acc | (u8::from(bit) << (7 - i)))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn heatshrink_stream_layout() {
+        // "aaaaa" at 8,7: a literal, then a four-byte copy of itself
+        let stream = heatshrink_stream(&[Hs::Lit(b'a'), Hs::Ref(1, 4)], 8, 7);
+        assert_eq!(stream, [0xB0, 0x80, 0x01, 0x80]);
+        assert_eq!(heatshrink_decode(&stream, 8, 7), b"aaaaa");
+    }
+
+    #[test]
+    fn heatshrink_default_parameters() {
+        let mut tokens: Vec<Hs> = b"tick ".iter().map(|&b| Hs::Lit(b)).collect();
+        // Overlapping copy, the longest a 4-bit lookahead allows
+        tokens.push(Hs::Ref(5, 16));
+        tokens.push(Hs::Lit(b'\n'));
+        // Reaching back before the first byte reads the zeroed window
+        tokens.push(Hs::Ref(30, 2));
+        let stream = heatshrink_stream(&tokens, 8, 4);
+        let mut expected = b"tick tick tick tick t\n".to_vec();
+        expected.extend([0, 0]);
+        assert_eq!(heatshrink_decode(&stream, 8, 4), expected);
+    }
+
+    #[test]
+    fn heatshrink_wide_window() {
+        let line: Vec<u8> = (0..300u16).map(|i| b'a' + (i % 26) as u8).collect();
+        let mut tokens: Vec<Hs> = line.iter().map(|&b| Hs::Lit(b)).collect();
+        // An offset past 8 bits and a count past 4
+        tokens.push(Hs::Ref(300, 32));
+        let codec = ChannelCodec::parse("heatshrink:10,5").unwrap().unwrap();
+        let ChannelCodec::Heatshrink {
+            window_bits,
+            lookahead_bits,
+        } = codec
+        else {
+            unreachable!()
+        };
+        let stream = heatshrink_stream(&tokens, window_bits, lookahead_bits);
+
+        let mut decoder = FrameDecoder::new(codec);
+        let mut out = Vec::new();
+        for piece in frame(&stream).chunks(5) {
+            out.extend(decoder.push(piece).0);
+        }
+        assert_eq!(out, [&line[..], &line[..32]].concat());
+    }
+
+    // ── Byte parser ──
+
+    /// xorshift64*, so a failing seed can be replayed
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    /// Mostly the bytes the parser treats specially, and pieces of UTF-8
+    fn fuzz_byte(rng: &mut Rng) -> u8 {
+        const SPECIAL: &[u8] = &[
+            0xFF, 0x1B, b'[', b'0', b'9', b'A', b'F', b'm', b'\n', b'\r', b'\t', 0x00, 0x07, 0x7F,
+            0xC2, 0x80, 0x9F, 0xE2, 0x82, 0xAC, 0xF0, 0x9F, 0x98, 0x80, 0xEF, 0xBB, 0xBF, 0xFE,
+        ];
+        match rng.below(4) {
+            0 => rng.next() as u8,
+            1 => b' ' + rng.below(95) as u8,
+            _ => SPECIAL[rng.below(SPECIAL.len())],
+        }
+    }
 
-            let target = match probe_rs::config::get_target_by_name(&chip) {
-                Ok(t) => t,
-                Err(e) => {
-                    emit_rtt_status(&app, "error", &format!("Unknown chip '{chip}': {e}"));
-                    break;
-                }
-            };
+    fn fuzz_options(rng: &mut Rng) -> ParseOptions {
+        let encodings = ["utf-8", "ascii", "latin1", "shift_jis"];
+        ParseOptions {
+            include_hex: rng.below(2) == 0,
+            merge_continuations: rng.below(2) == 0,
+            keep_partial_first_line: rng.below(2) == 0,
+            keep_blank_lines: rng.below(2) == 0,
+            encoding: TextEncoding::parse(encodings[rng.below(encodings.len())]).unwrap(),
+            ..Default::default()
+        }
+    }
 
-            let mut session = match probe.attach(target, probe_rs::Permissions::default()) {
-                Ok(s) => s,
-                Err(e) => {
-                    emit_rtt_status(
-                        &app,
-                        "warn",
-                        &format!("Attach failed: {e}. Retrying in 3s..."),
-                    );
-                    std::thread::sleep(std::time::Duration::from_secs(3));
-                    continue;
-                }
-            };
+    #[test]
+    fn byte_parser_fuzz() {
+        for seed in 1..=500u64 {
+            let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let options = fuzz_options(&mut rng);
+            let merge = options.merge_continuations;
+            let stream: Vec<u8> = (0..rng.below(4096)).map(|_| fuzz_byte(&mut rng)).collect();
 
-            let mut rtt = match attach_rtt(&mut session, core_idx, &scan_region, &app) {
-                Ok(r) => r,
-                Err(ConnectError::Fatal(msg)) => {
-                    emit_rtt_status(&app, "error", &msg);
-                    break;
-                }
-                Err(ConnectError::Retry(msg)) => {
-                    emit_rtt_status(&app, "warn", &format!("{msg}. Retrying in 3s..."));
-                    std::thread::sleep(std::time::Duration::from_secs(3));
-                    continue;
-                }
-            };
+            let collected = std::cell::RefCell::new(Vec::new());
+            let sink = LogSink::Collect(&collected);
+            let mut stats = RttStats::default();
+            let mut parser = RttParser::new(0, options);
+            parser.first_line_after_attach = rng.below(2) == 0;
+            // Reads end wherever the RTT buffer happened to
+            let mut rest = &stream[..];
+            while !rest.is_empty() {
+                let (read, tail) = rest.split_at((1 + rng.below(64)).min(rest.len()));
+                parser
+                    .process_bytes(read, read.len(), &mut stats, sink)
+                    .unwrap();
+                rest = tail;
+            }
+            parser.flush(&mut stats, sink).unwrap();
 
-            let mut core = match session.core(core_idx) {
-                Ok(c) => c,
-                Err(e) => {
-                    emit_rtt_status(
-                        &app,
-                        "warn",
-                        &format!("Core access failed: {e}. Retrying in 3s..."),
+            for entry in collected.borrow().iter() {
+                for text in [&entry.message, &entry.raw] {
+                    // Continuation lines are joined with '\n'; a lone '\r' is kept as text
+                    let stray = text.chars().find(|&c| {
+                        c.is_control() && !matches!(c, '\t' | '\r') && !(merge && c == '\n')
+                    });
+                    assert_eq!(stray, None, "seed {seed}: {text:?}");
+                }
+                if let Some(hex) = &entry.raw_hex {
+                    assert!(
+                        hex.split_whitespace()
+                            .all(|b| b.len() == 2 && u8::from_str_radix(b, 16).is_ok()),
+                        "seed {seed}: {hex:?}"
                     );
-                    std::thread::sleep(std::time::Duration::from_secs(3));
-                    continue;
                 }
-            };
+            }
+        }
+    }
 
-            match rtt_read_loop(&mut rtt, &mut core, &mut parser, &stop_flag, &app) {
-                ReadResult::Stopped | ReadResult::AppClosed => {
-                    let _ = app.emit("rtt-disconnected", ());
-                    return;
-                }
-                ReadResult::Disconnected => {
-                    emit_rtt_status(&app, "warn", "Disconnected. Reconnecting in 2s...");
-                    std::thread::sleep(std::time::Duration::from_secs(2));
-                }
+    #[test]
+    fn terminal_switch_split_across_reads() {
+        let collected = std::cell::RefCell::new(Vec::new());
+        let sink = LogSink::Collect(&collected);
+        let mut stats = RttStats::default();
+        let mut parser = RttParser::new(0, ParseOptions::default());
+        let stream = "\u{1b}[1;31mé\u{1b}[0m one\n".as_bytes();
+        let mut bytes = vec![0xFF, b'3'];
+        bytes.extend_from_slice(stream);
+        bytes.extend([0xFF, b'x', b'\n']);
+        for b in &bytes {
+            parser.process_bytes(&[*b], 1, &mut stats, sink).unwrap();
+        }
+        let entries = collected.borrow();
+        assert_eq!(entries[0].raw, "é one");
+        assert_eq!(entries[0].terminal, Some(3));
+        // Not a terminal digit: the 0xFF was payload, invalid on its own in UTF-8
+        assert_eq!(entries[1].raw, "\u{FFFD}x");
+        assert_eq!(entries[1].terminal, Some(3));
+    }
+
+    /// Attach, then feed each read to the parser the way the read loop does
+    fn entries_after_attach(options: ParseOptions, reads: &[&str]) -> Vec<LogEntry> {
+        let collected = std::cell::RefCell::new(Vec::new());
+        let sink = LogSink::Collect(&collected);
+        let mut stats = RttStats::default();
+        let mut parser = RttParser::new(0, options);
+        parser.awaiting_first_read = true;
+        for read in reads {
+            match read.len() {
+                0 => parser.quiet_first_read(),
+                n => parser
+                    .process_bytes(read.as_bytes(), n, &mut stats, sink)
+                    .unwrap(),
             }
         }
-        let _ = app.emit("rtt-disconnected", ());
-    });
+        collected.into_inner()
+    }
 
-    Ok(msg)
-}
+    #[test]
+    fn banner_after_empty_first_read_is_kept() {
+        let entries = entries_after_attach(
+            ParseOptions::default(),
+            &["", "*** Booting Zephyr OS ***\n"],
+        );
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].raw, "*** Booting Zephyr OS ***");
+        assert!(!entries[0].partial);
+    }
 
-#[tauri::command]
-async fn start_mock(app: AppHandle) -> Result<String, String> {
-    let state = app.state::<AppState>();
-    state.stop_flag.store(false, Ordering::Relaxed);
+    #[test]
+    fn buffered_first_read_may_start_mid_line() {
+        let reads = ["ting Zephyr OS ***\n<inf> main: up\n", "second raw line\n"];
+        let entries = entries_after_attach(ParseOptions::default(), &reads);
+        let raws: Vec<_> = entries.iter().map(|e| e.raw.as_str()).collect();
+        assert_eq!(raws, ["<inf> main: up", "second raw line"]);
 
-    let stop_flag = state.stop_flag.clone();
-    let app_clone = app.clone();
+        let keep = ParseOptions {
+            keep_partial_first_line: true,
+            ..Default::default()
+        };
+        let entries = entries_after_attach(keep, &reads);
+        assert_eq!(entries[0].raw, "ting Zephyr OS ***");
+        assert!(entries[0].partial);
+        assert!(entries[1..].iter().all(|e| !e.partial));
+    }
 
-    let messages: Vec<(&'static str, &'static str, &'static str)> = vec![
-        ("ble_mesh", "inf", "Mesh network initialized, node count: 5"),
-        ("cellular", "inf", "Modem powered on"),
-        ("gps", "inf", "Cold start, searching for satellites..."),
-        ("main", "inf", "System boot, fw v2.4.1"),
-        ("uwb", "dbg", "TWR ranging started with anchor 1"),
-        ("battery", "inf", "Voltage: 3.8V (72%)"),
-        ("plas", "inf", "PLAS engine started"),
-        ("ble_mesh", "wrn", "Peer timeout: 0x1A3F"),
-        ("cellular", "err", "Network registration failed"),
-        ("gps", "inf", "Fix acquired: 8 satellites"),
-        ("main", "dbg", "Heap: 42KB used / 128KB total"),
-        ("uwb", "inf", "Distance to anchor 1: 4.2m"),
-        ("ble_mesh", "inf", "Peer connected: 0xAB12"),
-        ("cellular", "inf", "Signal: RSRP=-87 dBm"),
-        ("plas", "wrn", "Worker approaching restricted area"),
-        ("battery", "dbg", "Current draw: 34mA"),
-    ];
+    // ── Entry fields ──
 
-    tokio::spawn(async move {
-        let mut idx = 0u64;
-        loop {
-            if stop_flag.load(Ordering::Relaxed) {
-                break;
-            }
-            let (tag, level, msg) = messages[(idx as usize) % messages.len()];
-            let secs = idx * 250 / 1000;
-            let ms = (idx * 250) % 1000;
-            let raw = format!(
-                "[00:{:02}:{:02}.{:03},000] <{level}> {tag}: {msg}",
-                secs / 60,
-                secs % 60,
-                ms
-            );
-            let entry = parse_line(&raw);
-            if app_clone.emit("rtt-log", &entry).is_err() {
-                break;
-            }
-            tokio::time::sleep(std::time::Duration::from_millis(150 + (idx % 7) * 50)).await;
-            idx += 1;
-        }
-        let _ = app_clone.emit("rtt-stopped", ());
-    });
+    #[test]
+    fn rfc3339_dates() {
+        assert_eq!(rfc3339_utc(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(rfc3339_utc(94_608_000_000), "1972-12-31T00:00:00.000Z");
+        assert_eq!(rfc3339_utc(1_704_067_199_999), "2023-12-31T23:59:59.999Z");
+        assert_eq!(rfc3339_utc(1_709_210_096_789), "2024-02-29T12:34:56.789Z");
+        // Divisible by 400: a leap year
+        assert_eq!(rfc3339_utc(951_782_400_000), "2000-02-29T00:00:00.000Z");
+        // Divisible by 100 only: not one
+        assert_eq!(rfc3339_utc(4_107_542_399_999), "2100-02-28T23:59:59.999Z");
+        assert_eq!(rfc3339_utc(4_107_542_400_000), "2100-03-01T00:00:00.000Z");
+    }
 
-    Ok("Mock started".to_string())
-}
+    #[test]
+    fn base64_padding() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foob"), "Zm9vYg==");
+        assert_eq!(base64_encode(&[0x00, 0xFF, 0xFE]), "AP/+");
+    }
 
-#[tauri::command]
-async fn extract_rtt_address_from_elf(elf_path: String) -> Result<ElfInfo, String> {
-    let data = std::fs::read(&elf_path).map_err(|e| format!("Failed to read ELF file: {e}"))?;
+    // ── SVD registers ──
 
-    let elf = goblin::elf::Elf::parse(&data).map_err(|e| format!("Failed to parse ELF: {e}"))?;
+    /// Cut down from Nordic's nRF52840 SVD: a derived peripheral, a register
+    /// array, a cluster array and a single cluster.
+    const NRF52840_SVD: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<device schemaVersion="1.1" xmlns:xs="http://www.w3.org/2001/XMLSchema-instance" xs:noNamespaceSchemaLocation="CMSIS-SVD.xsd">
+  <vendor>Nordic Semiconductor</vendor>
+  <name>nrf52840</name>
+  <width>32</width>
+  <size>32</size>
+  <resetValue>0x00000000</resetValue>
+  <peripherals>
+    <peripheral>
+      <name>UARTE0</name>
+      <description>UART with EasyDMA 0</description>
+      <baseAddress>0x40002000</baseAddress>
+      <registers>
+        <register>
+          <name>ENABLE</name>
+          <description>Enable UART</description>
+          <addressOffset>0x500</addressOffset>
+          <access>read-write</access>
+          <fields>
+            <field>
+              <name>ENABLE</name>
+              <lsb>0</lsb>
+              <msb>3</msb>
+              <enumeratedValues>
+                <enumeratedValue>
+                  <name>Disabled</name>
+                  <value>0</value>
+                </enumeratedValue>
+                <enumeratedValue>
+                  <name>Enabled</name>
+                  <value>8</value>
+                </enumeratedValue>
+              </enumeratedValues>
+            </field>
+          </fields>
+        </register>
+        <cluster>
+          <name>PSEL</name>
+          <addressOffset>0x508</addressOffset>
+          <register>
+            <name>RTS</name>
+            <addressOffset>0x000</addressOffset>
+          </register>
+          <register>
+            <name>TXD</name>
+            <addressOffset>0x004</addressOffset>
+            <fields>
+              <field>
+                <name>PIN</name>
+                <bitRange>[4:0]</bitRange>
+              </field>
+              <field>
+                <name>CONNECT</name>
+                <bitOffset>31</bitOffset>
+                <bitWidth>1</bitWidth>
+              </field>
+            </fields>
+          </register>
+        </cluster>
+        <register>
+          <name>BAUDRATE</name>
+          <addressOffset>0x524</addressOffset>
+        </register>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="UARTE0">
+      <name>UARTE1</name>
+      <baseAddress>0x40028000</baseAddress>
+    </peripheral>
+    <peripheral>
+      <name>PPI</name>
+      <baseAddress>0x4001F000</baseAddress>
+      <registers>
+        <register>
+          <name>CHEN</name>
+          <addressOffset>0x500</addressOffset>
+        </register>
+        <cluster>
+          <dim>20</dim>
+          <dimIncrement>0x008</dimIncrement>
+          <name>CH[%s]</name>
+          <addressOffset>0x510</addressOffset>
+          <register>
+            <name>EEP</name>
+            <addressOffset>0x000</addressOffset>
+          </register>
+          <register>
+            <name>TEP</name>
+            <addressOffset>0x004</addressOffset>
+          </register>
+        </cluster>
+      </registers>
+    </peripheral>
+    <peripheral>
+      <name>P0</name>
+      <baseAddress>0x50000000</baseAddress>
+      <registers>
+        <register>
+          <dim>32</dim>
+          <dimIncrement>0x4</dimIncrement>
+          <name>PIN_CNF[%s]</name>
+          <addressOffset>0x700</addressOffset>
+          <size>32</size>
+        </register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
 
-    // Find _SEGGER_RTT address
-    let mut rtt_address = None;
-    let mut symbols: Vec<(String, u64)> = Vec::new();
+    fn svd_address(device: &SvdDevice, peripheral: &str, register: &str) -> Option<u64> {
+        let p = device.peripherals.get(peripheral)?;
+        Some(p.base + p.registers.get(register)?.offset)
+    }
 
-    for sym in &elf.syms {
-        if let Some(name) = elf.strtab.get_at(sym.st_name) {
-            if name == "_SEGGER_RTT" {
-                rtt_address = Some(format!("0x{:08X}", sym.st_value));
+    #[test]
+    fn svd_registers_and_fields() {
+        let device = parse_svd(NRF52840_SVD).unwrap();
+        assert_eq!(device.name, "nrf52840");
+        assert_eq!(svd_address(&device, "UARTE0", "ENABLE"), Some(0x4000_2500));
+        let enable = &device.peripherals["UARTE0"].registers["ENABLE"];
+        assert_eq!(enable.size, 32);
+        assert_eq!(
+            (enable.fields[0].bit_offset, enable.fields[0].bit_width),
+            (0, 4)
+        );
+        assert_eq!(enable.fields[0].values[1], (8, "Enabled".to_string()));
+        let txd = &device.peripherals["UARTE0"].registers["PSEL.TXD"];
+        let bits: Vec<_> = txd
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.bit_offset, f.bit_width))
+            .collect();
+        assert_eq!(bits, [("PIN", 0, 5), ("CONNECT", 31, 1)]);
+    }
+
+    #[test]
+    fn svd_derived_from() {
+        let device = parse_svd(NRF52840_SVD).unwrap();
+        assert_eq!(svd_address(&device, "UARTE1", "ENABLE"), Some(0x4002_8500));
+        assert_eq!(
+            svd_address(&device, "UARTE1", "PSEL.TXD"),
+            Some(0x4002_850C)
+        );
+        assert_eq!(
+            device.peripherals["UARTE1"].registers.len(),
+            device.peripherals["UARTE0"].registers.len()
+        );
+    }
+
+    #[test]
+    fn svd_dim_and_clusters() {
+        let device = parse_svd(NRF52840_SVD).unwrap();
+        assert_eq!(
+            svd_address(&device, "UARTE0", "PSEL.RTS"),
+            Some(0x4000_2508)
+        );
+        assert_eq!(svd_address(&device, "P0", "PIN_CNF[0]"), Some(0x5000_0700));
+        assert_eq!(svd_address(&device, "P0", "PIN_CNF[31]"), Some(0x5000_077C));
+        assert_eq!(svd_address(&device, "P0", "PIN_CNF[32]"), None);
+        assert_eq!(device.peripherals["P0"].registers.len(), 32);
+        assert_eq!(svd_address(&device, "PPI", "CH[0].EEP"), Some(0x4001_F510));
+        assert_eq!(svd_address(&device, "PPI", "CH[19].TEP"), Some(0x4001_F5AC));
+        // CHEN plus EEP and TEP for each of the 20 channels
+        assert_eq!(device.peripherals["PPI"].registers.len(), 41);
+    }
+
+    #[test]
+    fn svd_dim_index_list() {
+        let node = parse_xml(
+            "<register><dim>3</dim><dimIncrement>0x10</dimIncrement><dimIndex>A,B,C</dimIndex></register>",
+        )
+        .unwrap();
+        let names = svd_dim_names(node.child("register").unwrap(), "GPIO%s_MODER");
+        assert_eq!(
+            names,
+            [
+                ("GPIOA_MODER".to_string(), 0),
+                ("GPIOB_MODER".to_string(), 0x10),
+                ("GPIOC_MODER".to_string(), 0x20),
+            ]
+        );
+        assert_eq!(svd_number("0x1F"), Some(31));
+        assert_eq!(svd_number("#1x0"), Some(4));
+        assert_eq!(svd_number("12"), Some(12));
+    }
+
+    // ── RAM log backlog ──
+
+    #[test]
+    fn backlog_ring_unwrapping() {
+        // No NUL: read front to back
+        assert_eq!(unwrap_backlog(b"one\ntwo\n"), b"one\ntwo\n");
+        // Never wrapped: only NULs after the write position
+        assert_eq!(unwrap_backlog(b"one\ntwo\n\0\0\0\0"), b"one\ntwo\n");
+        // Wrapped: the line cut by the write position is dropped, the rest is older
+        assert_eq!(
+            unwrap_backlog(b"new\n\0ld\nolder\noldest\n"),
+            b"older\noldest\nnew\n"
+        );
+        // Writer mid-line: the unfinished line is kept and terminated
+        assert_eq!(unwrap_backlog(b"partial\0xx\nolder\n"), b"older\npartial\n");
+        // Write position at the very end
+        assert_eq!(unwrap_backlog(b"a\nb\0"), b"a\nb\n");
+        assert_eq!(unwrap_backlog(b"\0\0\0"), b"");
+    }
+
+    // ── Session diff ──
+
+    /// Replay `ops` on `a`, taking inserted elements from `b`.
+    fn apply_diff<T: Clone>(ops: &[DiffOp], a: &[T], b: &[T]) -> Vec<T> {
+        let (mut i, mut j, mut out) = (0, 0, Vec::new());
+        for op in ops {
+            match op {
+                DiffOp::Equal => {
+                    out.push(a[i].clone());
+                    i += 1;
+                    j += 1;
+                }
+                DiffOp::Delete => i += 1,
+                DiffOp::Insert => {
+                    out.push(b[j].clone());
+                    j += 1;
+                }
             }
-            symbols.push((name.to_string(), sym.st_value));
         }
+        assert_eq!((i, j), (a.len(), b.len()), "ops must consume both inputs");
+        out
     }
 
-    let rtt_address = rtt_address.ok_or("_SEGGER_RTT symbol not found in ELF")?;
+    fn edits(ops: &[DiffOp]) -> usize {
+        ops.iter().filter(|&&op| op != DiffOp::Equal).count()
+    }
 
-    // Detect chip from ELF metadata
-    let chip_hint = detect_chip(&elf, &symbols).map(|s| s.replace("-", "_"));
+    #[test]
+    fn myers_diff_shortest_script() {
+        let cases: [(&str, &str, usize); 7] = [
+            ("", "", 0),
+            ("", "abc", 3),
+            ("abc", "", 3),
+            ("abc", "abc", 0),
+            ("abc", "axc", 2),
+            // The example from Myers' paper
+            ("abcabba", "cbabac", 5),
+            ("xaaay", "aaa", 2),
+        ];
+        for (a, b, d) in cases {
+            let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+            let ops = myers_diff(&a, &b).unwrap();
+            assert_eq!(apply_diff(&ops, &a, &b), b);
+            assert_eq!(edits(&ops), d, "{a:?} -> {b:?}");
+        }
+    }
 
-    Ok(ElfInfo {
-        rtt_address,
-        chip_hint,
-    })
-}
+    #[test]
+    fn myers_diff_hunks() {
+        let a = ["boot", "init", "tick", "done"];
+        let b = ["boot", "init", "fault", "done", "reset"];
+        let diff = diff_hunks(&myers_diff(&a, &b).unwrap());
+        let kinds: Vec<_> = diff.hunks.iter().map(|h| h.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                HunkKind::Equal,
+                HunkKind::Changed,
+                HunkKind::Equal,
+                HunkKind::Added
+            ]
+        );
+        assert_eq!((diff.removed, diff.added), (1, 2));
+    }
 
-fn detect_chip(elf: &goblin::elf::Elf, symbols: &[(String, u64)]) -> Option<String> {
-    // Must be ARM
-    if elf.header.e_machine != goblin::elf::header::EM_ARM {
-        return None;
+    #[test]
+    fn myers_diff_gives_up_on_unrelated_input() {
+        let a: Vec<u32> = (0..3000).collect();
+        let b: Vec<u32> = (3000..6000).collect();
+        assert!(myers_diff(&a, &b).is_err());
     }
 
-    // Check entry point and symbol addresses to identify chip family
-    let entry = elf.header.e_entry;
+    // ── Latency between events ──
 
-    // Look for known Zephyr/Nordic config symbols
-    let has_symbol = |prefix: &str| symbols.iter().any(|(name, _)| name.contains(prefix));
+    fn latency_entry(id: u64, raw: &str, device: &str, host_ms: u64) -> LogEntry {
+        LogEntry {
+            id,
+            raw: raw.to_string(),
+            device_timestamp: Some(device.to_string()).filter(|d| !d.is_empty()),
+            host_timestamp: Some(host_ms),
+            ..Default::default()
+        }
+    }
 
-    // nRF5340 detection
-    if has_symbol("NRF5340") || has_symbol("nrf5340") {
-        // Check if app or net core based on RAM address
-        let ram_base = symbols
+    fn pairs_of(
+        entries: &[LogEntry],
+        from: &str,
+        to: &str,
+    ) -> Vec<(u64, Option<u64>, Option<u64>)> {
+        let (from, to) = (Regex::new(from).unwrap(), Regex::new(to).unwrap());
+        let keyed = from.capture_names().any(|n| n == Some(LATENCY_KEY_GROUP));
+        pair_latencies(entries, &from, &to, keyed)
             .iter()
-            .find(|(name, _)| name == "_SEGGER_RTT")
-            .map(|(_, addr)| *addr)
-            .unwrap_or(0);
+            .map(|p| (p.start_id, p.end_id, p.latency_us))
+            .collect()
+    }
 
-        if ram_base >= 0x2100_0000 {
-            return Some("nRF5340_xxAA".to_string()); // net core RAM starts at 0x21000000
-        }
-        return Some("nRF5340_xxAA".to_string());
+    #[test]
+    fn latency_pairs_first_in_first_out() {
+        let entries = [
+            latency_entry(1, "done", "00:00:00.500", 0),
+            latency_entry(2, "start", "00:00:01.000", 0),
+            latency_entry(3, "start", "00:00:01.100", 0),
+            latency_entry(4, "done", "00:00:01.300", 0),
+            latency_entry(5, "start", "00:00:02.000", 0),
+        ];
+        // The first end has no start; the last start never ends
+        assert_eq!(
+            pairs_of(&entries, "start", "done"),
+            [
+                (2, Some(4), Some(300_000)),
+                (3, None, None),
+                (5, None, None)
+            ]
+        );
     }
 
-    // nRF52840
-    if has_symbol("NRF52840") || has_symbol("nrf52840") {
-        return Some("nRF52840_xxAA".to_string());
+    #[test]
+    fn latency_pairs_by_key() {
+        let entries = [
+            latency_entry(1, "req 7 sent", "00:00:01.000", 0),
+            latency_entry(2, "req 8 sent", "00:00:01.010", 0),
+            latency_entry(3, "req 8 done", "00:00:01.030", 0),
+            latency_entry(4, "req 9 done", "00:00:01.040", 0),
+            latency_entry(5, "req 7 done", "00:00:01.250", 0),
+        ];
+        assert_eq!(
+            pairs_of(&entries, r"req (?P<key>\d+) sent", r"req (?P<key>\d+) done"),
+            [(1, Some(5), Some(250_000)), (2, Some(3), Some(20_000))]
+        );
     }
 
-    // nRF52833
-    if has_symbol("NRF52833") || has_symbol("nrf52833") {
-        return Some("nRF52833_xxAA".to_string());
+    #[test]
+    fn latency_line_matching_both_patterns() {
+        let entries = [
+            latency_entry(1, "tick", "00:00:01.000", 0),
+            latency_entry(2, "tick", "00:00:01.100", 0),
+            latency_entry(3, "tick", "00:00:01.300", 0),
+        ];
+        assert_eq!(
+            pairs_of(&entries, "tick", "tick"),
+            [
+                (1, Some(2), Some(100_000)),
+                (2, Some(3), Some(200_000)),
+                (3, None, None)
+            ]
+        );
     }
 
-    // nRF52832
-    if has_symbol("NRF52832") || has_symbol("nrf52832") {
-        return Some("nRF52832_xxAA".to_string());
+    #[test]
+    fn latency_falls_back_to_host_clock() {
+        let from = Regex::new("start").unwrap();
+        let to = Regex::new("done").unwrap();
+        let entries = [
+            // The device reset in between: its uptime went backwards
+            latency_entry(1, "start", "00:05:00.000", 10_000),
+            latency_entry(2, "done", "00:00:00.200", 10_450),
+            // No device timestamp on one side
+            latency_entry(3, "start", "", 20_000),
+            latency_entry(4, "done", "00:00:01.000", 20_075),
+        ];
+        let pairs = pair_latencies(&entries, &from, &to, false);
+        let got: Vec<_> = pairs.iter().map(|p| (p.latency_us, p.clock)).collect();
+        assert_eq!(
+            got,
+            [(Some(450_000), Some("host")), (Some(75_000), Some("host"))]
+        );
     }
 
-    // nRF9160
-    if has_symbol("NRF9160") || has_symbol("nrf9160") {
-        return Some("nRF9160_xxAA".to_string());
+    // ── Remote viewing ──
+
+    #[test]
+    fn ws_token_in_query() {
+        let matches = |uri: &str| ws_token_matches(&uri.parse().unwrap(), "s3cret");
+        assert!(matches("/?token=s3cret"));
+        assert!(matches("/logs?view=all&token=s3cret"));
+        assert!(!matches("/"));
+        assert!(!matches("/?token="));
+        assert!(!matches("/?token=s3cret2"));
+        assert!(!matches("/?mytoken=s3cret"));
     }
 
-    // nRF91x1 (nRF9151/9161)
-    if has_symbol("NRF9151")
-        || has_symbol("nrf9151")
-        || has_symbol("NRF91X1")
-        || has_symbol("nrf91x1")
-    {
-        return Some("nRF9151_xxAA".to_string());
+    // ── Syslog forwarding ──
+
+    #[test]
+    fn udp_datagram_truncation() {
+        let short = "<134>1 - - app - - - hello";
+        assert_eq!(udp_datagram(short), short);
+        let exact = "x".repeat(SYSLOG_UDP_MAX);
+        assert_eq!(udp_datagram(&exact), exact);
+
+        // 'é' is two bytes, so the cut lands mid-character at every other offset
+        for pad in 0..2 {
+            let long = "x".repeat(pad) + &"é".repeat(SYSLOG_UDP_MAX);
+            let datagram = udp_datagram(&long);
+            assert!(datagram.len() <= SYSLOG_UDP_MAX);
+            assert!(datagram.len() > SYSLOG_UDP_MAX - SYSLOG_TRUNCATED.len() - 2);
+            let kept = datagram.strip_suffix(SYSLOG_TRUNCATED).unwrap();
+            assert!(long.starts_with(kept));
+        }
     }
 
-    if has_symbol("NRF9161") || has_symbol("nrf9161") {
-        return Some("nRF9161_xxAA".to_string());
+    // ── MQTT forwarding ──
+
+    #[test]
+    fn mqtt_broker_addresses() {
+        let split = |b| split_broker(b).map_err(|e| e.to_string());
+        assert_eq!(split("localhost"), Ok(("localhost", 1883)));
+        assert_eq!(split("broker.local:8883"), Ok(("broker.local", 8883)));
+        assert_eq!(split("::1"), Ok(("::1", 1883)));
+        assert_eq!(split("fe80::1:2"), Ok(("fe80::1:2", 1883)));
+        assert_eq!(split("[::1]"), Ok(("::1", 1883)));
+        assert_eq!(split("[2001:db8::5]:1884"), Ok(("2001:db8::5", 1884)));
+        assert!(split("host:mqtt").is_err());
+        assert!(split("[::1]:").is_err());
+        assert!(split("[::1").is_err());
+        assert!(split("[::1]1883").is_err());
     }
 
-    // STM32 detection by RAM/flash ranges
-    if has_symbol("STM32") || has_symbol("stm32") {
-        return Some("STM32 (check exact variant)".to_string());
+    // ── Search index ──
+
+    fn indexed(lines: &[&str]) -> Mutex<SearchIndex> {
+        let mut index = SearchIndex::default();
+        for (id, line) in (1..).zip(lines) {
+            index.insert(&LogEntry {
+                id,
+                raw: line.to_string(),
+                ..Default::default()
+            });
+        }
+        Mutex::new(index)
     }
 
-    // ESP32 — won't be ARM, but just in case
-    if has_symbol("ESP32") || has_symbol("esp32") {
-        return Some("ESP32".to_string());
+    #[test]
+    fn search_index_substring() {
+        let index = indexed(&[
+            "<inf> i2c: Transfer_Done addr=0x48",
+            "<err> i2c: transfer failed, NACK",
+            "<inf> spi: xfer done",
+            "a b c",
+        ]);
+        let find = |q| search_index(&index, q, false).unwrap();
+        assert_eq!(find("transfer"), [1, 2]);
+        // Inside a word, and across case
+        assert_eq!(find("ANSF"), [1, 2]);
+        // Edge words partial, the middle one whole
+        assert_eq!(find("sfer fail"), [2]);
+        assert_eq!(find("2c: trans"), [1, 2]);
+        // Punctuation and words too short to look up fall back to checking lines
+        assert_eq!(find("="), [1]);
+        assert_eq!(find("b c"), [4]);
+        assert_eq!(find("0x4"), [1]);
+        // Every word is indexed but the phrase isn't there
+        assert!(find("done transfer").is_empty());
+        assert!(find("nothing").is_empty());
     }
 
-    // Fallback: try to guess from memory map
-    // nRF52 family has RAM at 0x20000000, flash at 0x00000000
-    // nRF53 app core: RAM 0x20000000, flash 0x00000000
-    // nRF53 net core: RAM 0x21000000, flash 0x01000000
-    // nRF91: RAM 0x20000000, flash 0x00000000
-    if entry < 0x0010_0000 {
-        // Looks like nRF5x or nRF91 flash region
-        let rtt_addr = symbols
-            .iter()
-            .find(|(name, _)| name == "_SEGGER_RTT")
-            .map(|(_, addr)| *addr)
-            .unwrap_or(0);
+    #[test]
+    fn search_index_regex() {
+        let index = indexed(&["temp=21", "temp=105", "Temp=7"]);
+        let find = |q| search_index(&index, q, true);
+        assert_eq!(find(r"temp=\d{3}").unwrap(), [2]);
+        assert_eq!(find("(?i)^temp").unwrap(), [1, 2, 3]);
+        assert!(find("temp=(").is_err());
+    }
 
-        if rtt_addr >= 0x2100_0000 {
-            return Some("nRF5340_xxAA (net core?)".to_string());
+    #[test]
+    fn search_index_eviction() {
+        let mut index = SearchIndex::default();
+        // Two of these are over the limit
+        let line = "x".repeat(MAX_INDEX_BYTES / 2);
+        for (id, word) in [(1, "alpha"), (2, "gamma")] {
+            index.insert(&LogEntry {
+                id,
+                raw: format!("{word} {line}"),
+                ..Default::default()
+            });
         }
-        if rtt_addr >= 0x2000_0000 {
-            return Some("Nordic (nRF52/53/91 — check variant)".to_string());
+        let index = Mutex::new(index);
+        assert!(search_index(&index, "alpha", false).unwrap().is_empty());
+        assert_eq!(search_index(&index, "gamma", false).unwrap(), [2]);
+        let index = index.lock().unwrap();
+        // The evicted word's trigrams went with it
+        assert!(!index.postings.contains_key("alpha"));
+        assert!(!index.grams.contains_key(b"lph"));
+    }
+
+    // ── Live views ──
+
+    #[test]
+    fn views_see_entries_the_emit_filter_drops() {
+        let emit_filter = EmitFilter::from_preset(&LogFilter {
+            levels: vec!["error".to_string(), "warn".to_string()],
+            ..Default::default()
+        });
+        let views = [
+            LogView {
+                id: 1,
+                name: "i2c debug".to_string(),
+                filter: EmitFilter::from_preset(&LogFilter {
+                    levels: vec!["debug".to_string()],
+                    ..Default::default()
+                }),
+                tags: HashSet::from(["i2c".to_string()]),
+                text: None,
+            },
+            LogView {
+                id: 2,
+                name: "errors".to_string(),
+                filter: EmitFilter::from_preset(&LogFilter {
+                    levels: vec!["error".to_string()],
+                    ..Default::default()
+                }),
+                tags: HashSet::new(),
+                text: None,
+            },
+        ];
+        let entry = |level: &str, tag: &str| LogEntry {
+            level: level.to_string(),
+            tag: Some(tag.to_string()),
+            ..Default::default()
+        };
+
+        let debug = entry("debug", "i2c");
+        assert!(!emit_filter.allows(&debug));
+        assert_eq!(matching_views(&views, &debug), [1]);
+        assert_eq!(
+            matching_views(&views, &entry("debug", "spi")),
+            [] as [u32; 0]
+        );
+        assert_eq!(matching_views(&views, &entry("error", "i2c")), [2]);
+    }
+
+    // ── Flight recorder ──
+
+    fn recorder_entry(id: u64, raw: &str) -> LogEntry {
+        LogEntry {
+            id,
+            level: "raw".to_string(),
+            raw: raw.to_string(),
+            ..Default::default()
         }
     }
 
-    None
-}
+    #[test]
+    fn flight_recorder_freezes_without_more_traffic() {
+        let window = Duration::from_secs(4);
+        let mut recorder = FlightRecorder::new(window, Some(Regex::new("HardFault").unwrap()));
+        assert!(recorder.record(&recorder_entry(1, "boot")).is_none());
+        let fired = recorder.record(&recorder_entry(2, "HardFault at 0x0800"));
+        assert!(matches!(
+            fired,
+            Some(FlightRecorderEvent::Fired { id: 2, .. })
+        ));
 
-#[tauri::command]
-async fn read_text_file(path: String) -> Result<String, String> {
-    std::fs::read_to_string(&path).map_err(|e| format!("{e}"))
-}
+        let (_, at) = recorder.fired.unwrap();
+        assert!(recorder.tick(at + window / 8).is_none());
+        let ready = recorder.tick(at + window / 4);
+        assert!(matches!(
+            ready,
+            Some(FlightRecorderEvent::Ready { entries: 2 })
+        ));
+        assert!(recorder.tick(at + window).is_none());
+        assert!(recorder.record(&recorder_entry(3, "late")).is_none());
+        assert_eq!(recorder.ring.len(), 2);
+    }
 
-#[tauri::command]
-async fn write_text_file(path: String, contents: String) -> Result<(), String> {
-    std::fs::write(&path, &contents).map_err(|e| format!("{e}"))
-}
+    #[test]
+    fn flight_recorder_byte_cap() {
+        let big = "x".repeat(FLIGHT_RECORDER_MAX_BYTES / 8);
+        let mut recorder = FlightRecorder::new(
+            Duration::from_secs(3600),
+            Some(Regex::new("^fault$").unwrap()),
+        );
+        for id in 0..12 {
+            recorder.record(&recorder_entry(id, &big));
+        }
+        // The oldest go even though they are inside the window
+        assert_eq!(recorder.ring.len(), 8);
+        assert_eq!(recorder.ring.front().unwrap().1.id, 4);
+        assert!(recorder.bytes <= FLIGHT_RECORDER_MAX_BYTES);
 
-fn main() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_dialog::init())
-        .manage(AppState {
-            stop_flag: Arc::new(AtomicBool::new(false)),
-        })
-                .setup(|app| {
-            let file_menu = SubmenuBuilder::new(app.handle(), "File")
-                .text("import", "Import Logs…")
-                .separator()
-                .text("export_json", "Export as JSON…")
-                .text("export_csv", "Export as CSV…")
-                .text("export_txt", "Export as Text…")
-                .separator()
-                .quit()
-                .build()?;
+        // After the trigger the lead-up stays; the recorder stops short instead
+        recorder.record(&recorder_entry(12, "fault"));
+        let ready = recorder.record(&recorder_entry(13, &big));
+        assert!(matches!(ready, Some(FlightRecorderEvent::Ready { .. })));
+        assert_eq!(recorder.ring.front().unwrap().1.id, 5);
+        assert_eq!(recorder.ring.back().unwrap().1.id, 12);
+    }
 
-            let menu = MenuBuilder::new(app.handle())
-                .item(&file_menu)
-                .build()?;
+    // ── HTML export ──
 
-            app.set_menu(menu)?;
-            Ok(())
-        })
-        .on_menu_event(|app, event| {
-            let _ = app.emit("menu-event", event.id().0.as_str());
-        })
-        .invoke_handler(tauri::generate_handler![
-            start_rtt,
-            start_mock,
-            stop_source,
-            list_probes,
-            get_profiles,
-            save_profile,
-            delete_profile,
-            extract_rtt_address_from_elf,
-            read_text_file,
-            write_text_file,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+    #[test]
+    fn html_escaping() {
+        assert_eq!(
+            html_escape(r#"a & b < c > d "e" 'f'"#),
+            "a &amp; b &lt; c &gt; d &quot;e&quot; &#39;f&#39;"
+        );
+        assert_eq!(html_escape("plain ümlaut"), "plain ümlaut");
+    }
+
+    #[test]
+    fn html_report_escapes_entries() {
+        let entries = [
+            LogEntry {
+                id: 7,
+                level: "warn".to_string(),
+                tag: Some("<i2c>".to_string()),
+                message: r#"addr "0x48" & 'busy' <retry>"#.to_string(),
+                ..Default::default()
+            },
+            LogEntry {
+                id: 8,
+                level: "error".to_string(),
+                message: "fault\n<pc> & lr".to_string(),
+                ..Default::default()
+            },
+        ];
+        let html = entries_to_html(&entries, "A & B");
+        assert!(html.contains("<title>A &amp; B</title>"));
+        assert!(html.contains("&lt;i2c&gt;"));
+        assert!(html.contains("addr &quot;0x48&quot; &amp; &#39;busy&#39; &lt;retry&gt;"));
+        assert!(html.contains("<summary>fault</summary>&lt;pc&gt; &amp; lr</details>"));
+        assert!(!html.contains("<retry>") && !html.contains("<pc>"));
+    }
 }
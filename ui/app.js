@@ -1,6 +1,6 @@
 // Main app — wires everything together
 
-import { state, rebuild, appendEntry, clearLogs, setSearch, esc, terminalColor, updateSearchMatches, setSearchMode } from './log-engine.js';
+import { state, rebuild, appendEntry, appendEntries, clearLogs, setSearch, esc, terminalColor, updateSearchMatches, setSearchMode, matches, errorMessage, renderLine, findEntry } from './log-engine.js';
 import { tagColor } from './log-engine.js';
 import * as Profiles from './profiles.js';
 import * as LogIO from './log-io.js';
@@ -12,11 +12,18 @@ const $ = s => document.querySelector(s);
 const logArea = $('#logArea');
 const tagbar = $('#tagbar');
 const termbar = $('#termbar');
+const watchbar = $('#watchbar');
+const btnWatch = $('#btnWatch');
+const btnTrigger = $('#btnTrigger');
+const btnSnapshot = $('#btnSnapshot');
 const countEl = $('#count');
 const dot = $('#dot');
 const searchInput = $('#search');
 const btnConnect = $('#btnConnect');
 const btnMock = $('#btnMock');
+const btnSwo = $('#btnSwo');
+const btnFifo = $('#btnFifo');
+const btnPing = $('#btnPing');
 const probeSelect = $('#probeSelect');
 const btnRefreshProbes = $('#btnRefreshProbes');
 const btnExport = $('#btnExport');
@@ -24,11 +31,46 @@ const btnImport = $('#btnImport');
 
 
 const btnSearchMode = $('#btnSearchMode');
+const btnTimeBase = $('#btnTimeBase');
 const btnPrev = $('#btnPrev');
 const btnNext = $('#btnNext');
 const searchInfo = $('#searchInfo');
 
 let source = null;
+let activeProfileName = null;
+let remoteServerPort = null;
+let gdbServerAddr = null; // set while the RTT session serves GDB
+let syslogTarget = null; // "udp://host:port" while logs are forwarded to syslog
+let mqttTarget = null; // "host:port/topic" while logs are published to MQTT
+let lastLatency = { from: '', to: '' }; // patterns of the last Measure Latency
+let probeList = [];
+let lineEnding = 'lf'; // exports and copies: 'lf' | 'crlf' | 'native'
+let asciiOnly = false; // exports and copies escape non-ASCII text
+let errorThreshold = 3; // failed reads in a row before the backend reconnects
+let readMode = 'poll'; // 'poll' | 'block', how the backend waits on empty reads
+let statusInLog = false; // app status lines also go into the log, exports and search
+let emitBatching = { batchMs: null, batchSize: 2000 }; // batchMs null: picked from the line rate
+let recordSqlitePath = null; // start_rtt's log_to_sqlite for the next connection
+let flightRecorderArmed = false;
+let svdDevice = null; // name of the SVD loaded for read_register
+let lastRegister = '';
+let lastPoke = '';
+let indexComplete = true; // false once imported lines (never indexed) are on screen
+let continuousExport = null; // { path, format, lastId, timer } while appending to a file
+const CONTINUOUS_EXPORT_MS = 2000;
+
+async function appendExportTick() {
+  const job = continuousExport;
+  const entries = state.logs.filter(e => e.id > job.lastId);
+  if (!entries.length) return;
+  try {
+    job.lastId = await invoke('append_export', { path: job.path, entries, format: job.format, lineEnding, asciiOnly });
+  } catch (e) {
+    clearInterval(job.timer);
+    continuousExport = null;
+    alert(`Stopped exporting to ${job.path}: ${errorMessage(e)}`);
+  }
+}
 
 listen('menu-event', async (e) => {
   const id = e.payload;
@@ -40,19 +82,450 @@ listen('menu-event', async (e) => {
         onCountChanged: () => { countEl.textContent = state.logs.length; },
       });
       if (count) {
+        indexComplete = false;
         renderTagbar();
         renderTermbar();
         updateUI();
       }
-    } catch (e) { alert('Import failed: ' + e); }
+    } catch (e) { alert('Import failed: ' + errorMessage(e)); }
+  } else if (id === 'replay') {
+    const openDialog = window.__TAURI__.dialog?.open;
+    const path = openDialog
+      ? await openDialog({ multiple: false, filters: [{ name: 'JSON', extensions: ['json'] }] })
+      : prompt('Enter full path to a JSON log export:');
+    if (!path) return;
+    const speed = parseFloat(prompt('Replay speed (1 = original timing):', '1'));
+    if (!(speed > 0)) return;
+    try {
+      await invoke('replay_session', { path, speed });
+      // Replay behaves like the mock source: the Stop button ends it
+      source = 'mock';
+      updateUI();
+    } catch (e) { alert('Replay failed: ' + errorMessage(e)); }
+  } else if (id === 'diff') {
+    try { await diffAgainstFile(); } catch (e) { alert('Diff failed: ' + errorMessage(e)); }
+  } else if (id === 'record_sqlite') {
+    if (recordSqlitePath) {
+      if (confirm(`Stop recording new sessions to ${recordSqlitePath}?`)) recordSqlitePath = null;
+      return;
+    }
+    const saveDialog = window.__TAURI__.dialog?.save;
+    const path = saveDialog
+      ? await saveDialog({ defaultPath: 'rtt-capture.db', filters: [{ name: 'SQLite', extensions: ['db', 'sqlite'] }] })
+      : prompt('Record RTT sessions to SQLite database:', 'rtt-capture.db');
+    if (!path) return;
+    recordSqlitePath = path;
+    if (source === 'rtt') alert('Recording starts with the next connection.');
+  } else if (id === 'remote_server') {
+    try {
+      if (remoteServerPort) {
+        if (!confirm(`Stop the remote viewing server on port ${remoteServerPort}?`)) return;
+        await invoke('stop_server');
+        remoteServerPort = null;
+      } else {
+        const port = parseInt(prompt('Serve logs over WebSocket on port:', '9001'));
+        if (!port) return;
+        const bind = prompt('Listen on interface (127.0.0.1 keeps it on this machine, 0.0.0.0 for all):', '127.0.0.1')?.trim();
+        if (!bind) return;
+        let token = null;
+        if (!['127.0.0.1', '::1', 'localhost'].includes(bind)) {
+          token = prompt('Access token for remote viewers (they connect to ws://host:port/?token=...):')?.trim();
+          if (!token) return;
+        }
+        alert(await invoke('start_server', { port, bind: bind === 'localhost' ? null : bind, token }));
+        remoteServerPort = port;
+      }
+    } catch (e) { alert(errorMessage(e)); }
+  } else if (id === 'gdb_server') {
+    try {
+      if (gdbServerAddr) {
+        if (!confirm(`Stop the GDB server on ${gdbServerAddr}?`)) return;
+        await invoke('stop_gdb_server');
+        gdbServerAddr = null;
+      } else {
+        if (source !== 'rtt') { alert('Connect to a target first; GDB shares its session.'); return; }
+        const port = parseInt(prompt('Serve GDB on local port:', '1337'));
+        if (!port) return;
+        gdbServerAddr = await invoke('start_gdb_server', { port });
+        alert(`In GDB: target extended-remote ${gdbServerAddr}`);
+      }
+    } catch (e) { alert(errorMessage(e)); }
+  } else if (id === 'syslog') {
+    try {
+      if (syslogTarget) {
+        if (!confirm(`Stop forwarding logs to ${syslogTarget}?`)) return;
+        syslogTarget = null;
+        alert(await invoke('stop_syslog'));
+      } else {
+        const server = prompt('Syslog server as host:port, with tcp:// in front for TCP (UDP otherwise):', 'localhost:514')?.trim();
+        if (!server) return;
+        const m = server.match(/^(?:(udp|tcp):\/\/)?(.+):(\d+)$/i);
+        if (!m) { alert(`Expected host:port, got: ${server}`); return; }
+        const facility = prompt('Facility (local0-local7, user, or 0-23):', 'local0')?.trim();
+        if (!facility) return;
+        const protocol = (m[1] || 'udp').toLowerCase();
+        await invoke('log_to_syslog', { host: m[2], port: parseInt(m[3]), facility, protocol });
+        syslogTarget = `${protocol}://${m[2]}:${m[3]}`;
+      }
+    } catch (e) { alert(errorMessage(e)); }
+  } else if (id === 'mqtt') {
+    try {
+      if (mqttTarget) {
+        if (!confirm(`Stop publishing logs to ${mqttTarget}?`)) return;
+        mqttTarget = null;
+        alert(await invoke('stop_mqtt'));
+      } else {
+        const broker = prompt('MQTT broker (host, host:port or [IPv6]:port):', 'localhost:1883')?.trim();
+        if (!broker) return;
+        const topic = prompt('Topic:', `rtt/${Profiles.getSelectedProfile()?.name || 'device'}`)?.trim();
+        if (!topic) return;
+        const qos = parseInt(prompt('QoS (0, 1 or 2):', '0'));
+        if (![0, 1, 2].includes(qos)) { alert('QoS must be 0, 1 or 2.'); return; }
+        const perLevel = confirm(`Publish each level to its own subtopic (${topic}/error, ${topic}/info, ...)?\n\nOK: per level\nCancel: everything to ${topic}`);
+        const username = prompt('Username (empty for none):', '') || null;
+        const password = username ? prompt('Password:', '') : null;
+        mqttTarget = (await invoke('log_to_mqtt', { broker, topic, options: { qos, per_level: perLevel, username, password } }))
+          .replace(/^Publishing logs to /, '');
+      }
+    } catch (e) { alert(errorMessage(e)); }
+  } else if (id === 'benchmark') {
+    if (source !== 'rtt') { alert('Connect to a target first.'); return; }
+    const seconds = parseInt(prompt('Measure RTT throughput for how many seconds? Logs are paused meanwhile.', '10'));
+    if (!seconds) return;
+    try {
+      const r = await invoke('benchmark_rtt', { durationS: seconds });
+      const kib = n => (n / 1024).toFixed(1);
+      alert(`${kib(r.bytes)} KiB in ${(r.duration_ms / 1000).toFixed(1)} s\n` +
+        `Average: ${kib(r.average_bytes_per_sec)} KiB/s\nPeak: ${kib(r.peak_bytes_per_sec)} KiB/s\n` +
+        `Full-buffer reads: ${r.full_reads} of ${r.reads}` +
+        (r.full_reads ? ' (target out-paced the probe; data was dropped or the target blocked)' : ''));
+    } catch (e) { alert('Benchmark failed: ' + errorMessage(e)); }
+  } else if (id === 'enter_bootloader') {
+    if (source !== 'rtt') { alert('Connect to a target first.'); return; }
+    const chip = Profiles.getProfiles().find(p => p.name === activeProfileName)?.chip;
+    if (!chip || !confirm(`Restart ${chip} into its bootloader (DFU mode)? The application stops until DFU finishes.`)) return;
+    try {
+      await invoke('enter_bootloader', { chip });
+    } catch (e) { alert('Could not enter the bootloader: ' + errorMessage(e)); }
+  } else if (id === 'read_device_id') {
+    if (source !== 'rtt') { alert('Connect to a target first.'); return; }
+    const chip = Profiles.getProfiles().find(p => p.name === activeProfileName)?.chip;
+    if (!chip) return;
+    try {
+      const deviceId = await invoke('read_device_id', { chip });
+      prompt(`Unique ID of this ${chip}:`, deviceId);
+    } catch (e) { alert('Could not read the device ID: ' + errorMessage(e)); }
+  } else if (id === 'cycle_counter') {
+    if (source !== 'rtt') { alert('Connect to a target first.'); return; }
+    try {
+      const cycles = await invoke('read_cycle_counter');
+      if (confirm(`DWT_CYCCNT = ${cycles.toLocaleString()} cycles\n\nReset it to 0?`)) await invoke('reset_cycle_counter');
+    } catch (e) { alert('Cycle counter: ' + errorMessage(e)); }
+  } else if (id === 'control_blocks') {
+    const blocks = await invoke('list_control_blocks');
+    if (blocks.length < 2) { alert(blocks.length ? `One control block, at ${blocks[0].address}.` : 'Connect to a target first.'); return; }
+    const list = blocks.map(b => `${b.label}: ${b.address} on core ${b.core}, ${b.up_channels.length} up channel(s)`).join('\n');
+    const label = prompt(`${list}\n\nShow only the block with this label (empty for all):`, state.activeBlock ?? '');
+    if (label === null) return;
+    state.activeBlock = label.trim() || null;
+    rebuild(logArea);
+  } else if (id === 'last_error') {
+    const chain = await invoke('last_error_detail');
+    if (!chain.length) { alert('No connection error yet.'); return; }
+    // A prompt so the whole chain can be copied into a bug report
+    prompt('Last connection error (copy for a bug report):', chain.join(' → '));
+  } else if (id === 'verify_firmware') {
+    if (source !== 'rtt') { alert('Connect to a target first.'); return; }
+    const elfPath = Profiles.getProfiles().find(p => p.name === activeProfileName)?.elf_path;
+    if (!elfPath) { alert('The profile has no ELF to compare against.'); return; }
+    try {
+      const r = await invoke('verify_firmware_match', { elfPath });
+      const id = r.build_id ? `\nBuild ID: ${r.build_id}` : '';
+      if (r.matches) {
+        alert(`Firmware matches the ELF (${r.bytes_compared} bytes in ${r.regions_checked} regions).${id}`);
+      } else {
+        const m = r.mismatches[0];
+        alert(`WARNING: the running firmware does NOT match ${elfPath}.\n\n` +
+          `${r.mismatches.length} of ${r.regions_checked} regions differ; first at 0x${m.address.toString(16).toUpperCase().padStart(8, '0')}\n` +
+          `ELF:    ${m.expected}\nTarget: ${m.found}${id}\n\nSymbolized addresses from this ELF will be wrong. Reflash, or load the ELF that was flashed.`);
+      }
+    } catch (e) { alert('Verify failed: ' + errorMessage(e)); }
+  } else if (id === 'read_register') {
+    if (source !== 'rtt') { alert('Connect to a target first.'); return; }
+    try {
+      let name = svdDevice ? prompt(`Register to read from ${svdDevice} (PERIPHERAL.REGISTER), or leave empty to load another SVD file:`, lastRegister) : '';
+      if (name == null) return;
+      if (!name.trim()) {
+        const openDialog = window.__TAURI__.dialog?.open;
+        const path = openDialog
+          ? await openDialog({ multiple: false, filters: [{ name: 'CMSIS-SVD', extensions: ['svd', 'xml'] }] })
+          : prompt('Enter full path to the chip\'s .svd file:');
+        if (!path) return;
+        const svd = await invoke('load_svd', { path });
+        svdDevice = svd.device;
+        name = prompt(`Loaded ${svd.device}: ${svd.peripherals} peripherals, ${svd.registers} registers.\nRegister to read (PERIPHERAL.REGISTER):`, lastRegister);
+        if (!name?.trim()) return;
+      }
+      const dot = name.indexOf('.');
+      if (dot < 0) { alert('Expected PERIPHERAL.REGISTER, e.g. UARTE0.BAUDRATE'); return; }
+      lastRegister = name.trim();
+      const r = await invoke('read_register', { peripheral: name.slice(0, dot).trim(), register: name.slice(dot + 1).trim() });
+      const hex = n => '0x' + n.toString(16).toUpperCase();
+      alert(`${r.peripheral}.${r.register} @ ${hex(r.address)} = ${hex(r.value)}\n\n` +
+        r.fields.map(f => `${f.name} [${f.bit_offset + f.bit_width - 1}:${f.bit_offset}] = ${hex(f.value)}${f.meaning ? ` (${f.meaning})` : ''}`).join('\n'));
+    } catch (e) { alert('Register read failed: ' + errorMessage(e)); }
+  } else if (id === 'poke_memory') {
+    if (source !== 'rtt') { alert('Connect to a target first.'); return; }
+    const target = prompt('Variable from the ELF, or a 0x address:', lastPoke);
+    if (!target?.trim()) return;
+    const valueText = prompt(`New value for ${target.trim()} (decimal or 0x hex):`);
+    if (!valueText?.trim()) return;
+    const value = Number(valueText.trim());
+    if (!Number.isSafeInteger(value) || value < 0) { alert(`Not a value that can be written: ${valueText}`); return; }
+    const sizeText = prompt('Size in bytes (1, 2, 4 or 8; empty for the variable\'s own size):', '');
+    if (sizeText == null) return;
+    const halt = confirm('Halt the core for the write? Safer for values the firmware is using right now.');
+    lastPoke = target.trim();
+    try {
+      await invoke('poke_memory', { target: lastPoke, value, size: parseInt(sizeText) || null, halt });
+    } catch (e) { alert('Write failed: ' + errorMessage(e)); }
+  } else if (id === 'unwind_fault') {
+    if (source !== 'rtt') { alert('Connect to a target first.'); return; }
+    // The backtrace arrives as a log entry; only errors need handling here
+    try {
+      await invoke('unwind_fault');
+    } catch (e) { alert('Backtrace failed: ' + errorMessage(e)); }
+  } else if (id === 'error_threshold') {
+    const value = parseInt(prompt('Failed reads in a row before reconnecting (1-100). Higher tolerates a flaky cable but notices a real disconnect later:', errorThreshold));
+    if (!value) return;
+    try {
+      await invoke('set_error_threshold', { n: value });
+      errorThreshold = value;
+      scheduleUiSave();
+    } catch (e) { alert(errorMessage(e)); }
+  } else if (id === 'emit_batching') {
+    const msText = prompt('Render new lines every N ms (0: each line as it arrives, empty: automatic from the line rate). Lower is snappier, higher eases a slow window under a flood:',
+      emitBatching.batchMs ?? '');
+    if (msText == null) return;
+    const sizeText = prompt('...or as soon as this many lines are waiting:', emitBatching.batchSize);
+    if (sizeText == null) return;
+    const next = { batchMs: msText.trim() === '' ? null : parseInt(msText), batchSize: parseInt(sizeText) };
+    if (Number.isNaN(next.batchMs) || Number.isNaN(next.batchSize)) { alert('Expected whole numbers.'); return; }
+    try {
+      const applied = await invoke('set_emit_batching', next);
+      emitBatching = { batchMs: applied.batch_ms, batchSize: applied.batch_size };
+      scheduleUiSave();
+      if (emitBatching.batchMs !== next.batchMs || emitBatching.batchSize !== next.batchSize) {
+        alert(`Limited to ${emitBatching.batchMs ?? 'automatic'} ms and ${emitBatching.batchSize} lines.`);
+      }
+    } catch (e) { alert(errorMessage(e)); }
+  } else if (id === 'read_mode') {
+    const value = prompt('Read mode (poll or block). Block waits for data instead of sleeping 10 ms between reads (RTT re-checks sooner right after data): lower latency on bursty logs, with a few more probe reads.', readMode)?.trim().toLowerCase();
+    if (!value) return;
+    try {
+      await invoke('set_read_mode', { mode: value });
+      readMode = value;
+      scheduleUiSave();
+    } catch (e) { alert(errorMessage(e)); }
+  } else if (id === 'flight_recorder') {
+    try {
+      if (flightRecorderArmed) {
+        if (confirm('Dump the flight recorder window to a file now?')) await dumpFlightRecorder();
+        else if (confirm('Disarm the flight recorder?')) {
+          await invoke('disarm_flight_recorder');
+          flightRecorderArmed = false;
+        }
+        return;
+      }
+      const windowS = parseFloat(prompt('Keep the last how many seconds of logs?', '30'));
+      if (!windowS) return;
+      const pattern = prompt('Dump when a line matches this regex (leave empty to dump by hand):', '');
+      if (pattern == null) return;
+      await invoke('arm_flight_recorder', { windowS, triggerPattern: pattern.trim() || null });
+      flightRecorderArmed = true;
+    } catch (e) { alert('Flight recorder: ' + errorMessage(e)); }
+  } else if (id === 'log_timeline') {
+    const canvas = $('#timeline');
+    if (canvas.classList.toggle('open')) {
+      try { await renderTimeline(); } catch (e) { canvas.classList.remove('open'); alert('Timeline failed: ' + errorMessage(e)); }
+    }
+  } else if (id === 'measure_latency') {
+    const from = prompt('Start event (regex on the raw line; (?P<key>...) in both patterns pairs by key):', lastLatency.from);
+    if (!from) return;
+    const to = prompt('End event (regex on the raw line):', lastLatency.to || from);
+    if (!to) return;
+    lastLatency = { from, to };
+    try {
+      const pairs = await invoke('measure_latency', { entries: state.logs, fromPattern: from, toPattern: to });
+      alert(latencySummary(pairs));
+    } catch (e) { alert('Latency failed: ' + errorMessage(e)); }
+  } else if (id === 'create_view') {
+    const name = prompt('Keep the current filter results as a live view named:')?.trim();
+    if (!name) return;
+    try {
+      await openView(name);
+    } catch (e) { alert(errorMessage(e)); }
+  } else if (id === 'save_filter_preset') {
+    const name = prompt('Save the current level, tag and terminal filters as preset:')?.trim();
+    if (!name) return;
+    try {
+      await invoke('save_filter_preset', { name, filter: currentFilter() });
+    } catch (e) { alert(errorMessage(e)); }
+  } else if (id === 'apply_filter_preset') {
+    try {
+      const { active, presets } = await invoke('list_filter_presets');
+      const names = Object.keys(presets);
+      if (!names.length) { alert('No filter presets yet. Use "Save Filter Preset…" first.'); return; }
+      const input = prompt(`Presets: ${names.join(', ')}\nApply which one? Leave empty to clear filters, prefix with - to delete.`, active || '');
+      if (input == null) return;
+      const name = input.trim();
+      if (name.startsWith('-')) {
+        await invoke('delete_filter_preset', { name: name.slice(1).trim() });
+        if (name.slice(1).trim() === active) showFilter({});
+        return;
+      }
+      showFilter(await invoke('apply_filter_preset', { name: name || null }));
+    } catch (e) { alert(errorMessage(e)); }
+  } else if (id === 'line_ending') {
+    const value = prompt('Line endings for exports and copies (lf, crlf or native):', lineEnding)?.trim().toLowerCase();
+    if (!value) return;
+    if (!['lf', 'crlf', 'native'].includes(value)) { alert(`Unknown line ending: ${value}`); return; }
+    lineEnding = value;
+    scheduleUiSave();
+  } else if (id === 'ascii_only') {
+    asciiOnly = confirm(`Escape non-ASCII text in exports and copies, for tools that only read ASCII?\n\n` +
+      `OK: ASCII only (\\xNN bytes; \\uXXXX in JSON, &#N; in HTML)\nCancel: keep UTF-8\n\nCurrently: ${asciiOnly ? 'ASCII only' : 'UTF-8'}`);
+    scheduleUiSave();
+  } else if (id === 'status_in_log') {
+    const enabled = confirm(`Also put status messages ("Target attached", "Connection lost") in the log, where exports and search include them?\n\n` +
+      `OK: status in the log too\nCancel: status bar only\n\nCurrently: ${statusInLog ? 'in the log' : 'status bar only'}`);
+    try {
+      await invoke('set_status_in_log', { enabled });
+      statusInLog = enabled;
+      scheduleUiSave();
+    } catch (e) { alert('Failed: ' + errorMessage(e)); }
+  } else if (id === 'export_sqlite') {
+    try {
+      await LogIO.exportSqlite(Profiles.getSelectedProfile()?.name);
+    } catch (e) { alert('Export failed: ' + errorMessage(e)); }
+  } else if (id === 'export_html') {
+    try {
+      await LogIO.exportHtml(Profiles.getSelectedProfile()?.name, asciiOnly);
+    } catch (e) { alert('Export failed: ' + errorMessage(e)); }
+  } else if (id === 'export_continuous') {
+    if (continuousExport) {
+      if (confirm(`Stop exporting to ${continuousExport.path}?`)) {
+        clearInterval(continuousExport.timer);
+        continuousExport = null;
+      }
+      return;
+    }
+    const format = prompt('Continuously export new entries as (ndjson, csv or txt):', 'ndjson')?.trim().toLowerCase();
+    if (!format) return;
+    if (!['ndjson', 'csv', 'txt'].includes(format)) { alert(`Unknown format: ${format}`); return; }
+    const saveDialog = window.__TAURI__.dialog?.save;
+    const defaultName = await LogIO.defaultExportPath(format, Profiles.getSelectedProfile()?.name);
+    const path = saveDialog
+      ? await saveDialog({ defaultPath: defaultName, filters: [{ name: format.toUpperCase(), extensions: [format] }] })
+      : prompt('Append entries to file:', defaultName);
+    if (!path) return;
+    continuousExport = { path, format, lastId: -1, timer: setInterval(appendExportTick, CONTINUOUS_EXPORT_MS) };
+    appendExportTick();
   } else if (id.startsWith('export_')) {
     const format = id.replace('export_', '');
     try {
-      await LogIO.exportLogs(format);
-    } catch (e) { alert('Export failed: ' + e); }
+      await LogIO.exportLogs(format, lineEnding, Profiles.getSelectedProfile()?.name, asciiOnly);
+    } catch (e) { alert('Export failed: ' + errorMessage(e)); }
+  } else if (id.startsWith('copy_')) {
+    const format = id.replace('copy_', '');
+    try {
+      await invoke('copy_to_clipboard', { entries: state.logs.filter(matches), format, lineEnding, asciiOnly });
+    } catch (e) { alert('Copy failed: ' + errorMessage(e)); }
   }
 });
 
+// ── Filtered views ──
+
+const VIEW_MAX_LINES = 2000;
+
+// Freeze the current filter into a pane the backend keeps feeding (view-log:{id})
+async function dumpFlightRecorder() {
+  const saveDialog = window.__TAURI__.dialog?.save;
+  const path = saveDialog
+    ? await saveDialog({ defaultPath: 'flight-recorder.txt', filters: [{ name: 'Text', extensions: ['txt'] }] })
+    : prompt('Write the flight recorder window to:', 'flight-recorder.txt');
+  if (!path) return;
+  try {
+    alert(await invoke('dump_flight_recorder', { path }));
+  } catch (e) { alert('Flight recorder: ' + errorMessage(e)); }
+}
+
+async function openView(name) {
+  const filter = {
+    ...currentFilter(),
+    tags: [...state.activeTags],
+    text: state.searchMode === 'filter' ? searchInput.value.trim() || null : null,
+  };
+  const id = await invoke('create_view', { name, filter });
+  const pane = document.createElement('div');
+  pane.className = 'view-pane';
+  pane.innerHTML = `<div class="view-head"><span class="view-name">${esc(name)}</span><span class="view-count"></span><button class="btn view-close" title="Close view">✕</button></div><div class="view-body"></div>`;
+  $('#viewPanes').appendChild(pane);
+  const body = pane.querySelector('.view-body');
+  const count = pane.querySelector('.view-count');
+  let lines = 0;
+  const add = html => {
+    body.insertAdjacentHTML('beforeend', html);
+    lines += 1;
+    while (body.childElementCount > VIEW_MAX_LINES) body.firstElementChild.remove();
+    count.textContent = lines;
+    body.scrollTop = body.scrollHeight;
+  };
+  // Seed with what's on screen now; live entries follow from the backend
+  for (const e of state.logs.filter(matches).slice(-VIEW_MAX_LINES)) add(renderLine(e));
+  const unlisten = await listen(`view-log:${id}`, ev => add(renderLine(ev.payload)));
+  pane.querySelector('.view-close').addEventListener('click', async () => {
+    unlisten();
+    pane.remove();
+    await invoke('close_view', { id }).catch(e => console.warn('close_view failed:', errorMessage(e)));
+  });
+}
+
+// ── Session diff ──
+
+// Compare a saved JSON export (left) with what's on screen now (right)
+async function diffAgainstFile() {
+  if (state.logs.length === 0) { alert('Nothing captured to compare against'); return; }
+  const openDialog = window.__TAURI__.dialog?.open;
+  const path = openDialog
+    ? await openDialog({ multiple: false, filters: [{ name: 'JSON', extensions: ['json'] }] })
+    : prompt('Enter full path to a JSON log export:');
+  if (!path) return;
+  const baseline = JSON.parse(await invoke('read_text_file', { path }));
+  if (!Array.isArray(baseline)) throw new Error('Expected a JSON log export');
+  const current = state.logs;
+  const diff = await invoke('diff_sessions', { a: baseline, b: current });
+
+  const line = e => e ? esc(`${e.tag ? `<${e.tag}> ` : ''}${e.message}`) : '';
+  let html = '';
+  for (const h of diff.hunks) {
+    if (h.kind === 'equal') {
+      html += `<div class="diff-row equal-run">${h.a_len} identical line${h.a_len === 1 ? '' : 's'}</div>`;
+      continue;
+    }
+    for (let i = 0; i < Math.max(h.a_len, h.b_len); i++) {
+      const a = i < h.a_len ? baseline[h.a_start + i] : null;
+      const b = i < h.b_len ? current[h.b_start + i] : null;
+      html += `<div class="diff-row"><div class="${a ? 'removed' : ''}">${line(a)}</div><div class="${b ? 'added' : ''}">${line(b)}</div></div>`;
+    }
+  }
+  $('#diffSummary').textContent = `${path.split(/[\\/]/).pop()} → current capture: ${diff.removed} removed, ${diff.added} added`;
+  $('#diffView').innerHTML = html;
+  $('#diffModal').classList.add('open');
+}
+
+$('#closeDiffModal').addEventListener('click', () => $('#diffModal').classList.remove('open'));
+
 // ── Probe enumeration ──
 
 async function refreshProbes() {
@@ -60,11 +533,12 @@ async function refreshProbes() {
     btnRefreshProbes.disabled = true;
     btnRefreshProbes.textContent = '…';
     const probes = await invoke('list_probes');
+    probeList = probes;
     probeSelect.innerHTML = probes.length === 0
       ? '<option value="">— no probes —</option>'
       : probes.map(p => {
           const label = p.serial ? `${p.name} (${p.serial})` : p.name;
-          return `<option value="${p.index}">${esc(label)}</option>`;
+          return `<option value="${p.index}" title="${esc(probeCapsText(p.caps))}">${esc(label)}</option>`;
         }).join('');
   } catch (e) {
     probeSelect.innerHTML = `<option value="">— error —</option>`;
@@ -74,6 +548,15 @@ async function refreshProbes() {
   }
 }
 
+function probeCapsText(caps) {
+  if (!caps) return 'Capabilities unknown (probe busy?)';
+  const parts = [caps.protocols.join('/') || 'no protocols'];
+  if (caps.max_speed_khz) parts.push(`up to ${caps.max_speed_khz} kHz`);
+  if (caps.swo) parts.push('SWO');
+  if (caps.target_power) parts.push('target power');
+  return parts.join(' · ');
+}
+
 btnRefreshProbes.addEventListener('click', refreshProbes);
 
 // ── UI State ──
@@ -83,22 +566,265 @@ function updateUI() {
     btnConnect.textContent = 'Disconnect';
     btnConnect.className = 'btn red';
     btnMock.disabled = true;
+    btnSwo.disabled = true;
+    btnFifo.disabled = true;
+    dot.className = 'dot on';
+  } else if (source === 'swo') {
+    btnSwo.textContent = 'Stop';
+    btnSwo.className = 'btn red';
+    btnConnect.disabled = true;
+    btnMock.disabled = true;
+    btnFifo.disabled = true;
+    dot.className = 'dot on';
+  } else if (source === 'fifo') {
+    btnFifo.textContent = 'Stop';
+    btnFifo.className = 'btn red';
+    btnConnect.disabled = true;
+    btnMock.disabled = true;
+    btnSwo.disabled = true;
     dot.className = 'dot on';
   } else if (source === 'mock') {
     btnMock.textContent = 'Stop';
     btnMock.className = 'btn red';
     btnConnect.disabled = true;
+    btnSwo.disabled = true;
+    btnFifo.disabled = true;
     dot.className = 'dot mock';
   } else {
     btnConnect.textContent = 'Connect';
     btnConnect.className = 'btn green';
     btnConnect.disabled = false;
+    btnSwo.textContent = 'SWO';
+    btnSwo.className = 'btn';
+    btnSwo.disabled = false;
+    btnFifo.textContent = 'FIFO';
+    btnFifo.className = 'btn';
+    btnFifo.disabled = false;
     btnMock.textContent = 'Mock';
     btnMock.className = 'btn';
     btnMock.disabled = false;
     dot.className = 'dot';
   }
+  btnPing.disabled = source !== 'rtt';
+  btnWatch.disabled = source !== 'rtt';
+  btnTrigger.disabled = source !== 'rtt';
+  btnSnapshot.disabled = source !== 'rtt';
   countEl.textContent = state.logs.length;
+  scheduleUiSave();
+}
+
+// ── Persisted UI state ──
+
+let uiSaveTimer = null;
+let uiRestored = false;
+
+function scheduleUiSave() {
+  if (!uiRestored) return; // don't overwrite the saved state before it's applied
+  clearTimeout(uiSaveTimer);
+  uiSaveTimer = setTimeout(() => {
+    const probe = probeList.find(p => String(p.index) === probeSelect.value);
+    invoke('save_ui_state', {
+      ui: {
+        last_source: source,
+        profile: $('#profileSelect').value || null,
+        probe_serial: probe?.serial ?? null,
+        enabled_levels: [...state.enabledLevels],
+        excluded_tags: [...state.excludedTags],
+        search_mode: state.searchMode,
+        time_base: state.timeBase,
+        line_ending: lineEnding,
+        ascii_only: asciiOnly,
+        error_threshold: errorThreshold,
+        read_mode: readMode,
+        status_in_log: statusInLog,
+        emit_batch_ms: emitBatching.batchMs,
+        emit_batch_size: emitBatching.batchSize,
+      }
+    }).catch(e => console.warn('save_ui_state failed:', errorMessage(e)));
+  }, 500);
+}
+
+async function restoreUiState() {
+  let ui;
+  try { ui = await invoke('load_ui_state'); } catch (e) { ui = {}; }
+  if (ui.profile && Profiles.getProfiles().some(p => p.name === ui.profile)) {
+    $('#profileSelect').value = ui.profile;
+  }
+  const probe = ui.probe_serial && probeList.find(p => p.serial === ui.probe_serial);
+  if (probe) probeSelect.value = probe.index;
+  if (ui.enabled_levels) state.enabledLevels = new Set(ui.enabled_levels);
+  for (const t of ui.excluded_tags || []) state.excludedTags.add(t);
+  if (ui.search_mode && MODES.includes(ui.search_mode)) {
+    while (state.searchMode !== ui.search_mode) cycleSearchMode();
+  }
+  if (ui.time_base === 'host') await setTimeBase('host');
+  if (['lf', 'crlf', 'native'].includes(ui.line_ending)) lineEnding = ui.line_ending;
+  asciiOnly = !!ui.ascii_only;
+  if (ui.error_threshold) {
+    try {
+      await invoke('set_error_threshold', { n: ui.error_threshold });
+      errorThreshold = ui.error_threshold;
+    } catch (e) { console.warn('set_error_threshold failed:', errorMessage(e)); }
+  }
+  if (ui.read_mode === 'block') {
+    try {
+      await invoke('set_read_mode', { mode: 'block' });
+      readMode = 'block';
+    } catch (e) { console.warn('set_read_mode failed:', errorMessage(e)); }
+  }
+  if (ui.emit_batch_ms != null || ui.emit_batch_size) {
+    const saved = { batchMs: ui.emit_batch_ms ?? null, batchSize: ui.emit_batch_size || emitBatching.batchSize };
+    try {
+      const applied = await invoke('set_emit_batching', saved);
+      emitBatching = { batchMs: applied.batch_ms, batchSize: applied.batch_size };
+    } catch (e) { console.warn('set_emit_batching failed:', errorMessage(e)); }
+  }
+  if (ui.status_in_log) {
+    try {
+      await invoke('set_status_in_log', { enabled: true });
+      statusInLog = true;
+    } catch (e) { console.warn('set_status_in_log failed:', errorMessage(e)); }
+  }
+  uiRestored = true;
+}
+
+// ── Filter presets ──
+// Presets set the backend's emit-time filters; the chips mirror them so the
+// view shows why lines are missing
+
+const ALL_LEVELS = ['error', 'warn', 'info', 'debug', 'raw'];
+
+function currentFilter() {
+  const levels = [...state.enabledLevels];
+  return {
+    levels: levels.length === ALL_LEVELS.length ? [] : levels,
+    excluded_tags: [...state.excludedTags],
+    terminals: state.activeTerminals ? [...state.activeTerminals] : [],
+    channels: [],
+  };
+}
+
+function showFilter(filter) {
+  state.enabledLevels = new Set(filter.levels?.length ? filter.levels : ALL_LEVELS);
+  state.excludedTags = new Set(filter.excluded_tags || []);
+  state.activeTerminals = filter.terminals?.length ? new Set(filter.terminals) : null;
+  renderTagbar();
+  renderTermbar();
+  rebuild(logArea);
+  scheduleUiSave();
+}
+
+async function restoreFilterPreset() {
+  try {
+    const { active, presets } = await invoke('list_filter_presets');
+    if (active && presets[active]) showFilter(presets[active]);
+  } catch (e) { console.warn('list_filter_presets failed:', errorMessage(e)); }
+}
+
+// ── Watch bar ──
+
+const WATCH_KINDS = ['u8', 'i8', 'u16', 'i16', 'u32', 'i32', 'u64', 'i64', 'f32', 'f64'];
+const watches = new Map(); // symbol -> last value (null until the first read)
+
+function renderWatchbar() {
+  watchbar.classList.toggle('visible', watches.size > 0);
+  let html = watches.size ? '<span class="termbar-label">Watch</span>' : '';
+  for (const [symbol, value] of watches) {
+    const v = value == null ? '…' : typeof value === 'number' && !Number.isInteger(value) ? value.toPrecision(6) : value;
+    html += `<span class="term-chip" data-symbol="${esc(symbol)}" title="Click to stop watching">${esc(symbol)} = <b>${esc(String(v))}</b></span>`;
+  }
+  watchbar.innerHTML = html;
+  watchbar.querySelectorAll('.term-chip').forEach(el => {
+    el.addEventListener('click', async () => {
+      const symbol = el.dataset.symbol;
+      watches.delete(symbol);
+      renderWatchbar();
+      await invoke('remove_watch', { symbol }).catch(() => {});
+    });
+  });
+}
+
+btnWatch.addEventListener('click', async () => {
+  const symbol = prompt('Global variable to watch (from the profile ELF)')?.trim();
+  if (!symbol) return;
+  const kind = prompt(`Type (${WATCH_KINDS.join(', ')})`, 'u32')?.trim();
+  if (!kind) return;
+  if (!WATCH_KINDS.includes(kind)) { alert(`Unknown type: ${kind}`); return; }
+  try {
+    await invoke('add_watch', { symbol, kind });
+    watches.set(symbol, null);
+    renderWatchbar();
+  } catch (e) {
+    alert(errorMessage(e));
+  }
+});
+
+listen('watch-update', e => {
+  if (!watches.has(e.payload.symbol)) return;
+  watches.set(e.payload.symbol, e.payload.value);
+  renderWatchbar();
+});
+
+// ── Log trigger ──
+
+let triggerArmed = false;
+
+function setTriggerArmed(armed) {
+  triggerArmed = armed;
+  btnTrigger.className = armed ? 'btn red' : 'btn';
+  btnTrigger.textContent = armed ? 'Armed' : 'Trigger';
+}
+
+btnTrigger.addEventListener('click', async () => {
+  try {
+    if (triggerArmed) {
+      await invoke('clear_log_trigger');
+      setTriggerArmed(false);
+      return;
+    }
+    const pattern = prompt('Halt the core when a line matches (regex)');
+    if (!pattern) return;
+    await invoke('set_log_trigger', { pattern, action: 'halt' });
+    setTriggerArmed(true);
+    btnTrigger.title = `Armed: /${pattern}/ — click to clear`;
+  } catch (e) {
+    alert(errorMessage(e));
+  }
+});
+
+listen('trigger-fired', e => {
+  setTriggerArmed(false);
+  btnTrigger.title = 'Halt the core when a log line matches a regex';
+  const { message, pc } = e.payload;
+  alert(pc != null
+    ? `Core halted at PC 0x${pc.toString(16).toUpperCase().padStart(8, '0')} on:\n${message}`
+    : `Trigger matched but the core could not be halted:\n${message}`);
+});
+
+// The snapshot arrives as a log entry; only errors need handling here
+btnSnapshot.addEventListener('click', async () => {
+  try {
+    await invoke('core_snapshot', {});
+  } catch (e) {
+    alert(errorMessage(e));
+  }
+});
+
+// -- Latency --
+
+function latencySummary(pairs) {
+  const done = pairs.filter(p => p.latency_us != null);
+  const unmatched = pairs.filter(p => p.end_id == null).length;
+  if (!done.length) return `No complete start → end pairs (${unmatched} starts without an end).`;
+  const ms = us => `${(us / 1000).toFixed(3)} ms`;
+  const sorted = done.map(p => p.latency_us).sort((a, b) => a - b);
+  const mean = sorted.reduce((a, b) => a + b, 0) / sorted.length;
+  const clocks = [...new Set(done.map(p => p.clock))].join(' + ');
+  const slowest = [...done].sort((a, b) => b.latency_us - a.latency_us).slice(0, 5)
+    .map(p => `  ${ms(p.latency_us)}  #${p.start_id} → #${p.end_id}${p.key != null ? `  (${p.key})` : ''}`);
+  return `${done.length} pairs (${clocks} time), ${unmatched} starts without an end\n\n` +
+    `min ${ms(sorted[0])}   median ${ms(sorted[Math.floor(sorted.length / 2)])}   ` +
+    `mean ${ms(mean)}   max ${ms(sorted[sorted.length - 1])}\n\nSlowest:\n${slowest.join('\n')}`;
 }
 
 // ── Terminal bar ──
@@ -114,7 +840,9 @@ function renderTermbar() {
   for (const [id, count] of sorted) {
     const c = terminalColor(id);
     const isActive = !allActive && state.activeTerminals.has(id);
-    html += `<span class="term-chip${isActive ? ' active' : ''}" data-term="${id}" style="color:${c}">${id}<span class="term-count">${count}</span></span>`;
+    const name = state.terminalNames.get(id);
+    const label = name ? `<span title="Terminal ${id}">${esc(name)}</span>` : id;
+    html += `<span class="term-chip${isActive ? ' active' : ''}" data-term="${id}" style="color:${c}">${label}<span class="term-count">${count}</span></span>`;
   }
 
   termbar.innerHTML = html;
@@ -163,6 +891,7 @@ function renderTagbar() {
     state.enabledLevels.has(l) ? state.enabledLevels.delete(l) : state.enabledLevels.add(l);
     renderTagbar();
     rebuild(logArea);
+    scheduleUiSave();
   }));
 
   tagbar.querySelectorAll('.tag-chip').forEach(el => el.addEventListener('click', ev => {
@@ -174,6 +903,7 @@ function renderTagbar() {
     }
     renderTagbar();
     rebuild(logArea);
+    scheduleUiSave();
   }));
 }
 
@@ -190,34 +920,176 @@ btnConnect.addEventListener('click', async () => {
   const prof = Profiles.getSelectedProfile();
   if (!prof) { alert('Select a profile first'); return; }
   const probeIdx = probeSelect.value !== '' ? parseInt(probeSelect.value) : null;
+  await startRtt(prof, probeIdx);
+});
+
+async function startRtt(prof, probeIdx) {
   try {
-    await invoke('start_rtt', {
-      chip: prof.chip,
-      rttAddress: prof.rtt_address || null,
-      coreIndex: prof.core || 0,
-      probeIndex: probeIdx,
-    });
+    await invoke('start_rtt', { profile: prof, probeIndex: probeIdx, logToSqlite: recordSqlitePath });
     source = 'rtt';
+    activeProfileName = prof.name;
     updateUI();
-  } catch (e) { alert(e); }
+  } catch (e) {
+    if (e?.kind === 'NoProbe') {
+      alert('No debug probe found. Connect a probe and press ⟳ to refresh.');
+      refreshProbes();
+    } else {
+      alert(errorMessage(e));
+    }
+  }
+}
+
+// Editing the connected profile applies it live; the backend decides whether a re-attach is needed
+document.addEventListener('profile-saved', async e => {
+  if (source !== 'rtt' || !e.detail || e.detail.name !== activeProfileName) return;
+  try {
+    await invoke('restart_rtt', { profile: e.detail });
+  } catch (err) { console.warn('restart_rtt failed:', errorMessage(err)); }
 });
 
-btnMock.addEventListener('click', async () => {
-  if (source === 'mock') {
+$('#btnMarker').addEventListener('click', async () => {
+  const text = prompt('Marker text', `Marker ${new Date().toLocaleTimeString()}`);
+  if (text == null || !text.trim()) return;
+  try {
+    await invoke('insert_marker', { text });
+  } catch (e) {
+    alert(errorMessage(e));
+  }
+});
+
+btnPing.addEventListener('click', async () => {
+  btnPing.disabled = true;
+  try {
+    const ms = await invoke('rtt_ping');
+    btnPing.textContent = `${ms.toFixed(1)} ms`;
+  } catch (e) {
+    btnPing.textContent = 'no echo';
+    btnPing.title = errorMessage(e);
+  } finally {
+    btnPing.disabled = source !== 'rtt';
+    setTimeout(() => { btnPing.textContent = 'Ping'; }, 3000);
+  }
+});
+
+btnSwo.addEventListener('click', async () => {
+  if (source === 'swo') {
     await invoke('stop_source');
     source = null;
     updateUI();
     return;
   }
+  const prof = Profiles.getSelectedProfile();
+  if (!prof) { alert('Select a profile first'); return; }
+  const coreFreq = parseInt(prompt('Core clock (Hz):', localStorage.getItem('swoCoreFreq') || '64000000'));
+  if (!coreFreq) return;
+  const baud = parseInt(prompt('SWO baud rate:', localStorage.getItem('swoBaud') || '2000000'));
+  if (!baud) return;
+  const trace = prompt('Also decode DWT trace: "exceptions", "pc" (PC sampling), both, or empty for none:', localStorage.getItem('swoTrace') || '');
+  if (trace === null) return;
+  localStorage.setItem('swoCoreFreq', coreFreq);
+  localStorage.setItem('swoBaud', baud);
+  localStorage.setItem('swoTrace', trace);
+  const probeIdx = probeSelect.value !== '' ? parseInt(probeSelect.value) : null;
   try {
-    await invoke('start_mock');
+    await invoke('start_swo', {
+      chip: prof.chip, coreFreq, baud, probeIndex: probeIdx,
+      exceptionTrace: /exc/i.test(trace), pcSampling: /\bpc\b/i.test(trace),
+    });
+    source = 'swo';
+    updateUI();
+  } catch (e) { alert(errorMessage(e)); }
+});
+
+btnFifo.addEventListener('click', async () => {
+  if (source === 'fifo') {
+    await invoke('stop_source');
+    source = null;
+    updateUI();
+    return;
+  }
+  const path = prompt('Named pipe to read (create it with mkfifo):', localStorage.getItem('fifoPath') || '/tmp/rtt-viewer.fifo')?.trim();
+  if (!path) return;
+  localStorage.setItem('fifoPath', path);
+  try {
+    await invoke('start_fifo', { path });
+    source = 'fifo';
+    updateUI();
+  } catch (e) { alert(errorMessage(e)); }
+});
+
+// Shift-click: timestamps that jump back, repeat and wrap, to exercise time sorting
+async function startMock(jitter) {
+  try {
+    await invoke('start_mock', { jitter });
     source = 'mock';
     updateUI();
-  } catch (e) { alert(e); }
+  } catch (e) { alert(errorMessage(e)); }
+}
+
+btnMock.addEventListener('click', async e => {
+  if (source === 'mock') {
+    await invoke('stop_source');
+    source = null;
+    updateUI();
+    return;
+  }
+  await startMock(e.shiftKey);
+});
+
+// ── Log volume timeline ──
+
+// One bar per bucket: total volume in grey, errors and warnings stacked in color
+let timelineBuckets = [];
+
+async function renderTimeline() {
+  const canvas = $('#timeline');
+  canvas.width = canvas.clientWidth;
+  const stamped = state.logs.filter(e => e.host_timestamp != null);
+  const span = stamped.length ? stamped[stamped.length - 1].host_timestamp - stamped[0].host_timestamp : 0;
+  // About two pixels per bar, in whole 10 ms steps
+  const bucketMs = Math.max(10, Math.ceil(span / (canvas.width / 2) / 10) * 10);
+  const hist = await invoke('log_histogram', { entries: state.logs, bucketMs });
+  timelineBuckets = hist.buckets;
+  const ctx = canvas.getContext('2d');
+  const css = getComputedStyle(document.documentElement);
+  ctx.clearRect(0, 0, canvas.width, canvas.height);
+  const max = Math.max(1, ...hist.buckets.map(b => b.count));
+  const w = canvas.width / Math.max(1, hist.buckets.length);
+  hist.buckets.forEach((b, i) => {
+    const h = n => n / max * canvas.height;
+    const err = b.by_level.error ?? 0, warn = b.by_level.warn ?? 0;
+    ctx.fillStyle = css.getPropertyValue('--dim');
+    ctx.fillRect(i * w, canvas.height - h(b.count), Math.max(1, w - 1), h(b.count));
+    ctx.fillStyle = css.getPropertyValue('--orange');
+    ctx.fillRect(i * w, canvas.height - h(err + warn), Math.max(1, w - 1), h(warn));
+    ctx.fillStyle = css.getPropertyValue('--red');
+    ctx.fillRect(i * w, canvas.height - h(err), Math.max(1, w - 1), h(err));
+  });
+  canvas.title = `Log volume, ${bucketMs} ms per bar; click to jump there` +
+    (hist.excluded ? `\n${hist.excluded} entries without a host timestamp are left out` : '');
+}
+
+$('#timeline').addEventListener('click', ev => {
+  const canvas = ev.currentTarget;
+  const bucket = timelineBuckets[Math.floor(ev.offsetX / canvas.clientWidth * timelineBuckets.length)];
+  if (!bucket) return;
+  const entry = state.logs.find(e => e.host_timestamp >= bucket.start && matches(e));
+  const el = entry && logArea.querySelector(`[data-id="${entry.id}"]`);
+  if (el) {
+    el.scrollIntoView({ block: 'center' });
+    state.autoScroll = false;
+  }
 });
 
 $('#btnClear').addEventListener('click', () => {
+  $('#timeline').classList.remove('open');
+  pendingLogs = [];
+  hiddenByFilter = 0;
+  lostEvents = 0;
+  $('#gapInfo').textContent = '';
   clearLogs(logArea);
+  indexComplete = true;
+  invoke('clear_search_index').catch(() => {});
   searchInput.value = '';
   updateSearchInfo();
   renderTagbar();
@@ -274,7 +1146,55 @@ document.addEventListener('keydown', e => {
 
 // ── Tauri Events ──
 
+// Under a flood the backend's rtt-stats suggests a render interval; lines
+// wait in pendingLogs and go into the DOM in one batch per interval, or
+// sooner once batchSize of them are waiting
+let refreshMs = 0;
+let batchSize = Infinity;
+let pendingLogs = [];
+let flushTimer = null;
+
+function flushPendingLogs() {
+  flushTimer = null;
+  if (!pendingLogs.length) return;
+  const batch = pendingLogs;
+  pendingLogs = [];
+  const { isNewTag, isNewTerminal } = appendEntries(batch, logArea);
+  if (isNewTag) renderTagbar();
+  if (isNewTerminal || state.terminals.size > 0) renderTermbar();
+  countEl.textContent = state.logs.length;
+}
+
+// Backend ids are consecutive; a jump not covered by dropped_before means
+// events were lost between the backend and here
+let lastLogId = null;
+let hiddenByFilter = 0;
+let lostEvents = 0;
+
+function trackGap(entry) {
+  hiddenByFilter += entry.dropped_before || 0;
+  if (lastLogId !== null && entry.id > lastLogId) {
+    lostEvents += Math.max(0, entry.id - lastLogId - 1 - (entry.dropped_before || 0));
+  }
+  lastLogId = entry.id;
+  const parts = [];
+  if (hiddenByFilter) parts.push(`${hiddenByFilter} hidden by filter`);
+  if (lostEvents) parts.push(`${lostEvents} lost`);
+  $('#gapInfo').textContent = parts.length ? ` · ${parts.join(' · ')}` : '';
+}
+
 listen('rtt-log', e => {
+  trackGap(e.payload);
+  if (refreshMs > 0 || pendingLogs.length) {
+    pendingLogs.push(e.payload);
+    if (pendingLogs.length >= batchSize) {
+      clearTimeout(flushTimer);
+      flushPendingLogs();
+    } else {
+      flushTimer ??= setTimeout(flushPendingLogs, refreshMs);
+    }
+    return;
+  }
   const { isNewTag, isNewTerminal } = appendEntry(e.payload, logArea);
   if (isNewTag) renderTagbar();
   if (isNewTerminal) renderTermbar();
@@ -282,16 +1202,85 @@ listen('rtt-log', e => {
   countEl.textContent = state.logs.length;
 });
 
+// Up buffer fill: show the fullest channel, all of them on hover
+const FILL_WARN_PERCENT = 75;
+
+function renderFill(fill) {
+  const el = $('#fillInfo');
+  if (!fill?.length) { el.textContent = ''; el.title = ''; return; }
+  const pct = f => f.size ? Math.round(f.used * 100 / f.size) : 0;
+  const label = f => (f.core ? f.core + ' ' : '') + (f.name || 'ch' + f.channel);
+  const worst = fill.reduce((a, b) => pct(b) > pct(a) ? b : a);
+  el.textContent = ` · buffer ${pct(worst)}%`;
+  el.title = fill.map(f => `${label(f)}: ${f.used}/${f.size} bytes (${pct(f)}%)`).join('\n');
+  el.classList.toggle('warn', pct(worst) >= FILL_WARN_PERCENT);
+}
+
+listen('rtt-stats', e => {
+  refreshMs = e.payload.refresh_ms ?? 0;
+  batchSize = e.payload.batch_size || Infinity;
+  renderFill(e.payload.channel_fill);
+});
+
+// A boot-looping target: one counter instead of a status line per reset
+function renderBootLoop(status) {
+  const el = $('#bootLoopInfo');
+  el.textContent = status?.resets ? ` · boot loop ×${status.resets}` : '';
+  el.title = status?.resets ? `${status.resets} resets in ${status.elapsed_s}s` : '';
+}
+
+listen('rtt-boot-loop', e => renderBootLoop(e.payload));
+
+function endOfStream() {
+  refreshMs = 0;
+  renderFill(null);
+  renderBootLoop(null);
+  flushPendingLogs();
+}
+
+// App status messages land here, apart from the device's log
+const STATUS_HISTORY = 50;
+const statusHistory = [];
+listen('rtt-status', e => {
+  const { level, message, host_timestamp } = e.payload;
+  const bar = $('#statusBar');
+  statusHistory.push(`${new Date(host_timestamp).toLocaleTimeString()} [${level}] ${message}`);
+  if (statusHistory.length > STATUS_HISTORY) statusHistory.shift();
+  bar.textContent = message;
+  bar.title = 'Click for recent status messages';
+  bar.className = `statusbar open level-${level}`;
+});
+$('#statusBar').addEventListener('click', () => {
+  if (statusHistory.length) alert(statusHistory.join('\n'));
+});
+
 listen('rtt-connected', () => { dot.className = 'dot on'; });
-listen('rtt-disconnected', () => { source = null; updateUI(); });
-listen('rtt-stopped', () => { source = null; updateUI(); });
+// The trigger fired and the context after it is in: save the window
+listen('flight-recorder', e => { if (e.payload.kind === 'ready') dumpFlightRecorder(); });
+listen('rtt-disconnected', () => { endOfStream(); gdbServerAddr = null; source = null; watches.clear(); renderWatchbar(); setTriggerArmed(false); updateUI(); });
+listen('rtt-stopped', () => { endOfStream(); gdbServerAddr = null; source = null; updateUI(); });
 listen('rtt-error', e => {
   dot.className = 'dot err';
-  alert('RTT Error: ' + e.payload);
   source = null;
   updateUI();
+  if (e.payload?.kind === 'Protected') offerRecover(e.payload);
+  else alert('RTT Error: ' + errorMessage(e.payload));
 });
 
+// A read-back protected nRF can't be attached until it's erased
+async function offerRecover(err) {
+  const prof = Profiles.getProfiles().find(p => p.name === activeProfileName);
+  if (!prof) { alert(errorMessage(err)); return; }
+  const ok = confirm(`${errorMessage(err)}\n\nRecover ${prof.chip} now? This ERASES the whole chip: flash, UICR and RAM.`);
+  if (!ok) return;
+  const probeIdx = probeSelect.value !== '' ? parseInt(probeSelect.value) : null;
+  try {
+    alert(await invoke('recover_target', { chip: prof.chip, probeIndex: probeIdx }));
+  } catch (e) {
+    alert(errorMessage(e));
+  }
+}
+
 // -- Search --
 
 const MODES = ['find', 'regex', 'filter'];
@@ -305,9 +1294,28 @@ function cycleSearchMode() {
   btnSearchMode.className = state.searchMode === 'filter' ? 'btn active' : 'btn';
   searchInput.placeholder = MODE_PLACEHOLDERS[state.searchMode];
   applySearch();
+  scheduleUiSave();
+}
+
+// Big captures search through the backend index instead of scanning every line
+const INDEX_SEARCH_MIN_LOGS = 50000;
+let searchGen = 0;
+
+async function searchViaIndex(query) {
+  const gen = ++searchGen;
+  let ids;
+  try {
+    ids = await invoke('search_logs', { query, regex: false });
+  } catch (e) {
+    updateSearchMatches();
+    return;
+  }
+  if (gen !== searchGen) return; // superseded by a newer search
+  state.searchMatches = ids.map(findEntry).filter(e => e && matches(e)).map(e => e.id);
+  state.searchCurrent = -1;
 }
 
-function applySearch() {
+async function applySearch() {
   const val = searchInput.value.trim();
   if (state.searchMode === 'find') {
     // Plain text — escape for regex
@@ -317,7 +1325,12 @@ function applySearch() {
   } else {
     setSearch(val);
   }
-  updateSearchMatches();
+  if (val && state.searchMode === 'find' && indexComplete && state.logs.length >= INDEX_SEARCH_MIN_LOGS) {
+    await searchViaIndex(val);
+  } else {
+    searchGen++;
+    updateSearchMatches();
+  }
   rebuild(logArea);
   updateSearchInfo();
 
@@ -359,6 +1372,24 @@ function navigateMatch(dir) {
 }
 
 btnSearchMode.addEventListener('click', cycleSearchMode);
+
+// -- Time base --
+
+async function setTimeBase(base) {
+  try {
+    await invoke('set_time_base', { base });
+  } catch (e) {
+    console.warn('set_time_base failed:', errorMessage(e));
+    return;
+  }
+  state.timeBase = base;
+  btnTimeBase.textContent = base === 'host' ? 'Host' : 'Dev';
+  btnTimeBase.className = base === 'host' ? 'btn active' : 'btn';
+  rebuild(logArea);
+  scheduleUiSave();
+}
+
+btnTimeBase.addEventListener('click', () => setTimeBase(state.timeBase === 'host' ? 'device' : 'host'));
 btnPrev.addEventListener('click', () => navigateMatch(-1));
 btnNext.addEventListener('click', () => navigateMatch(1));
 
@@ -396,6 +1427,20 @@ document.addEventListener('keydown', e => {
 
 await Profiles.init();
 await refreshProbes();
+await restoreUiState();
+await restoreFilterPreset();
+$('#profileSelect').addEventListener('change', scheduleUiSave);
+probeSelect.addEventListener('change', scheduleUiSave);
 renderTagbar();
 renderTermbar();
-updateUI();
\ No newline at end of file
+updateUI();
+
+// Session requested on the command line (--profile, --chip, --mock)
+const startup = await invoke('take_startup').catch(() => null);
+if (startup?.mock) {
+  await startMock(startup.mock_jitter);
+} else if (startup?.profile) {
+  $('#profileSelect').value = startup.profile.name;
+  if (startup.probe_index != null) probeSelect.value = startup.probe_index;
+  await startRtt(startup.profile, startup.probe_index);
+}
\ No newline at end of file
@@ -8,6 +8,11 @@ export function tagColor(t) {
     return tagColorMap[t];
 }
 
+// Backend sends a stable palette index per tag; prefer it over first-seen order
+export function rememberTagColor(e) {
+    if (e.tag && e.tag_color != null) tagColorMap[e.tag] = TAG_COLORS[e.tag_color % TAG_COLORS.length];
+}
+
 export function terminalColor(n) {
     return TERMINAL_COLORS[n % TERMINAL_COLORS.length];
 }
@@ -19,23 +24,43 @@ export const state = {
     excludedTags: new Set(),
     enabledLevels: new Set(['error', 'warn', 'info', 'debug', 'raw']),
     searchRe: null,
+    fieldQuery: null,       // { key, value } when a filter looks like key=value
     autoScroll: true,
     terminals: new Map(), // id -> count
+    terminalNames: new Map(), // id -> Profile.terminal_names label
     activeTerminals: null, // null = all, Set = specific
+    activeBlock: null,     // null = all, else a control block label (entries' core_name)
     searchMode: 'find',    // 'find' | 'regex' | 'filter'
     searchMatches: [],      // indices into logs[]
     searchCurrent: -1,      // index into searchMatches[]
+    timeBase: 'device',     // 'device' | 'host', mirrors the backend's set_time_base
 };
 
+// Backend errors arrive as { kind, message }; plain JS errors and strings still work
+export function errorMessage(e) {
+    if (e && typeof e === 'object' && 'message' in e) return e.message;
+    return String(e);
+}
+
 export function esc(s) {
     return s.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;');
 }
 
+// Filter mode: "key=value" also matches a parsed field exactly (quotes and
+// escapes resolved), and "key=" any line that has the field
+function fieldMatches(e) {
+    const q = state.fieldQuery;
+    if (!q || !e.fields || !(q.key in e.fields)) return false;
+    return q.value === '' || e.fields[q.key] === q.value;
+}
+
 export function matches(e) {
+    if (e.level === 'marker' || e.level === 'snapshot') return true; // user-requested, never filtered out
     if (!state.enabledLevels.has(e.level)) return false;
     if (e.tag && state.excludedTags.has(e.tag)) return false;
     if (state.activeTags.size > 0 && !state.activeTags.has(e.tag)) return false;
-    if (state.searchMode === 'filter' && state.searchRe && !state.searchRe.test(e.raw)) return false;
+    if (state.searchMode === 'filter' && state.searchRe && !fieldMatches(e) && !state.searchRe.test(e.raw)) return false;
+    if (state.activeBlock !== null && e.core_name && e.core_name !== state.activeBlock) return false;
     if (state.activeTerminals !== null) {
         const t = e.terminal ?? 0;
         if (!state.activeTerminals.has(t)) return false;
@@ -43,27 +68,55 @@ export function matches(e) {
     return true;
 }
 
+function hostTime(ms) {
+    const d = new Date(ms);
+    const p = (n, w = 2) => String(n).padStart(w, '0');
+    return `${p(d.getHours())}:${p(d.getMinutes())}:${p(d.getSeconds())}.${p(d.getMilliseconds(), 3)}`;
+}
+
 export function renderLine(e) {
-    let msg = esc(e.message);
-    if (state.searchRe) msg = msg.replace(state.searchRe, '<span class="hl">$&</span>');
+    const hl = s => state.searchRe ? esc(s).replace(state.searchRe, '<span class="hl">$&</span>') : esc(s);
+    let msg;
+    if (e.continuation_lines) {
+        // Backtrace or dump folded into this entry: first line visible, the rest on demand
+        const nl = e.message.indexOf('\n');
+        msg = `<details class="cont"><summary>${hl(e.message.slice(0, nl))} <span class="cont-count">+${e.continuation_lines}</span></summary>${hl(e.message.slice(nl + 1))}</details>`;
+    } else {
+        msg = hl(e.message);
+    }
     const c = e.tag ? tagColor(e.tag) : '#666';
     const tagH = e.tag
         ? `<span class="tag" style="background:${c}18;color:${c}">${esc(e.tag)}</span>`
         : '<span class="tag"></span>';
-    const ts = e.device_timestamp
-        ? `<span class="ts">${esc(e.device_timestamp)}</span>`
+    const tsText = state.timeBase === 'host' && e.host_timestamp != null
+        ? hostTime(e.host_timestamp)
+        : e.device_timestamp;
+    const tip = [];
+    if (e.delta_us != null) tip.push(`+${(e.delta_us / 1000).toFixed(3)} ms`);
+    if (e.host_wallclock) tip.push(`host ${e.host_wallclock}`);
+    const delta = tip.length ? ` title="${tip.join(' · ')}"` : '';
+    const ts = tsText
+        ? `<span class="ts"${delta}>${esc(tsText)}</span>`
         : '<span class="ts"></span>';
     const termId = e.terminal ?? 0;
     const tc = terminalColor(termId);
-    const termH = `<span class="term" style="color:${tc}">${termId}</span>`;
+    const termH = e.terminal_name
+        ? `<span class="term" style="color:${tc}" title="Terminal ${termId}">${esc(e.terminal_name)}</span>`
+        : `<span class="term" style="color:${tc}">${termId}</span>`;
+    const coreH = e.core_name ? `<span class="core core-${e.core_name}">${esc(e.core_name)}</span>` : '';
 
     const isMatch = state.searchMode !== 'filter' && state.searchMatches.includes(e.id);
     const isCurrent = isMatch && state.searchMatches[state.searchCurrent] === e.id;
     let cls = `log-line level-${e.level}`;
     if (isMatch) cls += ' search-match';
     if (isCurrent) cls += ' search-current';
+    if (e.backlog) cls += ' backlog';
+    if (e.partial) cls += ' partial';
+    if (e.burst_start) cls += ' burst-start';
+
+    const hexTitle = e.raw_hex ? ` title="${e.raw_hex}"` : e.partial ? ' title="First line after connecting; may be cut off"' : '';
 
-    return `<div class="${cls}" data-id="${e.id}"><span class="seq">${e.id}</span>${termH}${ts}<span class="lvl ${e.level}">${e.level.substring(0, 3)}</span>${tagH}<span class="msg">${msg}</span></div>`;
+    return `<div class="${cls}" data-id="${e.id}"><span class="seq">${e.id}</span>${coreH}${termH}${ts}<span class="lvl ${e.level}">${e.level.substring(0, 3)}</span>${tagH}<span class="msg"${hexTitle}>${msg}</span></div>`;
 }
 
 export function updateSearchMatches() {
@@ -77,6 +130,17 @@ export function updateSearchMatches() {
   }
 }
 
+// Entries arrive in id order, so one can be found by bisecting
+export function findEntry(id) {
+  let lo = 0, hi = state.logs.length;
+  while (lo < hi) {
+    const mid = (lo + hi) >>> 1;
+    if (state.logs[mid].id < id) lo = mid + 1; else hi = mid;
+  }
+  const e = state.logs[lo];
+  return e && e.id === id ? e : null;
+}
+
 export function setSearchMode(mode) {
   state.searchMode = mode;
 }
@@ -87,7 +151,37 @@ export function rebuild(logArea) {
 }
 
 export function appendEntry(e, logArea) {
+    const res = addEntry(e);
+    if (matches(e)) {
+        const empty = logArea.querySelector('.empty');
+        if (empty) empty.remove();
+        logArea.insertAdjacentHTML('beforeend', renderLine(e));
+        if (state.autoScroll) logArea.scrollTop = logArea.scrollHeight;
+    }
+    return res;
+}
+
+// Same as appendEntry for each, with a single DOM insert and scroll
+export function appendEntries(entries, logArea) {
+    let isNewTag = false, isNewTerminal = false, html = '';
+    for (const e of entries) {
+        const res = addEntry(e);
+        isNewTag ||= res.isNewTag;
+        isNewTerminal ||= res.isNewTerminal;
+        if (matches(e)) html += renderLine(e);
+    }
+    if (html) {
+        const empty = logArea.querySelector('.empty');
+        if (empty) empty.remove();
+        logArea.insertAdjacentHTML('beforeend', html);
+        if (state.autoScroll) logArea.scrollTop = logArea.scrollHeight;
+    }
+    return { isNewTag, isNewTerminal };
+}
+
+function addEntry(e) {
     state.logs.push(e);
+    rememberTagColor(e);
     const isNewTag = e.tag && !state.tags.has(e.tag);
     if (e.tag) state.tags.add(e.tag);
 
@@ -97,15 +191,11 @@ export function appendEntry(e, logArea) {
         state.terminals.set(termId, 0);
         isNewTerminal = true;
     }
-    state.terminals.set(termId, state.terminals.get(termId) + 1);
-
-    if (matches(e)) {
-        const empty = logArea.querySelector('.empty');
-        if (empty) empty.remove();
-        logArea.insertAdjacentHTML('beforeend', renderLine(e));
-        if (state.autoScroll) logArea.scrollTop = logArea.scrollHeight;
+    if (e.terminal_name && state.terminalNames.get(termId) !== e.terminal_name) {
+        state.terminalNames.set(termId, e.terminal_name);
+        isNewTerminal = true;
     }
-
+    state.terminals.set(termId, state.terminals.get(termId) + 1);
     return { isNewTag, isNewTerminal };
 }
 
@@ -115,7 +205,9 @@ export function clearLogs(logArea) {
   state.activeTags.clear();
   state.excludedTags.clear();
   state.terminals.clear();
+  state.terminalNames.clear();
   state.activeTerminals = null;
+  state.activeBlock = null;
   state.searchRe = null;
   state.searchMatches = [];
   state.searchCurrent = -1;
@@ -123,6 +215,8 @@ export function clearLogs(logArea) {
 }
 
 export function setSearch(val) {
+    const kv = /^([\p{L}_][\p{L}\p{N}_.-]*)=(.*)$/u.exec(val || '');
+    state.fieldQuery = kv ? { key: kv[1], value: kv[2] } : null;
     try {
         state.searchRe = val ? new RegExp(val, 'gi') : null;
     } catch {
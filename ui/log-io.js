@@ -1,6 +1,6 @@
 // Log import/export — JSON, CSV, plain text
 
-import { state, rebuild, appendEntry, clearLogs, esc } from './log-engine.js';
+import { state, rebuild, appendEntry, clearLogs, esc, rememberTagColor } from './log-engine.js';
 
 const invoke = window.__TAURI__.core.invoke;
 
@@ -41,10 +41,15 @@ function logsToCSV(logs) {
     return [header, ...rows].join('\n');
 }
 
+// Same as MARKER_PREFIX in main.rs
+const MARKER_PREFIX = '>>> MARKER:';
+
 function logsToText(logs) {
     return logs.map(e => {
+        if (e.level === 'marker') return `${String(e.id).padStart(5)} ${MARKER_PREFIX} ${e.message}`;
         const parts = [];
         if (e.id != null) parts.push(String(e.id).padStart(5));
+        if (e.core_name) parts.push(`[${e.core_name}]`);
         if (e.terminal != null) parts.push(`T${e.terminal}`);
         if (e.device_timestamp) parts.push(e.device_timestamp);
         parts.push(`[${(e.level || 'raw').toUpperCase().substring(0, 3)}]`);
@@ -56,7 +61,13 @@ function logsToText(logs) {
 
 const serializers = { json: logsToJSON, csv: logsToCSV, txt: logsToText };
 
-export async function exportLogs(format = 'json') {
+// Save dialog starting point from the profile's export folder and name template
+export function defaultExportPath(format, profile) {
+    return invoke('default_export_path', { profile: profile ?? null, format });
+}
+
+// lineEnding: 'lf' | 'crlf' | 'native'; lineEnding and asciiOnly are applied by the backend when saving
+export async function exportLogs(format = 'json', lineEnding = 'lf', profile = null, asciiOnly = false) {
     const fmt = FORMATS[format];
     if (!fmt) throw new Error(`Unknown format: ${format}`);
 
@@ -67,7 +78,7 @@ export async function exportLogs(format = 'json') {
     }
 
     const content = serializers[format](logs);
-    const defaultName = `rtt-logs-${timestamp()}.${fmt.ext}`;
+    const defaultName = await defaultExportPath(format, profile);
 
     if (save) {
         const path = await save({
@@ -75,12 +86,41 @@ export async function exportLogs(format = 'json') {
             filters: [{ name: fmt.name, extensions: [fmt.ext] }],
         });
         if (!path) return;
-        await invoke('write_text_file', { path, contents: content });
+        await invoke('write_text_file', { path, contents: content, lineEnding, asciiOnly });
     } else {
-        downloadBlob(content, defaultName, fmt.mime);
+        const crlf = lineEnding === 'crlf' || (lineEnding === 'native' && navigator.userAgent.includes('Windows'));
+        downloadBlob(crlf ? content.replace(/\r?\n/g, '\r\n') : content, defaultName, fmt.mime);
     }
 }
 
+// The backend writes the database; there's no browser fallback
+export async function exportSqlite(profile = null) {
+    if (state.logs.length === 0) {
+        alert('No logs to export');
+        return;
+    }
+    const defaultName = await defaultExportPath('sqlite', profile);
+    const path = save
+        ? await save({ defaultPath: defaultName, filters: [{ name: 'SQLite', extensions: ['db', 'sqlite'] }] })
+        : prompt('Save SQLite database to:', defaultName);
+    if (!path) return;
+    await invoke('export_logs_sqlite', { path, entries: state.logs });
+}
+
+// Standalone report for people without the viewer; rendered by the backend
+export async function exportHtml(profile = null, asciiOnly = false) {
+    if (state.logs.length === 0) {
+        alert('No logs to export');
+        return;
+    }
+    const defaultName = await defaultExportPath('html', profile);
+    const path = save
+        ? await save({ defaultPath: defaultName, filters: [{ name: 'HTML', extensions: ['html', 'htm'] }] })
+        : prompt('Save HTML report to:', defaultName);
+    if (!path) return;
+    await invoke('export_logs_html', { path, entries: state.logs, asciiOnly });
+}
+
 // ── Import ──
 
 function parseJSON(text) {
@@ -122,6 +162,12 @@ function parseText(text) {
         content = pm[2];
       }
 
+      // Our own marker line: SEQ >>> MARKER: text
+      const mk = content.match(/^\s*(\d+)?\s*>>> MARKER:\s*(.*)$/);
+      if (mk) {
+        return normalize({ id: mk[1] ? parseInt(mk[1]) : id++, terminal, level: 'marker', message: mk[2], raw: line });
+      }
+
       // Try Zephyr format: [00:29:56.296,813] <inf> ble_manager: IU 3 ON
       const zephyr = /^\[(\d{2}:\d{2}:\d{2}\.\d{3}(?:,\d{3})?)\]\s*<(\w+)>\s*([\w._-]+):\s*(.*)$/;
       const zm = content.match(zephyr);
@@ -151,17 +197,18 @@ function parseText(text) {
         });
       }
 
-      // Try: SEQ  Tn  TIMESTAMP  [LVL]  <TAG>  message (re-import of our own export)
-      const re = /^\s*(\d+)?\s*(?:T(\d+))?\s*([\d:.,]+)?\s*\[(\w+)\]\s*(?:<([^>]+)>)?\s*(.*)$/;
+      // Try: SEQ  [CORE]  Tn  TIMESTAMP  [LVL]  <TAG>  message (re-import of our own export)
+      const re = /^\s*(\d+)?\s*(?:\[(app|net)\])?\s*(?:T(\d+))?\s*([\d:.,]+)?\s*\[(\w+)\]\s*(?:<([^>]+)>)?\s*(.*)$/;
       const m = content.match(re);
       if (m) {
         return normalize({
           id: m[1] ? parseInt(m[1]) : id++,
-          terminal: m[2] ? parseInt(m[2]) : terminal,
-          device_timestamp: m[3] || null,
-          level: m[4],
-          tag: m[5] || null,
-          message: m[6] || '',
+          core_name: m[2] || null,
+          terminal: m[3] ? parseInt(m[3]) : terminal,
+          device_timestamp: m[4] || null,
+          level: m[5],
+          tag: m[6] || null,
+          message: m[7] || '',
           raw: line,
         });
       }
@@ -224,6 +271,7 @@ export async function importLogs(format, logArea, callbacks = {}) {
     let newTags = false, newTerminals = false;
     for (const e of entries) {
         state.logs.push(e);
+        rememberTagColor(e);
         if (e.tag && !state.tags.has(e.tag)) {
             state.tags.add(e.tag);
             newTags = true;
@@ -233,6 +281,7 @@ export async function importLogs(format, logArea, callbacks = {}) {
             state.terminals.set(termId, 0);
             newTerminals = true;
         }
+        if (e.terminal_name) state.terminalNames.set(termId, e.terminal_name);
         state.terminals.set(termId, state.terminals.get(termId) + 1);
     }
 
@@ -253,11 +302,20 @@ function normalize(obj) {
     return {
         id: obj.id != null ? Number(obj.id) : state.logs.length,
         terminal: obj.terminal != null ? Number(obj.terminal) : 0,
+        terminal_name: obj.terminal_name || null,
         device_timestamp: obj.device_timestamp || null,
-        level: ['error', 'warn', 'info', 'debug', 'raw'].includes(level) ? level : 'raw',
+        level: ['error', 'warn', 'info', 'debug', 'raw', 'marker'].includes(level) ? level : 'raw',
         tag: obj.tag || null,
+        tag_color: obj.tag_color != null && obj.tag_color !== '' ? Number(obj.tag_color) : null,
+        core_name: obj.core_name || null,
         message: obj.message || obj.raw || '',
         raw: obj.raw || obj.message || '',
+        host_timestamp: obj.host_timestamp != null ? Number(obj.host_timestamp) : null,
+        host_wallclock: obj.host_wallclock || null,
+        host_monotonic_ns: obj.host_monotonic_ns != null ? Number(obj.host_monotonic_ns) : null,
+        display_time: obj.display_time != null ? Number(obj.display_time) : null,
+        delta_us: obj.delta_us != null ? Number(obj.delta_us) : null,
+        fields: obj.fields && typeof obj.fields === 'object' ? obj.fields : {},
     };
 }
 
@@ -289,10 +347,6 @@ function detectFormatFromContent(text) {
     return 'txt';
 }
 
-function timestamp() {
-    return new Date().toISOString().replace(/[:.]/g, '-').substring(0, 19);
-}
-
 function downloadBlob(content, filename, mime) {
     const blob = new Blob([content], { type: mime });
     const url = URL.createObjectURL(blob);
@@ -1,5 +1,7 @@
 // Profile management — modals, ELF extraction, CRUD
 
+import { errorMessage } from './log-engine.js';
+
 const invoke = window.__TAURI__.core.invoke;
 
 let openDialog;
@@ -23,11 +25,14 @@ export async function init() {
 }
 
 function renderSelect() {
+  const selected = $('#profileSelect').value;
   $('#profileSelect').innerHTML = '<option value="">— profile —</option>' +
     profiles.map(p => {
       const coreStr = p.core ? ', core ' + p.core : '';
       return `<option value="${esc(p.name)}">${esc(p.name)} (${esc(p.chip)}${coreStr})</option>`;
     }).join('');
+  // Keep the selection across a duplicate or another profile's delete
+  if (profiles.some(p => p.name === selected)) $('#profileSelect').value = selected;
 }
 
 function esc(s) { return s.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;'); }
@@ -45,6 +50,7 @@ function renderProfileList() {
       <span class="prof-addr">${p.rtt_address ? esc(p.rtt_address) : 'scan RAM'}</span>
       <div class="prof-actions">
         <button class="prof-btn edit" data-name="${esc(p.name)}">edit</button>
+        <button class="prof-btn copy" data-name="${esc(p.name)}" title="Duplicate">copy</button>
         <button class="prof-btn del" data-name="${esc(p.name)}">✕</button>
       </div>
     </div>
@@ -54,6 +60,19 @@ function renderProfileList() {
     el.addEventListener('click', e => { e.stopPropagation(); openEditModal(el.dataset.name); });
   });
 
+  list.querySelectorAll('.prof-btn.copy').forEach(el => {
+    el.addEventListener('click', async e => {
+      e.stopPropagation();
+      const newName = prompt(`Name for the copy of "${el.dataset.name}":`, `${el.dataset.name} copy`)?.trim();
+      if (!newName) return;
+      try {
+        profiles = await invoke('duplicate_profile', { name: el.dataset.name, newName });
+      } catch (err) { alert(errorMessage(err)); return; }
+      renderProfileList();
+      renderSelect();
+    });
+  });
+
   list.querySelectorAll('.prof-btn.del').forEach(el => {
     el.addEventListener('click', async e => {
       e.stopPropagation();
@@ -67,9 +86,31 @@ function renderProfileList() {
 
 function openProfilesModal() {
   renderProfileList();
+  renderExamples();
   $('#profilesModal').classList.add('open');
 }
 
+async function renderExamples() {
+  try {
+    const examples = await invoke('list_example_profiles');
+    $('#exampleSelect').innerHTML = '<option value="">+ From an example board…</option>' +
+      examples.map(e => `<option value="${esc(e.profile.name)}" title="${esc(e.description)}">${esc(e.board)}</option>`).join('');
+  } catch (e) { console.warn('list_example_profiles failed:', errorMessage(e)); }
+}
+
+async function installExample(name) {
+  $('#exampleSelect').value = '';
+  try {
+    profiles = await invoke('install_example_profile', { name });
+    renderProfileList();
+    renderSelect();
+    // Examples have no RTT address or ELF yet; open it so those can be filled in
+    openEditModal(name);
+  } catch (e) {
+    alert(errorMessage(e));
+  }
+}
+
 function openEditModal(existingName) {
   editingProfile = existingName;
   const prof = existingName ? profiles.find(p => p.name === existingName) : null;
@@ -81,11 +122,47 @@ function openEditModal(existingName) {
   $('#profChip').value = prof ? prof.chip : '';
   $('#profCore').value = prof ? (prof.core || 0) : 0;
   $('#profAddr').value = prof ? (prof.rtt_address || '') : '';
+  $('#profIncludeHex').checked = !!prof?.include_hex;
+  $('#profAccessPort').value = prof?.access_port ?? '';
+  $('#profConnectSequence').value = prof?.connect_sequence || '';
+  $('#profReadStrategy').value = prof?.channel_read_strategy || '';
+  $('#profSpeed').value = prof?.speed_khz ?? '';
+  $('#profAutoTune').checked = !!prof?.auto_tune_speed;
+  $('#profProbeSerial').value = prof?.probe_serial || '';
+  $('#profIdleTimeout').value = prof?.idle_timeout_s ?? '';
+  $('#profRttGrace').value = prof?.rtt_init_grace_ms ?? '';
+  $('#profWatchProbe').checked = !!prof?.watch_probe_serial;
+  $('#profWatchElf').checked = !!prof?.watch_elf;
+  $('#profRttId').value = prof?.rtt_id || '';
+  $('#profTargetYaml').value = prof?.target_yaml || '';
+  $('#profBothCores').checked = !!prof?.both_cores;
+  $('#profKeepTag').checked = !!prof?.keep_tag_in_message;
+  $('#profMergeCont').checked = !!prof?.merge_continuations;
+  $('#profKeepPartial').checked = !!prof?.keep_partial_first_line;
+  $('#profKeepBlank').checked = !!prof?.keep_blank_lines;
+  $('#profQuietConnect').checked = !!prof?.quiet_connect;
+  $('#profShowDeviceId').checked = !!prof?.show_device_id;
+  $('#profBurstGap').value = prof?.burst_gap_ms ?? '';
+  $('#profBinaryChannels').value = (prof?.binary_channels ?? []).join(', ');
+  $('#profBase64Channels').value = (prof?.base64_channels ?? []).join(', ');
+  $('#profChannelCodecs').value = Object.entries(prof?.channel_codec ?? {}).map(([ch, codec]) => `${ch}=${codec}`).join(', ');
+  $('#profTerminalNames').value = Object.entries(prof?.terminal_names ?? {}).map(([t, name]) => `${t}=${name}`).join(', ');
+  $('#profExtraBlocks').value = Object.entries(prof?.extra_rtt_blocks ?? {}).map(([label, addr]) => `${label}=${addr}`).join(', ');
+  $('#profEncoding').value = prof?.encoding || '';
+  $('#profCorruptionPct').value = prof?.corruption_warn_percent ?? '';
+  $('#profSkipCorruption').checked = !!prof?.skip_corruption_check;
+  $('#profBacklogSymbol').value = prof?.backlog_symbol || '';
+  $('#profExportDir').value = prof?.export_dir || '';
+  $('#profExportTemplate').value = prof?.export_filename_template || '';
+  $('#profParseKv').checked = !!prof?.parse_kv;
+  $('#chipSuggestions').style.display = 'none';
+  renderChipPicker();
   $('#elfPath').textContent = prof?.elf_path || '';
   $('#elfSuccess').style.display = 'none';
   $('#elfError').style.display = 'none';
   $('#editError').style.display = 'none';
   $('#btnDeleteProfile').style.display = prof ? 'inline-block' : 'none';
+  renderRecentElfs();
 
   $('#profilesModal').classList.remove('open');
   $('#editModal').classList.add('open');
@@ -103,17 +180,62 @@ async function saveProfile() {
   const addr = $('#profAddr').value.trim() || null;
   const core = parseInt($('#profCore').value) || 0;
   const elfPath = $('#elfPath').textContent || null;
+  const includeHex = $('#profIncludeHex').checked;
+  const watchElf = $('#profWatchElf').checked;
+  const rttId = $('#profRttId').value.trim() || null;
+  const targetYaml = $('#profTargetYaml').value.trim() || null;
+  const bothCores = $('#profBothCores').checked;
+  const keepTag = $('#profKeepTag').checked;
+  const mergeCont = $('#profMergeCont').checked;
+  const keepPartial = $('#profKeepPartial').checked;
+  const keepBlank = $('#profKeepBlank').checked;
+  const quietConnect = $('#profQuietConnect').checked;
+  const showDeviceId = $('#profShowDeviceId').checked;
+  const burstGap = parseInt($('#profBurstGap').value) || null;
+  const binaryChannels = $('#profBinaryChannels').value.split(/[\s,]+/).filter(Boolean).map(Number).filter(n => Number.isInteger(n) && n >= 0);
+  const base64Channels = $('#profBase64Channels').value.split(/[\s,]+/).filter(Boolean).map(Number).filter(n => Number.isInteger(n) && n >= 0);
+  // heatshrink:W,L has its own comma, so match pairs rather than splitting
+  const channelCodec = Object.fromEntries([...$('#profChannelCodecs').value.matchAll(/(\d+)\s*=\s*([\w-]+(?::\s*\d+\s*,\s*\d+)?)/g)].map(m => [m[1], m[2]]));
+  const terminalNamePairs = $('#profTerminalNames').value.split(',').map(s => s.split('=').map(p => p.trim())).filter(([t, name]) => t && name);
+  const terminalNames = terminalNamePairs.length ? Object.fromEntries(terminalNamePairs) : null;
+  const extraBlocks = Object.fromEntries($('#profExtraBlocks').value.split(',').map(s => s.split('=').map(p => p.trim())).filter(([label, addr]) => label && addr));
+  const encoding = $('#profEncoding').value.trim() || null;
+  const corruptionPct = parseInt($('#profCorruptionPct').value) || null;
+  const skipCorruption = $('#profSkipCorruption').checked;
+  const backlogSymbol = $('#profBacklogSymbol').value.trim() || null;
+  const exportDir = $('#profExportDir').value.trim() || null;
+  const exportTemplate = $('#profExportTemplate').value.trim() || null;
+  const parseKv = $('#profParseKv').checked;
+  const accessPortStr = $('#profAccessPort').value.trim();
+  const accessPort = accessPortStr === '' ? null : parseInt(accessPortStr);
+  const connectSequence = $('#profConnectSequence').value || null;
+  const readStrategy = $('#profReadStrategy').value || null;
+  const speedKhz = parseInt($('#profSpeed').value) || null;
+  const autoTune = $('#profAutoTune').checked;
+  const probeSerial = $('#profProbeSerial').value.trim() || null;
+  const watchProbe = $('#profWatchProbe').checked;
+  const idleTimeout = parseInt($('#profIdleTimeout').value) || null;
+  // 0 is meaningful here: no grace period
+  const rttGrace = Number.isInteger(parseInt($('#profRttGrace').value)) ? parseInt($('#profRttGrace').value) : null;
+  // Keep fields this form doesn't edit
+  const prev = editingProfile ? profiles.find(p => p.name === editingProfile) : null;
 
   try {
+    // A changed name renames the profile rather than saving a second one
+    if (editingProfile && name !== editingProfile) {
+      profiles = await invoke('rename_profile', { old: editingProfile, new: name });
+      editingProfile = name;
+    }
     profiles = await invoke('save_profile', {
-      profile: { name, chip, rtt_address: addr, elf_path: elfPath, core }
+      profile: { ...prev, name, chip, rtt_address: addr, elf_path: elfPath, core, include_hex: includeHex, access_port: accessPort, watch_elf: watchElf, rtt_id: rttId, target_yaml: targetYaml, burst_gap_ms: burstGap, both_cores: bothCores, keep_tag_in_message: keepTag, merge_continuations: mergeCont, binary_channels: binaryChannels, base64_channels: base64Channels, encoding, backlog_symbol: backlogSymbol, parse_kv: parseKv, connect_sequence: connectSequence, corruption_warn_percent: corruptionPct, skip_corruption_check: skipCorruption, export_dir: exportDir, export_filename_template: exportTemplate, keep_partial_first_line: keepPartial, keep_blank_lines: keepBlank, speed_khz: speedKhz, auto_tune_speed: autoTune, terminal_names: terminalNames, probe_serial: probeSerial, watch_probe_serial: watchProbe, channel_codec: channelCodec, idle_timeout_s: idleTimeout, channel_read_strategy: readStrategy, extra_rtt_blocks: extraBlocks, rtt_init_grace_ms: rttGrace, quiet_connect: quietConnect, show_device_id: showDeviceId }
     });
     renderSelect();
     $('#profileSelect').value = name;
+    document.dispatchEvent(new CustomEvent('profile-saved', { detail: profiles.find(p => p.name === name) }));
     $('#editModal').classList.remove('open');
     openProfilesModal();
   } catch (e) {
-    $('#editError').textContent = e;
+    $('#editError').textContent = errorMessage(e);
     $('#editError').style.display = 'block';
   }
 }
@@ -127,30 +249,60 @@ async function deleteCurrentProfile() {
   openProfilesModal();
 }
 
-async function pickElf() {
-  $('#elfSuccess').style.display = 'none';
-  $('#elfError').style.display = 'none';
+async function renderRecentElfs() {
+  const sel = $('#recentElfs');
+  let recent = [];
+  try { recent = await invoke('recent_elfs'); } catch (e) {}
+  sel.innerHTML = '<option value="">— recent ELF files —</option>' +
+    recent.map(r => {
+      const when = r.mtime ? ' · ' + new Date(r.mtime * 1000).toLocaleString() : '';
+      return `<option value="${esc(r.path)}">${esc(r.path.split(/[\\/]/).pop())}${esc(when)}</option>`;
+    }).join('');
+  sel.style.display = recent.length ? 'block' : 'none';
+}
 
+async function pickElf() {
   let path;
   if (openDialog) {
     path = await openDialog({
       multiple: false,
-      filters: [{ name: 'ELF', extensions: ['elf', 'out', ''] }],
+      filters: [
+        { name: 'ELF', extensions: ['elf', 'out', ''] },
+        { name: 'Linker map', extensions: ['map'] },
+      ],
     });
   } else {
-    path = prompt('Enter full path to .elf file:');
+    path = prompt('Enter full path to .elf or .map file:');
   }
   if (!path) return;
+  await extractFrom(path);
+}
+
+async function extractFrom(path) {
+  $('#elfSuccess').style.display = 'none';
+  $('#elfError').style.display = 'none';
 
-  $('#elfPath').textContent = path;
+  // Map files only give us the address — keep the profile's ELF path untouched
+  const isMap = path.toLowerCase().endsWith('.map');
+  if (!isMap) $('#elfPath').textContent = path;
   $('#btnPickElf').textContent = 'Extracting…';
   $('#btnPickElf').disabled = true;
 
   try {
-    const info = await invoke('extract_rtt_address_from_elf', { elfPath: path });
+    const info = isMap
+      ? await invoke('extract_rtt_address_from_map', { mapPath: path })
+      : await invoke('extract_rtt_address_from_elf', { elfPath: path });
     $('#profAddr').value = info.rtt_address;
 
     let msg = `Found _SEGGER_RTT at ${info.rtt_address}`;
+    if (info.extra_rtt_addresses?.length) {
+      // Secure/non-secure images linked together: offer the other blocks too
+      if (!$('#profExtraBlocks').value.trim()) {
+        $('#profExtraBlocks').value = info.extra_rtt_addresses.map((addr, i) => `block${i + 2}=${addr}`).join(', ');
+      }
+      msg += ` and ${info.extra_rtt_addresses.join(', ')}`;
+    }
+    if (info.ram_region) msg += ` (RAM ${info.ram_region.start}+${info.ram_region.size})`;
     if (info.chip_hint) {
       msg += ` · Detected: ${info.chip_hint}`;
       // Auto-fill chip if empty
@@ -160,29 +312,114 @@ async function pickElf() {
     }
     $('#elfSuccess').textContent = msg;
     $('#elfSuccess').style.display = 'block';
+    if (!isMap) renderChipSuggestions(path);
   } catch (e) {
-    $('#elfError').textContent = '' + e;
+    if (e?.kind === 'Cancelled') return;
+    $('#elfError').textContent = errorMessage(e);
     $('#elfError').style.display = 'block';
   } finally {
     $('#btnPickElf').textContent = 'Browse for .elf file…';
     $('#btnPickElf').disabled = false;
+    if (!isMap) renderRecentElfs();
   }
 }
 
+// Ranked targets the ELF fits; picking one fills chip and core
+async function renderChipSuggestions(path) {
+  const sel = $('#chipSuggestions');
+  let suggestions = [];
+  try { suggestions = await invoke('suggest_chips_for_elf', { elfPath: path }); } catch (e) {}
+  sel.innerHTML = '<option value="">— suggested chips —</option>' +
+    suggestions.map((s, i) => {
+      const core = s.core ? `, core ${s.core}` : '';
+      return `<option value="${i}" title="${esc(s.reason)}">${esc(s.name)}${core} · ${Math.round(s.confidence * 100)}%</option>`;
+    }).join('');
+  sel.style.display = suggestions.length ? 'block' : 'none';
+  sel.onchange = () => {
+    const s = suggestions[sel.value];
+    if (!s) return;
+    $('#profChip').value = s.name;
+    $('#profCore').value = s.core;
+    $('#elfSuccess').textContent = `${s.name}: ${s.reason}`;
+    $('#elfSuccess').style.display = 'block';
+  };
+}
+
+// Vendor → family → variant browser over probe-rs's built-in targets
+let chipFamilies = null;
+async function renderChipPicker() {
+  const vendorSel = $('#chipVendor'), familySel = $('#chipFamily'), variantSel = $('#chipVariant');
+  familySel.style.display = variantSel.style.display = 'none';
+  if (!chipFamilies) {
+    try { chipFamilies = await invoke('list_chip_families'); } catch (e) { chipFamilies = []; }
+  }
+  const vendors = [...new Set(chipFamilies.map(f => f.vendor))];
+  vendorSel.innerHTML = '<option value="">— browse by vendor —</option>' +
+    vendors.map(v => `<option value="${esc(v)}">${esc(v)}</option>`).join('');
+  vendorSel.style.display = vendors.length ? 'block' : 'none';
+
+  let families = [];
+  vendorSel.onchange = () => {
+    families = chipFamilies.filter(f => f.vendor === vendorSel.value);
+    familySel.innerHTML = '<option value="">— family —</option>' +
+      families.map((f, i) => `<option value="${i}">${esc(f.family)} (${f.variants.length})</option>`).join('');
+    familySel.style.display = families.length ? 'block' : 'none';
+    variantSel.style.display = 'none';
+  };
+  familySel.onchange = () => {
+    const fam = families[familySel.value];
+    if (!fam) { variantSel.style.display = 'none'; return; }
+    variantSel.innerHTML = '<option value="">— chip —</option>' +
+      fam.variants.map(v => {
+        const cores = v.core_types.length ? ` · ${esc(v.core_types.join(', '))}` : '';
+        return `<option value="${esc(v.name)}">${esc(v.name)}${cores}</option>`;
+      }).join('');
+    variantSel.style.display = 'block';
+  };
+  variantSel.onchange = () => {
+    if (variantSel.value) $('#profChip').value = variantSel.value;
+  };
+}
+
+function closeEditModal() {
+  // Don't leave a big ELF parse running for a form that's gone
+  if ($('#btnPickElf').disabled) invoke('cancel_elf_parse');
+  $('#editModal').classList.remove('open');
+  openProfilesModal();
+}
+
 function bindEvents() {
  $('#btnProfiles').addEventListener('click', openProfilesModal);
   $('#btnAddProfile').addEventListener('click', () => openEditModal(null));
-  $('#editCancel').addEventListener('click', () => { $('#editModal').classList.remove('open'); openProfilesModal(); });
+  $('#exampleSelect').addEventListener('change', e => { if (e.target.value) installExample(e.target.value); });
+  $('#editCancel').addEventListener('click', closeEditModal);
   $('#editSave').addEventListener('click', saveProfile);
   $('#btnDeleteProfile').addEventListener('click', deleteCurrentProfile);
   $('#btnPickElf').addEventListener('click', pickElf);
+  $('#profAddr').addEventListener('blur', async () => {
+    const addr = $('#profAddr').value.trim();
+    if (!addr) return;
+    try {
+      await invoke('validate_rtt_address', { address: addr });
+      $('#editError').style.display = 'none';
+    } catch (e) {
+      $('#editError').textContent = errorMessage(e);
+      $('#editError').style.display = 'block';
+    }
+  });
+  $('#recentElfs').addEventListener('change', e => { if (e.target.value) extractFrom(e.target.value); });
 
   // Close buttons
   $('#closeProfilesModal').addEventListener('click', () => $('#profilesModal').classList.remove('open'));
-  $('#closeEditModal').addEventListener('click', () => { $('#editModal').classList.remove('open'); openProfilesModal(); });
+  $('#closeEditModal').addEventListener('click', closeEditModal);
+
+  window.__TAURI__.event.listen('elf-progress', e => {
+    if ($('#btnPickElf').disabled) $('#btnPickElf').textContent = `Extracting… ${e.payload.percent}%`;
+  });
 }
 
 export function closeModals() {
+  if ($('#btnPickElf').disabled) invoke('cancel_elf_parse');
   $('#editModal').classList.remove('open');
   $('#profilesModal').classList.remove('open');
 }
\ No newline at end of file